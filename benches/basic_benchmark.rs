@@ -1,4 +1,4 @@
-use container_image_dist_ref::ImgRef;
+use container_image_dist_ref::{batch::Columns, ImgRef};
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 fn parse_test_corpus(c: &mut Criterion) {
     const VALID: &str = include_str!("../tests/fixtures/references/valid/inputs.txt");
@@ -15,7 +15,41 @@ fn parse_test_corpus(c: &mut Criterion) {
             }
         })
     });
+    c.bench_function("entire_test_suite_is_valid", |b| {
+        b.iter(|| {
+            for input in inputs.iter() {
+                let _valid = ImgRef::is_valid(black_box(input));
+            }
+        })
+    });
+    let mut columns = Columns::with_capacity(inputs.len());
+    c.bench_function("entire_test_suite_batch", |b| {
+        b.iter(|| {
+            columns.clear();
+            columns.extend(black_box(&inputs).iter().copied());
+        })
+    });
+}
+
+fn parse_long_sections(c: &mut Criterion) {
+    let sha256 = format!("docker.io/library/alpine:3.19@sha256:{}", "a1".repeat(32));
+    let sha512 = format!("docker.io/library/alpine:3.19@sha512:{}", "b2".repeat(64));
+    let long_encoded = format!("registry.example.com/app@xxh3:{}", "c3".repeat(512));
+    let long_path = format!(
+        "registry.example.com/{}/{}@sha256:{}",
+        "abcdefghijklmnopqrstuvwxyz0123456789".repeat(3),
+        "0123456789abcdefghijklmnopqrstuvwxyz".repeat(3),
+        "d4".repeat(32)
+    );
+    let inputs = [sha256, sha512, long_encoded, long_path];
+    c.bench_function("long_digests_and_paths", |b| {
+        b.iter(|| {
+            for input in inputs.iter() {
+                let _parse = ImgRef::new(black_box(input));
+            }
+        })
+    });
 }
 
-criterion_group!(benches, parse_test_corpus);
+criterion_group!(benches, parse_test_corpus, parse_long_sections);
 criterion_main!(benches);
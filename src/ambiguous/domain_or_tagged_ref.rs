@@ -39,6 +39,7 @@ use crate::{
     path::PathSpan,
     span::{Lengthy, OptionallyZero},
     tag::TagSpan,
+    Limits,
 };
 use HostOrPathKind::{Any, Host, HostOrPath, IpV6, Path};
 use PortOrTagKind::Port;
@@ -63,8 +64,7 @@ impl Lengthy<'_, u16, NonZeroU16> for DomainOrRefSpan<'_> {
         match self {
             DomainOrRefSpan::Domain(d) => d.short_len(),
             DomainOrRefSpan::TaggedRef((left, right)) => {
-                let mut len =
-                    unsafe { NonZeroU16::new_unchecked(u8::from(left.short_len()) as u16) };
+                let mut len = left.short_len();
                 if let Some(tag) = right {
                     // safe to unwrap since left can be at most 255 and right can be at most 128
                     len = len.saturating_add(1); // add 1 for the leading ':'
@@ -80,25 +80,21 @@ impl Lengthy<'_, u16, NonZeroU16> for DomainOrRefSpan<'_> {
     }
 }
 
-#[inline]
-fn to_large_err(e: err::Error<u8>) -> err::Error<u16> {
-    e.into()
-}
-
 impl<'src> DomainOrRefSpan<'src> {
-    pub(crate) fn new(src: &'src str) -> Result<Self, Error> {
+    pub(crate) fn new(src: &'src [u8], limits: Limits) -> Result<Self, Error> {
         let left = HostOrPathSpan::new(src, HostOrPathKind::Any)?;
         let mut len = left.short_len().widen().upcast(); // current possible max: 255
-        let right = match src.as_bytes().get(len as usize) {
+        let right = match src.get(len as usize) {
             Some(b'/') | Some(b'@') | None => None,
             Some(b':') => {
                 len = len.saturating_add(1); // +1 for the ':'
-                let right = PortOrTagSpan::new(&src[len as usize..], Port).map_err(|e| {
-                    Error::at(
-                        len.saturating_add(e.index() as u16), // ok since len <= 256, so len + u8::MAX < u16::MAX
-                        e.kind(),
-                    )
-                })?;
+                let right =
+                    PortOrTagSpan::new(&src[len as usize..], Port, limits.tag()).map_err(|e| {
+                        Error::at(
+                            len.saturating_add(e.index() as u16), // ok since len <= 256, so len + u8::MAX < u16::MAX
+                            e.kind(),
+                        )
+                    })?;
                 Some(right)
             }
             Some(_) => {
@@ -110,12 +106,12 @@ impl<'src> DomainOrRefSpan<'src> {
         };
 
         len = len.saturating_add(right.map(|r| r.short_len().widen().upcast()).unwrap_or(0));
-        match src.as_bytes().get(len as usize) {
+        match src.get(len as usize) {
             Some(b'@') | None => {
                 // since the next section must be a digest, the right side must be a tag
-                let path = PathSpan::try_from(left).map_err(to_large_err)?;
+                let path = PathSpan::try_from(left)?;
                 let tag = if let Some(tag) = right {
-                    Some(tag.try_into().map_err(|e: err::Error<u8>| {
+                    Some(TagSpan::from_ambiguous(tag, limits.tag()).map_err(|e| {
                         Error::at(
                             path.short_len()
                                         .widen()
@@ -141,11 +137,10 @@ impl<'src> DomainOrRefSpan<'src> {
                         Path => {
                             // need to extend the path
                             let path = PathSpan::try_from(left)?
-                                .extend(&src[len as usize..])
-                                .map_err(to_large_err)?;
+                                .extend(&src[len as usize..], limits.name())?;
 
                             let tag = if let Some(t) = right {
-                                Some(t.try_into().map_err(|e: err::Error<u8>| {
+                                Some(TagSpan::from_ambiguous(t, limits.tag()).map_err(|e| {
                                     Error::at(
                                         path.short_len()
                                         .widen()
@@ -178,7 +173,7 @@ mod tests {
     use super::*;
     use crate::span::Lengthy;
     fn should_split(src: &str, left: &str, right: &str) {
-        let tag = DomainOrRefSpan::new(src);
+        let tag = DomainOrRefSpan::new(src.as_bytes(), Limits::DEFAULT);
         match tag {
             Ok(span) => match span {
                 DomainOrRefSpan::Domain(domain) => {
@@ -34,6 +34,7 @@ use crate::{
             HostOrPathInvalidChar as InvalidChar, HostOrPathInvalidComponentEnd, HostOrPathTooLong,
         },
     },
+    simd::{self, Class},
     span::{impl_span_methods_on_tuple, Length, Lengthy, ShortLength},
 };
 
@@ -78,7 +79,8 @@ impl From<Kind> for Scan {
     }
 }
 
-struct Scan(u8);
+/// The state of a scan over a single host-or-path component, packed into one byte.
+pub(crate) struct Scan(u8);
 impl Scan {
     /// the number of underscores
     const UNDERSCORE_COUNT: u8 = 0b0011; //     0b00000011;
@@ -88,9 +90,23 @@ impl Scan {
     const LAST_WAS_DASH: u8 = 1 << 5; //        0b00100000;
     const IPV6: u8 = 1 << 6; //              0b01000000;
 
+    /// consume one ascii character of a component, enforcing the constraint(s)
+    /// of the `Kind` the scan started with. Stopping characters (`:`, `/`, `@`)
+    /// must be handled by the caller.
+    pub(crate) fn update(&mut self, ascii_char: u8) -> Result<(), err::Kind> {
+        match ascii_char {
+            b'a'..=b'z' | b'0'..=b'9' => self.reset(),
+            b'A'..=b'Z' => self.set_upper(),
+            b'_' => self.add_underscore(),
+            b'.' => self.set_dot(),
+            b'-' => self.set_dash(),
+            _ => Err(InvalidChar),
+        }
+    }
+
     // setters -----------------------------------------------------------------
     // all of which are fallible
-    fn set_dot(&mut self) -> Result<(), err::Kind> {
+    const fn set_dot(&mut self) -> Result<(), err::Kind> {
         if self.last_was_dot() || self.last_was_dash() || self.underscore_count() > 0 {
             Err(HostOrPathInvalidComponentEnd)
         } else {
@@ -99,7 +115,7 @@ impl Scan {
         }
     }
 
-    fn set_dash(&mut self) -> Result<(), err::Kind> {
+    const fn set_dash(&mut self) -> Result<(), err::Kind> {
         if self.last_was_dot() || self.underscore_count() > 0 {
             Err(HostOrPathInvalidComponentEnd)
         } else {
@@ -116,7 +132,7 @@ impl Scan {
             self.reset()
         }
     }
-    fn set_underscore_count(&mut self, count: u8) -> Result<(), err::Kind> {
+    const fn set_underscore_count(&mut self, count: u8) -> Result<(), err::Kind> {
         match count {
             0..=2 => {
                 self.0 &= !Self::UNDERSCORE_COUNT; // clear the count
@@ -150,10 +166,10 @@ impl Scan {
         self.unset_last_was_dot();
         Ok(())
     }
-    fn unset_last_was_dot(&mut self) {
+    const fn unset_last_was_dot(&mut self) {
         self.0 &= !Self::LAST_WAS_DOT;
     }
-    fn unset_last_was_dash(&mut self) {
+    const fn unset_last_was_dash(&mut self) {
         self.0 &= !Self::LAST_WAS_DASH;
     }
 
@@ -163,7 +179,11 @@ impl Scan {
     }
 
     // getters -----------------------------------------------------------------
-    const fn has_upper(&self) -> bool {
+    /// whether the scan could stop here without leaving a dangling separator
+    pub(crate) const fn at_component_end(&self) -> bool {
+        !self.last_was_dash() && !self.last_was_dot() && self.underscore_count() == 0
+    }
+    pub(crate) const fn has_upper(&self) -> bool {
         self.0 & Self::HAS_UPPERCASE == Self::HAS_UPPERCASE
     }
     const fn last_was_dot(&self) -> bool {
@@ -175,7 +195,7 @@ impl Scan {
     const fn underscore_count(&self) -> u8 {
         self.0 & Self::UNDERSCORE_COUNT
     }
-    const fn has_underscore(&self) -> bool {
+    pub(crate) const fn has_underscore(&self) -> bool {
         self.0 & Self::HAS_UNDERSCORE == Self::HAS_UNDERSCORE
     }
 }
@@ -193,6 +213,12 @@ impl State {
             .ok_or(Error::at(self.len, HostOrPathTooLong))?;
         Ok(())
     }
+    /// skip over characters that wouldn't change the scan
+    const fn skip(&mut self, n: usize) {
+        #[allow(clippy::cast_possible_truncation)]
+        let n = n as u8; // safe since only the first u8::MAX characters are skipped
+        self.len = self.len.saturating_add(n);
+    }
     fn update_decider(&mut self) {
         self.deciding_char = self.deciding_char.or(Some(self.len));
     }
@@ -200,21 +226,16 @@ impl State {
         #[cfg(debug_assertions)]
         let _c = ascii_char as char;
 
-        match ascii_char {
-            b'a'..=b'z' | b'0'..=b'9' => self.scan.reset(),
-            b'A'..=b'Z' => self.scan.set_upper().map(|_| self.update_decider()),
-            b'_' => self.scan.add_underscore().map(|_| self.update_decider()),
-            b'.' => self.scan.set_dot(),
-            b'-' => self.scan.set_dash(),
-            _ => Err(InvalidChar),
+        self.scan
+            .update(ascii_char)
+            .map_err(|err_kind| Error::at(self.len, err_kind))?;
+        if matches!(ascii_char, b'A'..=b'Z' | b'_') {
+            self.update_decider();
         }
-        .map_err(|err_kind| Error::at(self.len, err_kind))
+        Ok(())
     }
     const fn check_component_end(&self) -> Result<(), Error> {
-        let ok = !self.scan.last_was_dash()
-            && !self.scan.last_was_dot()
-            && self.scan.underscore_count() == 0;
-        match ok {
+        match self.scan.at_component_end() {
             true => Ok(()),
             false => Err(Error::at(
                 self.len.saturating_sub(1),
@@ -261,7 +282,7 @@ impl<'src> HostOrPathSpan<'src> {
     }
 
     /// can return None if at EOF or the first character is a `/` or `@`
-    pub(crate) fn new(src: &'src str, kind: Kind) -> Result<Self, Error> {
+    pub(crate) fn new(src: &'src [u8], kind: Kind) -> Result<Self, Error> {
         let mut state = State {
             len: 0,
             scan: kind.into(), // <- scan's setters will enforce the kind's constraint(s)
@@ -269,7 +290,7 @@ impl<'src> HostOrPathSpan<'src> {
         };
         {
             // check the first character, if any
-            let c = src.bytes().next();
+            let c = src.first().copied();
             #[cfg(test)]
             let _c = c.map(|c| c as char);
             match c {
@@ -288,7 +309,14 @@ impl<'src> HostOrPathSpan<'src> {
             };
         };
 
-        for c in src.bytes() {
+        let bytes = src;
+        if bytes.len() >= simd::MIN_LEN {
+            // a leading run of lowercase alphanumerics can't change the scan, so
+            // skip it in bulk. Components can't be longer than u8::MAX characters.
+            let bounded = &bytes[..bytes.len().min(u8::MAX.into())];
+            state.skip(simd::prefix_len(bounded, Class::LOWER_ALNUM));
+        }
+        for &c in &bytes[usize::from(state.len)..] {
             #[cfg(debug_assertions)]
             let (_pre, _ch) = (DebugScan::from(&state.scan), c as char);
             match c {
@@ -340,10 +368,13 @@ impl<'src> HostOrPathSpan<'src> {
     clippy::indexing_slicing
 )]
 mod tests {
+    extern crate alloc;
+    use alloc::string::String;
+
     use super::*;
     use crate::span::Lengthy;
     fn should_parse(src: &str) -> super::HostOrPathSpan<'_> {
-        HostOrPathSpan::new(src, Kind::Any)
+        HostOrPathSpan::new(src.as_bytes(), Kind::Any)
             .map_err(|e| {
                 panic!(
                     "failed to parse {:?}: {:?} @ {} ({:?})",
@@ -376,7 +407,7 @@ mod tests {
     }
 
     fn should_fail_with(src: &str, err_kind: err::Kind, bad_char_index: u8) {
-        let err = super::HostOrPathSpan::new(src, Kind::Any)
+        let err = super::HostOrPathSpan::new(src.as_bytes(), Kind::Any)
             .map(|e| {
                 panic!(
                     "should have failed to parse {:?}: {:?} @ {}",
@@ -425,5 +456,13 @@ mod tests {
             HostOrPathInvalidComponentEnd,
             ("google.com.".len() - 1) as u8,
         );
+        let long = "abcdefghijklmnopqrstuvwxyz0123456789".repeat(8);
+        should_fail_with(&long[..256], HostOrPathTooLong, u8::MAX);
+        should_fail_with(&(String::from(&long[..40]) + "$"), InvalidChar, 40);
+        should_fail_with(
+            &(String::from(&long[..70]) + "."),
+            HostOrPathInvalidComponentEnd,
+            70,
+        );
     }
 }
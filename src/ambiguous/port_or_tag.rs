@@ -54,6 +54,8 @@ struct State {
     kind: Kind,
     /// can be 0, but only relevant when kind is `Kind::Tag`
     first_tag_char: u8,
+    /// the longest allowed tag
+    max_tag: u8,
 }
 impl State {
     fn update_kind(&mut self, other: Kind) -> Result<(), Error> {
@@ -69,13 +71,17 @@ impl State {
         Ok(())
     }
     fn advance(&mut self) -> Result<(), Error> {
-        if self.len >= nonzero!(u8, 129) && self.kind == Kind::Tag {
-            Error::at(self.len.upcast(), err::Kind::TagTooLong).into()
+        let too_long = match self.kind {
+            Kind::Tag => err::Kind::TagTooLong,
+            _ => err::Kind::PortTooLong,
+        };
+        if self.len.upcast() > self.max_tag && self.kind == Kind::Tag {
+            Error::at(self.len.upcast(), too_long).into()
         } else {
             self.len = self
                 .len
                 .checked_add(1)
-                .ok_or(Error::at(self.len.upcast(), err::Kind::PortTooLong))?;
+                .ok_or(Error::at(self.len.upcast(), too_long))?;
             Ok(())
         }
     }
@@ -100,9 +106,9 @@ impl<'src> PortOrTagSpan<'src> {
     /// Parse a port or tag from the start of a string.
     /// Does NOT include the leading colon.
     /// Can match an empty span if the first character in src is a `/` or `@`
-    /// Max length = 128, enforced in [`State::advance`]
-    pub(crate) fn new(src: &str, kind: Kind) -> Result<Self, Error> {
-        let mut bytes = src.bytes();
+    /// Tags longer than `max_tag` are rejected in [`State::advance`].
+    pub(crate) fn new(src: &[u8], kind: Kind, max_tag: u8) -> Result<Self, Error> {
+        let mut bytes = src.iter().copied();
 
         // the first character after the colon must be alphanumeric or an underscore
         let kind = match bytes.next() {
@@ -121,8 +127,9 @@ impl<'src> PortOrTagSpan<'src> {
             len: nonzero!(u8, 1),
             kind,
             first_tag_char: 0, // only set on transition from port to tag
-                               // and only used for providing an error index when
-                               // trying to cast back from tag to port
+            // and only used for providing an error index when
+            // trying to cast back from tag to port
+            max_tag,
         };
 
         for c in bytes {
@@ -142,8 +149,7 @@ impl<'src> PortOrTagSpan<'src> {
         }
         debug_assert!(state.len.as_usize() <= src.len());
         debug_assert!(if (state.len.as_usize()) < src.len() {
-            src.as_bytes()[state.len.as_usize()] == b'/'
-                || src.as_bytes()[state.len.as_usize()] == b'@'
+            src[state.len.as_usize()] == b'/' || src[state.len.as_usize()] == b'@'
         } else {
             true
         });
@@ -161,7 +167,7 @@ mod tests {
     use super::*;
     use crate::span::Lengthy;
     fn should_parse_as(src: &str, kind: Kind) {
-        let tag = PortOrTagSpan::new(src, kind);
+        let tag = PortOrTagSpan::new(src.as_bytes(), kind, crate::tag::MAX_LEN.get());
         match tag {
             Ok(tag) => {
                 assert_eq!(tag.span().span_of(src), src);
@@ -0,0 +1,203 @@
+//! # References or image IDs
+//! [`AnyRef`] mirrors `distribution/reference`'s [`ParseAnyReference`](https://github.com/distribution/reference/blob/v0.5.0/normalize.go#L180-L190),
+//! which accepts the `identifier` production of the grammar as well as image
+//! references:
+//! ```ebnf
+//! identifier           ::= [a-f0-9]{64}
+//! ```
+//! A bare identifier is an image ID, read as a `sha256` digest. A string that
+//! parses as a digest using one of the algorithms `go-digest` registers by
+//! default, `sha256`, `sha384` and `sha512`, is also an image ID. Anything else
+//! must be an image reference, so unlike [`AlgorithmRegistry::DEFAULT`], `blake3`
+//! digests aren't image IDs; `go-digest` only registers `blake3` in programs
+//! that import its `blake3` package.
+//! ```rust
+//! use container_image_dist_ref::{err, AnyRef};
+//! let hex = "09ca7e4eaa6e8ae9c7d261167129184883644d07dfba7cbfbc4c8a2e08360d5b";
+//! match AnyRef::new(hex).unwrap() {
+//!     AnyRef::Identifier(digest) => assert_eq!(digest.to_str(), format!("sha256:{hex}")),
+//!     AnyRef::Reference(_) => unreachable!(),
+//! }
+//! match AnyRef::new("alpine:3.14").unwrap() {
+//!     AnyRef::Reference(img) => assert_eq!(img.tag(), Some("3.14")),
+//!     AnyRef::Identifier(_) => unreachable!(),
+//! }
+//! let err = AnyRef::new(&format!("{hex}:latest")).err().unwrap();
+//! assert_eq!(err.kind(), err::Kind::PathIsIdentifier);
+//! ```
+
+use crate::{
+    digest::{
+        hash::OwnedDigest,
+        notation,
+        registry::{AlgorithmRegistry, Charset, Length, Rule},
+        Digest,
+    },
+    err, Error, ImgRef, Limits,
+};
+
+/// the algorithms `go-digest` registers by default
+const GO_DIGEST: AlgorithmRegistry<'static> = AlgorithmRegistry::new(&[
+    Rule::SHA256,
+    Rule::new("sha384", Charset::LowerHex, Length::Exact(96)),
+    Rule::SHA512,
+]);
+
+/// Either an image ID or an image reference.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnyRef<'src> {
+    /// An image ID: a bare 64-hex-digit identifier, stored as a `sha256`
+    /// digest, or a `sha256`, `sha384` or `sha512` digest.
+    Identifier(OwnedDigest),
+    /// An image reference that isn't an image ID.
+    Reference(ImgRef<'src>),
+}
+
+/// whether `src` matches `[a-f0-9]{64}`
+pub(crate) fn is_identifier(src: &[u8]) -> bool {
+    src.len() == 64 && src.iter().all(|c| matches!(c, b'a'..=b'f' | b'0'..=b'9'))
+}
+
+/// parse a reference the way `ParseNormalizedNamed` does, rejecting
+/// repository names that would be mistaken for image IDs
+pub(crate) fn normalized_name(src: &[u8], limits: Limits) -> Result<ImgRef<'_>, Error> {
+    let img = ImgRef::parse(src, limits, AlgorithmRegistry::DEFAULT)?;
+    if img.span.name.domain.is_none() && is_identifier(img.path_str().as_bytes()) {
+        return Error::at(0, err::Kind::PathIsIdentifier).into();
+    }
+    Ok(img)
+}
+
+impl<'src> AnyRef<'src> {
+    /// Parse an image ID or image reference.
+    pub fn new(src: &'src str) -> Result<Self, Error> {
+        Self::with_limits(src, Limits::DEFAULT)
+    }
+    /// Parse an image ID or image reference with custom limits on the length of
+    /// each section of a reference. See [`Limits`] for details.
+    pub fn with_limits(src: &'src str, limits: Limits) -> Result<Self, Error> {
+        Self::parse(src.as_bytes(), limits)
+    }
+    /// Parse an image ID or image reference from bytes without validating them as UTF-8.
+    pub fn from_bytes(src: &'src [u8]) -> Result<Self, Error> {
+        Self::parse(src, Limits::DEFAULT)
+    }
+    fn parse(src: &'src [u8], limits: Limits) -> Result<Self, Error> {
+        if is_identifier(src) {
+            let hex = crate::ascii::prefix(src, src.len());
+            return notation::from_hex("sha256", hex).map(Self::Identifier);
+        }
+        // like distribution/reference, fall back to parsing a reference when
+        // a digest is invalid or uses an unregistered algorithm
+        let registered = src
+            .iter()
+            .position(|&c| c == b':')
+            .and_then(|i| core::str::from_utf8(&src[..i]).ok())
+            .is_some_and(|algorithm| GO_DIGEST.get(algorithm).is_some());
+        if registered {
+            if let Some(digest) = Digest::parse(src, limits, GO_DIGEST)
+                .ok()
+                .and_then(|digest| OwnedDigest::copy_of(digest).ok())
+            {
+                return Ok(Self::Identifier(digest));
+            }
+        }
+        normalized_name(src, limits).map(Self::Reference)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    extern crate alloc;
+    use alloc::format;
+
+    use super::*;
+    use crate::{err::Kind, span::Lengthy};
+
+    const HEX: &str = "853ff93762a06ddbf722c4ebe9ddd66d8f63ddaea97f521c3ecc20da7c976020";
+
+    fn identifier(src: &str) -> OwnedDigest {
+        match AnyRef::new(src) {
+            Ok(AnyRef::Identifier(digest)) => digest,
+            _ => panic!("{src:?} should parse as an identifier"),
+        }
+    }
+    fn reference(src: &str) -> (Option<&str>, &str, Option<&str>) {
+        match AnyRef::new(src) {
+            Ok(AnyRef::Reference(img)) => (
+                img.span.name.domain.map(|d| &src[..d.len()]),
+                &src[img.span.path_range()],
+                img.span.tag_range().map(|r| &src[r]),
+            ),
+            _ => panic!("{src:?} should parse as a reference"),
+        }
+    }
+    fn err(src: &str) -> (u16, Kind) {
+        let e = AnyRef::new(src).err().unwrap();
+        (e.index(), e.kind())
+    }
+
+    #[test]
+    fn identifiers() {
+        let sha256 = format!("sha256:{HEX}");
+        assert_eq!(identifier(HEX).to_str(), sha256);
+        assert_eq!(identifier(&sha256).to_str(), sha256);
+        let sha512 = format!("sha512:{HEX}{HEX}");
+        assert_eq!(identifier(&sha512).to_str(), sha512);
+        let sha384 = format!("sha384:{HEX}{}", &HEX[..32]);
+        assert_eq!(identifier(&sha384).to_str(), sha384);
+    }
+
+    #[test]
+    fn copies_compare_equal() {
+        for src in [HEX, "alpine:3.14"] {
+            let parsed = AnyRef::new(src).unwrap();
+            let copy = parsed;
+            assert_eq!(copy, parsed);
+        }
+        assert_ne!(AnyRef::new(HEX).unwrap(), AnyRef::new("alpine").unwrap());
+        assert_eq!(
+            format!("{:?}", AnyRef::new("alpine").unwrap()),
+            r#"Reference(ImgRef("alpine"))"#
+        );
+    }
+
+    #[test]
+    fn references() {
+        assert_eq!(reference("alpine"), (None, "alpine", None));
+        assert_eq!(
+            reference("docker.io/library/alpine:3.14"),
+            (Some("docker.io"), "library/alpine", Some("3.14"))
+        );
+        // 65 or 63 hex digits aren't identifiers
+        let long = format!("{HEX}0");
+        assert_eq!(reference(&long), (None, long.as_str(), None));
+        assert_eq!(reference(&HEX[1..]), (None, &HEX[1..], None));
+        // identifiers are fine as repository names within a domain
+        let with_domain = format!("host.com/{HEX}");
+        assert_eq!(reference(&with_domain), (Some("host.com"), HEX, None));
+        // invalid digests fall back to parsing as a name and tag
+        assert_eq!(reference("sha256:abc"), (None, "sha256", Some("abc")));
+        // unregistered algorithms aren't image IDs
+        assert_eq!(
+            reference("md5:d41d8cd98f00b204e9800998ecf8427e"),
+            (None, "md5", Some("d41d8cd98f00b204e9800998ecf8427e"))
+        );
+        // neither is blake3, which go-digest doesn't register by default
+        let blake3 = format!("blake3:{HEX}");
+        assert_eq!(reference(&blake3), (None, "blake3", Some(HEX)));
+    }
+
+    #[test]
+    fn identifiers_as_repository_names() {
+        assert_eq!(err(&format!("{HEX}:latest")), (0, Kind::PathIsIdentifier));
+        assert_eq!(
+            err(&format!("{HEX}@sha256:{HEX}")),
+            (0, Kind::PathIsIdentifier)
+        );
+        // uppercase hex isn't an identifier, but isn't a valid name either
+        assert_eq!(err(&HEX.to_ascii_uppercase()).1, Kind::PathInvalidChar);
+        assert_eq!(err(""), (0, Kind::RefMissing));
+    }
+}
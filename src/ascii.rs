@@ -0,0 +1,95 @@
+//! # Parsing from bytes
+//! Every parser in this crate reads bytes and only accepts ascii, so the
+//! `from_bytes` constructors run the same parsers as the `&str` constructors
+//! directly over the bytes. Parsing stops at the first non-ascii byte with the
+//! same error the `&str` parsers report for a non-ascii character at that
+//! offset, and only the consumed prefix is ever borrowed as a `&str`.
+
+/// Borrow the first `len` bytes of `src`, which a parser accepted, as a `&str`.
+#[inline]
+pub(crate) fn prefix(src: &[u8], len: usize) -> &str {
+    let prefix = &src[..len];
+    debug_assert!(prefix.is_ascii(), "parsers only accept ascii: {prefix:?}");
+    // SAFETY: parsers only accept ascii bytes, which are valid UTF-8
+    unsafe { core::str::from_utf8_unchecked(prefix) }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    extern crate alloc;
+    use alloc::{string::String, vec::Vec};
+
+    use crate::{
+        digest::{algorithm::Algorithm, encoded::Encoded, Compliance, Digest},
+        domain::Domain,
+        err::Kind,
+        path::Path,
+        tag::Tag,
+        CanonicalImgRef, ImgRef,
+    };
+
+    type Outcome = Result<usize, (usize, Kind)>;
+
+    /// parse `src` as both a `&str` and `&[u8]`, returning the consumed length or the error
+    macro_rules! both {
+        ($t:ident, $len:expr, $src:expr $(, $arg:expr)?) => {{
+            let src: &str = $src;
+            let from_str: Outcome = $t::new(src $(, $arg)?)
+                .map($len)
+                .map_err(|e| (e.index().into(), e.kind()));
+            let from_bytes: Outcome = $t::from_bytes(src.as_bytes() $(, $arg)?)
+                .map($len)
+                .map_err(|e| (e.index().into(), e.kind()));
+            assert_eq!(from_str, from_bytes, "{}::from_bytes({src:?})", stringify!($t));
+        }};
+    }
+
+    fn should_match(src: &str) {
+        both!(ImgRef, |r| r.name().to_str().len(), src);
+        both!(CanonicalImgRef, |r| r.name().to_str().len(), src);
+        both!(Digest, |d| d.to_str().len(), src);
+        both!(Algorithm, |(a, _)| a.len(), src);
+        both!(Encoded, |e| e.to_str().len(), src, Compliance::Universal);
+        both!(Tag, |t| t.to_str().len(), src);
+        both!(Path, |p| p.to_str().len(), src);
+        both!(Domain, |d| d.len(), src);
+    }
+
+    #[test]
+    fn matches_str_parsers_on_corpus() {
+        let corpus: Vec<&str> = include_str!("../tests/fixtures/references/valid/inputs.txt")
+            .lines()
+            .chain(include_str!("../tests/fixtures/references/invalid/inputs.txt").lines())
+            .collect();
+        let mut src = String::new();
+        for line in corpus {
+            should_match(line);
+            // replace each character in turn with a non-ascii character
+            for (i, _) in line.char_indices() {
+                src.clear();
+                src.push_str(&line[..i]);
+                src.push('é');
+                src.push_str(&line[i + 1..]);
+                should_match(&src);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_utf8_at_first_non_ascii_byte() {
+        let err = ImgRef::from_bytes(b"host.com/re\xffpo:tag").err().unwrap();
+        assert_eq!((err.index(), err.kind()), (11, Kind::PathInvalidChar));
+        let err = Digest::from_bytes(b"sha256:\x80").err().unwrap();
+        assert_eq!((err.index(), err.kind()), (7, Kind::EncodedInvalidChar));
+        // the parser stops before reaching the non-ascii byte
+        assert_eq!(Tag::from_bytes(b"tag@\xff").unwrap().to_str(), "tag");
+    }
+
+    #[test]
+    fn long_prefixes() {
+        let long = String::from("a@a:") + &"0".repeat(3000) + "é";
+        should_match(&long);
+        should_match(&long[4..]);
+    }
+}
@@ -0,0 +1,246 @@
+//! # Batch parsing into columns
+//! Parse many references at once into a struct of arrays, one row per input.
+//! Each column is a flat slice of small integers that can be handed to a
+//! columnar data frame without constructing an [`ImgRef`](crate::ImgRef) per row.
+//!
+//! All offsets are byte offsets into each row's source string. Absent sections
+//! are represented by empty ranges, and every offset in a row that failed to
+//! parse is 0. Digest compliance and error kinds are stored as codes, with
+//! [`NONE`] for rows without a digest or error; map them back with
+//! [`Compliance::from_code`](crate::digest::Compliance::from_code) and
+//! [`err::Kind::from_code`](crate::err::Kind::from_code).
+//! ```rust
+//! use container_image_dist_ref::{
+//!     batch::{Columns, NONE},
+//!     digest::Compliance,
+//!     err,
+//! };
+//! let columns: Columns = ["host.com/repo:tag", "repo@sha256:", "x/y@algo:a_b"]
+//!     .into_iter()
+//!     .collect();
+//! assert_eq!(columns.len(), 3);
+//! assert_eq!(columns.domain_end(), &[8, 0, 1]);
+//! assert_eq!(columns.path_end(), &[13, 0, 3]);
+//! assert_eq!(columns.tag_start(), &[14, 0, 3]);
+//! assert_eq!(columns.tag_end(), &[17, 0, 3]);
+//! assert_eq!(columns.digest_start(), &[17, 0, 4]);
+//! assert_eq!(columns.digest_end(), &[17, 0, 12]);
+//! assert_eq!(columns.compliance(), &[NONE, NONE, Compliance::Oci.code()]);
+//! assert_eq!(columns.error_kind(), &[NONE, err::Kind::EncodedMissing.code(), NONE]);
+//! let kind = err::Kind::from_code(columns.error_kind()[1]);
+//! assert_eq!(kind, Some(err::Kind::EncodedMissing));
+//! assert_eq!(columns.error_index(), &[0, 12, 0]);
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{digest::registry::AlgorithmRegistry, span::Lengthy, Limits, RefSpan};
+
+/// The code in the `compliance` and `error_kind` columns for rows without a
+/// digest or without an error.
+pub const NONE: u8 = u8::MAX;
+
+/// Parse results for a batch of image references, stored column-wise.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Columns {
+    domain_end: Vec<u16>,
+    path_end: Vec<u16>,
+    tag_start: Vec<u16>,
+    tag_end: Vec<u16>,
+    digest_start: Vec<u16>,
+    digest_end: Vec<u16>,
+    compliance: Vec<u8>,
+    error_kind: Vec<u8>,
+    error_index: Vec<u16>,
+}
+
+/// narrow an offset into a successfully-parsed reference
+#[inline]
+#[allow(clippy::cast_possible_truncation)]
+const fn offset(i: usize) -> u16 {
+    i as u16 // safe since a valid reference is at most 255+1+128+1+255+1+1024 bytes long
+}
+
+impl Columns {
+    /// An empty batch.
+    pub const fn new() -> Self {
+        Self {
+            domain_end: Vec::new(),
+            path_end: Vec::new(),
+            tag_start: Vec::new(),
+            tag_end: Vec::new(),
+            digest_start: Vec::new(),
+            digest_end: Vec::new(),
+            compliance: Vec::new(),
+            error_kind: Vec::new(),
+            error_index: Vec::new(),
+        }
+    }
+    /// An empty batch with room for `rows` rows in every column.
+    pub fn with_capacity(rows: usize) -> Self {
+        Self {
+            domain_end: Vec::with_capacity(rows),
+            path_end: Vec::with_capacity(rows),
+            tag_start: Vec::with_capacity(rows),
+            tag_end: Vec::with_capacity(rows),
+            digest_start: Vec::with_capacity(rows),
+            digest_end: Vec::with_capacity(rows),
+            compliance: Vec::with_capacity(rows),
+            error_kind: Vec::with_capacity(rows),
+            error_index: Vec::with_capacity(rows),
+        }
+    }
+    /// The number of rows parsed so far.
+    pub const fn len(&self) -> usize {
+        self.error_kind.len()
+    }
+    /// Whether no rows have been parsed.
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Remove every row while keeping the allocated capacity.
+    pub fn clear(&mut self) {
+        self.domain_end.clear();
+        self.path_end.clear();
+        self.tag_start.clear();
+        self.tag_end.clear();
+        self.digest_start.clear();
+        self.digest_end.clear();
+        self.compliance.clear();
+        self.error_kind.clear();
+        self.error_index.clear();
+    }
+    /// Parse one image reference and append its row.
+    pub fn push(&mut self, src: &str) {
+        match RefSpan::new(src.as_bytes(), Limits::DEFAULT, AlgorithmRegistry::DEFAULT) {
+            Ok(span) => {
+                let path_end = offset(span.path_range().end);
+                let tag = span
+                    .tag_range()
+                    .map(|r| (offset(r.start), offset(r.end)))
+                    .unwrap_or((path_end, path_end));
+                let digest = span
+                    .digest_range()
+                    .map(|r| (offset(r.start), offset(src.len())))
+                    .unwrap_or((tag.1, tag.1));
+                self.domain_end
+                    .push(span.name.domain.map(|d| offset(d.len())).unwrap_or(0));
+                self.path_end.push(path_end);
+                self.tag_start.push(tag.0);
+                self.tag_end.push(tag.1);
+                self.digest_start.push(digest.0);
+                self.digest_end.push(digest.1);
+                self.compliance
+                    .push(span.digest.map(|d| d.compliance().code()).unwrap_or(NONE));
+                self.error_kind.push(NONE);
+                self.error_index.push(0);
+            }
+            Err(e) => {
+                self.domain_end.push(0);
+                self.path_end.push(0);
+                self.tag_start.push(0);
+                self.tag_end.push(0);
+                self.digest_start.push(0);
+                self.digest_end.push(0);
+                self.compliance.push(NONE);
+                self.error_kind.push(e.kind().code());
+                self.error_index.push(e.index());
+            }
+        }
+    }
+    /// The end of each row's domain, or 0 if there is no domain.
+    pub fn domain_end(&self) -> &[u16] {
+        &self.domain_end
+    }
+    /// The end of each row's path, which is also the end of its name.
+    pub fn path_end(&self) -> &[u16] {
+        &self.path_end
+    }
+    /// The start of each row's tag, after the leading `:`.
+    pub fn tag_start(&self) -> &[u16] {
+        &self.tag_start
+    }
+    /// The end of each row's tag. Equal to the tag's start if there is no tag.
+    pub fn tag_end(&self) -> &[u16] {
+        &self.tag_end
+    }
+    /// The start of each row's digest, after the leading `@`.
+    pub fn digest_start(&self) -> &[u16] {
+        &self.digest_start
+    }
+    /// The end of each row's digest. Equal to the digest's start if there is no digest.
+    pub fn digest_end(&self) -> &[u16] {
+        &self.digest_end
+    }
+    /// The [`Compliance::code`](crate::digest::Compliance::code) of each row's digest, or [`NONE`] if the row
+    /// has no digest.
+    pub fn compliance(&self) -> &[u8] {
+        &self.compliance
+    }
+    /// The [`err::Kind::code`](crate::err::Kind::code) of the error each row failed with, or [`NONE`]
+    /// if it parsed successfully.
+    pub fn error_kind(&self) -> &[u8] {
+        &self.error_kind
+    }
+    /// The byte index at which each row failed, or 0 if it parsed successfully.
+    pub fn error_index(&self) -> &[u16] {
+        &self.error_index
+    }
+}
+
+impl<'src> Extend<&'src str> for Columns {
+    fn extend<I: IntoIterator<Item = &'src str>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|src| self.push(src));
+    }
+}
+
+impl<'src> FromIterator<&'src str> for Columns {
+    fn from_iter<I: IntoIterator<Item = &'src str>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut columns = Self::with_capacity(iter.size_hint().0);
+        columns.extend(iter);
+        columns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{digest::Compliance, err, ImgRef};
+
+    #[test]
+    fn matches_img_ref_on_corpus() {
+        let inputs: Vec<&str> = include_str!("../tests/fixtures/references/valid/inputs.txt")
+            .lines()
+            .chain(include_str!("../tests/fixtures/references/invalid/inputs.txt").lines())
+            .collect();
+        let columns: Columns = inputs.iter().copied().collect();
+        assert_eq!(columns.len(), inputs.len());
+        for (row, src) in inputs.iter().enumerate() {
+            let slice = |start: &[u16], end: &[u16]| &src[start[row].into()..end[row].into()];
+            match ImgRef::new(src) {
+                Ok(img) => {
+                    let domain = img.domain().map(|d| d.to_str()).unwrap_or("");
+                    assert_eq!(&src[..columns.domain_end()[row].into()], domain);
+                    assert_eq!(&src[..columns.path_end()[row].into()], img.name().to_str());
+                    let tag = slice(columns.tag_start(), columns.tag_end());
+                    assert_eq!(tag, img.tag().unwrap_or(""));
+                    let digest = slice(columns.digest_start(), columns.digest_end());
+                    assert_eq!(digest, img.digest().map(|d| d.to_str()).unwrap_or(""));
+                    assert_eq!(
+                        Compliance::from_code(columns.compliance()[row]),
+                        img.digest().map(|d| d.compliance())
+                    );
+                    assert_eq!(columns.error_kind()[row], NONE);
+                }
+                Err(e) => {
+                    assert_eq!(
+                        err::Kind::from_code(columns.error_kind()[row]),
+                        Some(e.kind())
+                    );
+                    assert_eq!(columns.error_index()[row], e.index());
+                }
+            }
+        }
+    }
+}
@@ -49,16 +49,20 @@ fn try_add(a: NonZeroU8, b: u8) -> Result<NonZeroU8, Error> {
 pub const MAX_LEN: u8 = u8::MAX;
 
 impl<'src> AlgorithmSpan<'src> {
-    pub(crate) fn new(src: &'src str) -> Result<(Self, Compliance), Error> {
+    /// parse an algorithm of at most `max` characters
+    pub(crate) fn new(src: &'src [u8], max: u8) -> Result<(Self, Compliance), Error> {
         let (mut len, mut compliance) =
             component(src, Compliance::Universal)?.ok_or(Error::at(0, AlgorithmMissing))?;
         let max_len = src.len().try_into().unwrap_or(MAX_LEN);
         loop {
+            if u8::from(len) > max {
+                return Error::at(max.into(), err::Kind::AlgorithmTooLong).into();
+            }
             if u8::from(len) >= max_len {
                 break;
             } else {
                 // Note: using .get() seems to slow down performance here
-                match src.as_bytes()[u8::from(len) as usize] {
+                match src[u8::from(len) as usize] {
                     b':' => break,
                     b'+' | b'.' | b'_' | b'-' => {
                         len = try_add(len, 1)?; // consume the separator
@@ -74,8 +78,8 @@ impl<'src> AlgorithmSpan<'src> {
         }
         Ok((Self(ShortLength::from_nonzero(len)), compliance))
     }
-    fn from_exact_match(src: &'src str) -> Result<(Self, Compliance), Error> {
-        let (span, compliance) = Self::new(src)?;
+    fn from_exact_match(src: &'src [u8]) -> Result<(Self, Compliance), Error> {
+        let (span, compliance) = Self::new(src, MAX_LEN)?;
         if span.len() == src.len() {
             Ok((span, compliance))
         } else {
@@ -100,6 +104,7 @@ impl<'src> AlgorithmSpan<'src> {
 /// assert_eq!(algorithm.to_str(), "a+b");
 /// assert_eq!(algorithm.parts().collect::<Vec<_>>(), vec!["a", "b"]);
 /// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Algorithm<'src>(&'src str);
 #[allow(clippy::len_without_is_empty)]
 impl<'src> Algorithm<'src> {
@@ -115,12 +120,16 @@ impl<'src> Algorithm<'src> {
     /// Parse an algorithm from the start of the string. Parsing may not consume the entire string
     /// if it reaches a valid stopping point, i.e. `:`.
     pub fn new(src: &'src str) -> Result<(Self, Compliance), Error> {
-        let (span, compliance) = AlgorithmSpan::new(src)?;
-        Ok((Self(span.span_of(src)), compliance))
+        Self::from_bytes(src.as_bytes())
+    }
+    /// Parse an algorithm from the start of some bytes without validating them as UTF-8.
+    pub fn from_bytes(src: &'src [u8]) -> Result<(Self, Compliance), Error> {
+        let (span, compliance) = AlgorithmSpan::new(src, MAX_LEN)?;
+        Ok((Self(crate::ascii::prefix(src, span.len())), compliance))
     }
     /// checks that the entire source string is parsed.
     pub fn from_exact_match(src: &'src str) -> Result<(Self, Compliance), Error> {
-        let (span, compliance) = AlgorithmSpan::from_exact_match(src)?;
+        let (span, compliance) = AlgorithmSpan::from_exact_match(src.as_bytes())?;
         Ok((Self(span.span_of(src)), compliance))
     }
     pub(super) fn from_span(src: &'src str, span: AlgorithmSpan<'src>) -> Self {
@@ -157,10 +166,10 @@ const fn is_separator(c: u8) -> bool {
 
 /// match an algorithm component and return the length of the match, along
 /// with what standard(s) the component is compliant with.
-fn component(src: &str, compliance: Compliance) -> Result<Option<(NonZeroU8, Compliance)>, Error> {
+fn component(src: &[u8], compliance: Compliance) -> Result<Option<(NonZeroU8, Compliance)>, Error> {
     use Compliance::*;
-    let mut bytes = src.bytes();
-    let compliance = match bytes.next() {
+    let mut bytes = src.iter().copied();
+    let mut compliance = match bytes.next() {
         None => return Ok(None),
         Some(b'a'..=b'z') => Ok(compliance), // universally compatible first character
         Some(b'0'..=b'9') => {
@@ -196,7 +205,10 @@ fn component(src: &str, compliance: Compliance) -> Result<Option<(NonZeroU8, Com
             b'A'..=b'Z' => {
                 match compliance {
                     // uppercase letters are acceptable according to distribution/reference
-                    Distribution | Universal => Ok(()),
+                    Distribution | Universal => {
+                        compliance = Distribution;
+                        Ok(())
+                    }
                     // but not the OCI image spec
                     Oci => Err(InvalidOciAlgorithm),
                 }
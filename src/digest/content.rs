@@ -0,0 +1,229 @@
+//! # Content digests from registries
+//! Registries report the digest of the content they serve in a
+//! `Docker-Content-Digest` header, and OCI descriptors pair a `digest` with the
+//! content's `size`. [`ContentDigest`] parses either kind of digest and says
+//! whether its algorithm is registered, meaning `sha256` or `sha512`, or the
+//! digest is merely valid syntax. Digests using registered algorithms must
+//! follow their algorithm's rule. Unlike [`AlgorithmRegistry::DEFAULT`], this
+//! doesn't register `blake3`, so `blake3` digests are only valid syntax.
+//! ```rust
+//! use container_image_dist_ref::digest::{
+//!     content::{ContentDigest, Descriptor, Validity},
+//!     Digest,
+//! };
+//! let hex = "09ca7e4eaa6e8ae9c7d261167129184883644d07dfba7cbfbc4c8a2e08360d5b";
+//! let header = format!(" sha256:{hex}");
+//! let requested = format!("sha256:{hex}");
+//! let requested = Digest::new(&requested).unwrap();
+//! let digest = ContentDigest::from_response(&header, Some(requested)).unwrap();
+//! assert_eq!(digest.validity(), Validity::Registered);
+//! assert_eq!(digest.digest(), requested);
+//!
+//! let md5 = ContentDigest::new("md5:d41d8cd98f00b204e9800998ecf8427e").unwrap();
+//! assert_eq!(md5.validity(), Validity::Syntactic);
+//! assert!(md5.require_registered().is_err());
+//!
+//! let descriptor = Descriptor::new(requested.to_str(), 12).unwrap();
+//! assert_eq!(descriptor.size(), 12);
+//! assert!(Descriptor::new(requested.to_str(), -1).is_err());
+//! ```
+
+use super::{
+    registry::{AlgorithmRegistry, Rule},
+    Digest,
+};
+use crate::{err::Kind, Error, Limits};
+
+/// the algorithms registered for content digests
+const REGISTERED: AlgorithmRegistry<'static> =
+    AlgorithmRegistry::new(&[Rule::SHA256, Rule::SHA512]);
+
+/// convert an offset to an error index, saturating for absurd amounts of whitespace
+fn index(i: usize) -> u16 {
+    u16::try_from(i).unwrap_or(u16::MAX)
+}
+
+/// How strictly a [`ContentDigest`] was validated.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Validity {
+    /// The digest is valid syntax, but its algorithm isn't registered.
+    Syntactic,
+    /// The digest's algorithm is registered, and the encoded section follows
+    /// the algorithm's rule.
+    Registered,
+}
+
+/// A digest reported by a registry.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ContentDigest<'src> {
+    digest: Digest<'src>,
+    validity: Validity,
+}
+
+impl<'src> ContentDigest<'src> {
+    /// Parse a digest, such as a descriptor's `digest` field.
+    pub fn new(src: &'src str) -> Result<Self, Error> {
+        let digest = Digest::with_registry(src, Limits::DEFAULT, REGISTERED)?;
+        let validity = match REGISTERED.get(digest.algorithm().to_str()) {
+            Some(_) => Validity::Registered,
+            None => Validity::Syntactic,
+        };
+        Ok(Self { digest, validity })
+    }
+    /// Parse a `Docker-Content-Digest` header's value, ignoring surrounding
+    /// whitespace. If the request was pinned to a digest, the response's digest
+    /// must match it. Errors are reported at offsets within `value`.
+    pub fn from_response(value: &'src str, requested: Option<Digest<'_>>) -> Result<Self, Error> {
+        let trimmed = value.trim_start_matches([' ', '\t']);
+        let start = index(value.len() - trimmed.len());
+        let shift = |e: Error| Error::at(e.index().saturating_add(start), e.kind());
+        let result = Self::new(trimmed.trim_end_matches([' ', '\t'])).map_err(shift)?;
+        match requested {
+            Some(requested) if requested != result.digest => {
+                Error::at(start, Kind::ContentDigestMismatch).into()
+            }
+            _ => Ok(result),
+        }
+    }
+    /// The parsed digest.
+    pub const fn digest(&self) -> Digest<'src> {
+        self.digest
+    }
+    /// Whether the digest's algorithm is registered.
+    pub const fn validity(&self) -> Validity {
+        self.validity
+    }
+    /// Reject digests whose algorithm isn't registered.
+    pub fn require_registered(self) -> Result<Self, Error> {
+        match self.validity {
+            Validity::Registered => Ok(self),
+            Validity::Syntactic => Error::at(0, Kind::ContentDigestUnregistered).into(),
+        }
+    }
+}
+
+/// The `digest` and `size` of an OCI descriptor.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Descriptor<'src> {
+    digest: ContentDigest<'src>,
+    size: u64,
+}
+
+impl<'src> Descriptor<'src> {
+    /// Validate a descriptor's `digest` and `size` fields. The size is signed,
+    /// as the OCI image spec defines it, but can't be negative.
+    pub fn new(digest: &'src str, size: i64) -> Result<Self, Error> {
+        let digest = ContentDigest::new(digest)?;
+        let size = u64::try_from(size).map_err(|_| Error::at(0, Kind::DescriptorSizeNegative))?;
+        Ok(Self { digest, size })
+    }
+    /// The content's digest.
+    pub const fn digest(&self) -> ContentDigest<'src> {
+        self.digest
+    }
+    /// The content's size in bytes.
+    pub const fn size(&self) -> u64 {
+        self.size
+    }
+    /// Check that fetched content has the size the descriptor promised.
+    pub fn check_size(&self, actual: u64) -> Result<(), Error> {
+        if actual == self.size {
+            Ok(())
+        } else {
+            Error::at(0, Kind::DescriptorSizeMismatch).into()
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    extern crate alloc;
+    use alloc::format;
+
+    use super::*;
+
+    const HEX: &str = "853ff93762a06ddbf722c4ebe9ddd66d8f63ddaea97f521c3ecc20da7c976020";
+
+    fn err<T>(result: Result<T, Error>) -> (u16, Kind) {
+        let e = result.err().unwrap();
+        (e.index(), e.kind())
+    }
+
+    #[test]
+    fn validity() {
+        let sha256 = format!("sha256:{HEX}");
+        assert_eq!(
+            ContentDigest::new(&sha256).unwrap().validity(),
+            Validity::Registered
+        );
+        let sha512 = format!("sha512:{HEX}{HEX}");
+        let sha512 = ContentDigest::new(&sha512).unwrap();
+        assert_eq!(sha512.require_registered().unwrap(), sha512);
+        let unregistered = format!("sha384:{HEX}");
+        assert_eq!(
+            ContentDigest::new(&unregistered).unwrap().validity(),
+            Validity::Syntactic
+        );
+        // registries only have to support sha256 and sha512
+        let blake3 = format!("blake3:{HEX}");
+        assert_eq!(
+            ContentDigest::new(&blake3).unwrap().validity(),
+            Validity::Syntactic
+        );
+        // registered algorithms must follow their rules; like `Digest`, rule
+        // violations are reported at offsets within the encoded section
+        assert_eq!(
+            err(ContentDigest::new("sha256:abc")),
+            (3, Kind::OciRegisteredAlgorithmWrongDigestLength)
+        );
+        let upper = format!("sha256:{}", HEX.to_ascii_uppercase());
+        assert_eq!(
+            err(ContentDigest::new(&upper)),
+            (3, Kind::OciRegisteredDigestInvalidChar)
+        );
+    }
+
+    #[test]
+    fn responses() {
+        let sha256 = format!("sha256:{HEX}");
+        let requested = Digest::new(&sha256).unwrap();
+        let header = format!("\t{sha256} ");
+        let response = ContentDigest::from_response(&header, Some(requested)).unwrap();
+        assert_eq!(response.digest().to_str(), sha256);
+        assert!(ContentDigest::from_response(&header, None).is_ok());
+
+        let other = format!("sha256:{}1", &HEX[..63]);
+        let other = Digest::new(&other).unwrap();
+        assert_eq!(
+            err(ContentDigest::from_response(&header, Some(other))),
+            (1, Kind::ContentDigestMismatch)
+        );
+        assert_eq!(
+            err(ContentDigest::from_response("  sha256:", None)),
+            (9, Kind::EncodedMissing)
+        );
+        // offsets past lots of whitespace saturate rather than wrapping
+        let padded = format!("{}{sha256}", " ".repeat(70_000));
+        assert_eq!(
+            err(ContentDigest::from_response(&padded, Some(other))),
+            (u16::MAX, Kind::ContentDigestMismatch)
+        );
+    }
+
+    #[test]
+    fn descriptors() {
+        let sha256 = format!("sha256:{HEX}");
+        let descriptor = Descriptor::new(&sha256, 1024).unwrap();
+        assert_eq!(descriptor.digest().validity(), Validity::Registered);
+        assert!(descriptor.check_size(1024).is_ok());
+        assert_eq!(
+            err(descriptor.check_size(1023)),
+            (0, Kind::DescriptorSizeMismatch)
+        );
+        assert_eq!(
+            err(Descriptor::new(&sha256, -1)),
+            (0, Kind::DescriptorSizeNegative)
+        );
+    }
+}
@@ -20,8 +20,14 @@
 
 use core::num::NonZeroU16;
 
-use super::{algorithm::Algorithm, Compliance};
+use super::{
+    algorithm::Algorithm,
+    raw,
+    registry::{AlgorithmRegistry, Charset},
+    Compliance,
+};
 use crate::err;
+use crate::simd::{self, Class};
 use crate::span::{impl_span_methods_on_tuple, nonzero, Lengthy, LongLength};
 /// an arbitrary maximum length for the encoded section of a digest.
 /// This a realistic limit; hex-encoded sha512 digests are 128 characters long.
@@ -29,7 +35,7 @@ pub const MAX_LEN: u16 = 1024;
 
 use crate::err::Kind::{
     EncodedInvalidChar, EncodedNonLowerHex, EncodingTooLong, EncodingTooShort,
-    OciRegisteredAlgorithmWrongDigestLength, OciRegisteredDigestInvalidChar,
+    RegisteredAlgorithmNoncompliant,
 };
 type Error = crate::err::Error<u16>;
 
@@ -39,11 +45,20 @@ pub(crate) struct EncodedSpan<'src>(LongLength<'src>);
 impl_span_methods_on_tuple!(EncodedSpan, u16, NonZeroU16);
 impl<'src> EncodedSpan<'src> {
     #[allow(clippy::arithmetic_side_effects)]
-    pub(crate) fn new(src: &'src str, compliance: Compliance) -> Result<(Self, Compliance), Error> {
+    /// parse an encoded section of at most `max` characters
+    pub(crate) fn new(
+        src: &'src [u8],
+        compliance: Compliance,
+        max: u16,
+    ) -> Result<(Self, Compliance), Error> {
         use Compliance::*;
-        let mut len = 0;
+        let bytes = src;
+        // hex digits never change the compliance, so runs of them can be skipped in bulk
+        let hex = simd::prefix_len(&bytes[..bytes.len().min(max.into())], Class::HEX);
+        #[allow(clippy::cast_possible_truncation)]
+        let mut len = hex as u16; // safe since hex <= max
         let mut compliance = compliance;
-        for c in src.as_bytes() {
+        for c in &bytes[hex..] {
             compliance = match c {
                 b'a'..=b'f' | b'0'..=b'9' | b'A'..=b'F' => Ok(compliance), // hex digits are universally accepted
                 b'g'..=b'z' | b'G'..=b'Z' | b'=' | b'_' | b'-' => {
@@ -58,10 +73,10 @@ impl<'src> EncodedSpan<'src> {
                 _ => Err(EncodedInvalidChar),
             }
             .map_err(|kind| Error::at(len, kind))?;
-            if len >= MAX_LEN {
+            if len >= max {
                 return Error::at(len, EncodingTooLong).into();
             }
-            len = len.saturating_add(1); // safe since len < MAX_LEN < u16::MAX
+            len = len.saturating_add(1); // safe since len < max <= u16::MAX
         }
 
         debug_assert!(len as usize == src.len(), "must have consume all src");
@@ -74,6 +89,7 @@ impl<'src> EncodedSpan<'src> {
 
 /// The encoded portion of a digest string. This may not be a hex-encoded value,
 /// since the OCI spec allows for base64 encoding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Encoded<'src>(&'src str);
 impl<'src> Encoded<'src> {
     #[allow(missing_docs)]
@@ -84,54 +100,46 @@ impl<'src> Encoded<'src> {
     /// with respect to the given standard (Oci, Distribution, or Universal).
     /// Parsing always continues until the end of the string or an error.
     pub fn new(src: &'src str, compliance: Compliance) -> Result<Self, Error> {
-        let (span, _compliance) = EncodedSpan::new(src, compliance)?;
-        Ok(Self::from_span(src, span))
+        Self::from_bytes(src.as_bytes(), compliance)
+    }
+    /// Parse an encoded digest value from bytes without validating them as UTF-8.
+    pub fn from_bytes(src: &'src [u8], compliance: Compliance) -> Result<Self, Error> {
+        let (span, _compliance) = EncodedSpan::new(src, compliance, MAX_LEN)?;
+        Ok(Self::from_span(crate::ascii::prefix(src, span.len()), span))
     }
     pub(crate) fn from_span(src: &'src str, span: EncodedSpan<'src>) -> Self {
         Self(span.span_of(src))
     }
-    /// validates whether every ascii character is a lowercase hex digit
-    fn validate_all_lower_hex(&self) -> Result<(), Error> {
-        self.to_str().bytes().enumerate().try_for_each(|(i, c)| {
-            if matches!(c, b'a'..=b'f' | b'0'..=b'9') {
-                Ok(())
-            } else {
-                #[allow(clippy::cast_possible_truncation)]
-                Error::at(
-                    i as u16, // safe since i is at most 1024
-                    OciRegisteredDigestInvalidChar,
-                )
-                .into()
-            }
-        })
-    }
-    /// check that the encoded string is an appropriate hex length for the registered
-    /// algorithms `sha256` and `sha512`.
-    fn validate_registered_algorithms(&self, algorithm: &Algorithm<'src>) -> Result<(), Error> {
-        match algorithm.to_str() {
-            "sha256" | "sha512" => {
-                self.validate_all_lower_hex()?;
-                #[allow(clippy::cast_possible_truncation)]
-                match (algorithm.to_str(), self.len()) {
-                    ("sha256", 64) => Ok(()),
-                    ("sha512", 128) => Ok(()),
-                    (_, _) => Error::at(
-                        self.len() as u16, // safe since self.len() is at most 1024
-                        OciRegisteredAlgorithmWrongDigestLength,
-                    )
-                    .into(),
-                }
-            }
-            _ => Ok(()), // non-registered algorithm, so validation falls to the caller
+    /// Decode hex digits (of either case) into `out`, returning the number of
+    /// bytes written. Errors are reported at offsets within the encoded section.
+    /// ```rust
+    /// use container_image_dist_ref::digest::{encoded::Encoded, Compliance};
+    /// let mut key = [0u8; 4];
+    /// let encoded = Encoded::new("c0ffee", Compliance::Universal).unwrap();
+    /// assert_eq!(encoded.decode_hex_into(&mut key).unwrap(), 3);
+    /// assert_eq!(key[..3], [0xc0, 0xff, 0xee]);
+    /// ```
+    pub fn decode_hex_into(&self, out: &mut [u8]) -> Result<usize, Error> {
+        raw::decode_hex(self.0, out)
+    }
+    /// Decode padded or unpadded base64url into `out`, returning the number of
+    /// bytes written. Errors are reported at offsets within the encoded section.
+    pub fn decode_base64url_into(&self, out: &mut [u8]) -> Result<usize, Error> {
+        raw::decode_base64(self.0, out, raw::Alphabet::Url)
+    }
+    /// Decode into `out` using the given charset, returning the number of bytes written.
+    pub fn decode_into(&self, charset: Charset, out: &mut [u8]) -> Result<usize, Error> {
+        match charset {
+            Charset::LowerHex => self.decode_hex_into(out),
+            Charset::Base64Url => self.decode_base64url_into(out),
         }
     }
-    /// check that the encoded string is an appropriate length according to distribution/reference
+    /// check that the encoded string is long enough for distribution/reference,
+    /// whose grammar has no maximum; the parser already enforced the length limit
     fn validate_distribution(&self) -> Result<(), Error> {
-        const MAX: usize = MAX_LEN as usize;
         match self.len() {
             0..=31 => Error::at(self.short_len().into(), EncodingTooShort).into(),
-            32..=MAX => Ok(()),
-            _ => Error::at(self.short_len().into(), EncodingTooLong).into(),
+            _ => Ok(()),
         }
     }
     /// Validate the encoded string is compliant with an algorithm string (possibly a
@@ -142,7 +150,24 @@ impl<'src> Encoded<'src> {
         algorithm: &Algorithm<'src>,
         compliance: Compliance,
     ) -> Result<Compliance, Error> {
-        self.validate_registered_algorithms(algorithm)?;
+        self.validate_with_registry(algorithm, compliance, AlgorithmRegistry::DEFAULT)
+    }
+    /// Like [`Encoded::validate_algorithm`], but checks registered algorithms'
+    /// rules in a custom [`AlgorithmRegistry`].
+    pub fn validate_with_registry(
+        &self,
+        algorithm: &Algorithm<'src>,
+        compliance: Compliance,
+        registry: AlgorithmRegistry<'_>,
+    ) -> Result<Compliance, Error> {
+        let compliance = match registry.get(algorithm.to_str()) {
+            Some(rule) => {
+                rule.validate(self.to_str())?;
+                restrict(compliance, rule.compliance())
+                    .ok_or(Error::at(0, RegisteredAlgorithmNoncompliant))?
+            }
+            None => compliance, // non-registered algorithm, so validation falls to the caller
+        };
         // Note: `validate_algorithm` doesn't check character sets since that's handled
         // by the `from_exact_match` constructor.
         match compliance {
@@ -158,6 +183,17 @@ impl<'src> Encoded<'src> {
         }
     }
 }
+
+/// the standards both compliance levels are compliant with, if any
+pub(crate) const fn restrict(a: Compliance, b: Compliance) -> Option<Compliance> {
+    use Compliance::*;
+    match (a, b) {
+        (Universal, c) | (c, Universal) => Some(c),
+        (Oci, Oci) => Some(Oci),
+        (Distribution, Distribution) => Some(Distribution),
+        (Oci, Distribution) | (Distribution, Oci) => None,
+    }
+}
 impl Lengthy<'_, u16, NonZeroU16> for Encoded<'_> {
     #[inline]
     fn len(&self) -> usize {
@@ -181,7 +217,7 @@ mod tests {
     #[test]
     fn encoded_consumes_all() {
         fn consumes_all(src: &str) -> Result<(), Error> {
-            let (span, _) = EncodedSpan::new(src, Compliance::Oci)?;
+            let (span, _) = EncodedSpan::new(src.as_bytes(), Compliance::Oci, MAX_LEN)?;
             assert_eq!(span.len(), src.len());
             Ok(())
         }
@@ -191,4 +227,51 @@ mod tests {
         assert_eq!(err.kind(), EncodedInvalidChar);
         assert_eq!(err.index(), 3);
     }
+
+    #[test]
+    fn long_runs_report_exact_offsets() {
+        extern crate alloc;
+        use alloc::string::String;
+        let hex = "0123456789abcdefABCDEF".repeat(50);
+        for (i, bad) in [(37, " "), (100, "g"), (1000, "!")] {
+            let src = String::from(&hex[..i]) + bad + &hex[..20];
+            let err = EncodedSpan::new(src.as_bytes(), Compliance::Distribution, MAX_LEN)
+                .expect_err("invalid");
+            let kind = if bad == "g" {
+                EncodedNonLowerHex
+            } else {
+                EncodedInvalidChar
+            };
+            assert_eq!((err.kind(), err.index() as usize), (kind, i));
+        }
+        let err = EncodedSpan::new(&hex.as_bytes()[..1025], Compliance::Universal, MAX_LEN)
+            .expect_err("too long");
+        assert_eq!((err.kind(), err.index()), (EncodingTooLong, MAX_LEN));
+    }
+
+    #[test]
+    fn registered_algorithms() {
+        use crate::err::Kind::{Blake3WrongDigestLength, OciRegisteredDigestInvalidChar};
+        extern crate alloc;
+        use crate::digest::Digest;
+        use alloc::string::String;
+        let hex = "0123456789abcdef".repeat(8);
+        for (algorithm, len) in [("sha256", 64), ("sha512", 128), ("blake3", 64)] {
+            let src = String::from(algorithm) + ":" + &hex[..len];
+            assert!(Digest::new(&src).is_ok(), "{src}");
+        }
+        let (blake3, _) = Algorithm::new("blake3").expect("valid");
+        let err = |encoded: &str| {
+            let e = Encoded::new(encoded, Compliance::Universal)
+                .and_then(|e| e.validate_algorithm(&blake3, Compliance::Universal))
+                .expect_err("invalid");
+            (e.index(), e.kind())
+        };
+        assert_eq!(err(&hex[..63]), (63, Blake3WrongDigestLength));
+        assert_eq!(err(&hex[..128]), (128, Blake3WrongDigestLength));
+        let upper = String::from(&hex[..63]) + "F";
+        assert_eq!(err(&upper), (63, OciRegisteredDigestInvalidChar));
+        let base64 = String::from(&hex[..63]) + "_";
+        assert_eq!(err(&base64), (63, OciRegisteredDigestInvalidChar));
+    }
 }
@@ -0,0 +1,206 @@
+//! # Explaining compliance
+//! [`Digest::compliance`] says which standards a digest complies with;
+//! [`Digest::explain_compliance`] says why it doesn't comply with the others.
+//! Each [`Noncompliance`] names the [`Kind`] that the stricter standard's
+//! parser would report, at the offset it would report it.
+//! ```rust
+//! use container_image_dist_ref::{
+//!     digest::{Digest, Standard},
+//!     err::Kind,
+//! };
+//! let digest = Digest::new("0sha:abc_def").unwrap();
+//! let causes: Vec<_> = digest.explain_compliance().collect();
+//! assert_eq!(causes.len(), 3);
+//! assert_eq!(causes[0].kind(), Kind::AlgorithmInvalidNumericPrefix);
+//! assert_eq!(causes[1].kind(), Kind::EncodedNonLowerHex);
+//! assert_eq!(causes[1].index(), 8);
+//! assert_eq!(causes[2].kind(), Kind::EncodingTooShort);
+//! assert!(causes.iter().all(|c| c.standard() == Standard::Distribution));
+//! assert_eq!(
+//!     causes[0].to_string(),
+//!     "distribution/reference rejects algorithm components starting with a digit (at offset 0)"
+//! );
+//! ```
+
+use core::fmt;
+
+use super::{Compliance, Digest, Standard};
+use crate::err::Kind;
+
+/// One reason a digest doesn't comply with a [`Standard`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Noncompliance {
+    index: u16,
+    kind: Kind,
+    standard: Standard,
+}
+
+impl Noncompliance {
+    /// The byte offset within the digest string where the problem starts.
+    pub const fn index(&self) -> u16 {
+        self.index
+    }
+    /// The error a parser for the violated standard would report.
+    pub const fn kind(&self) -> Kind {
+        self.kind
+    }
+    /// The standard the digest doesn't comply with.
+    pub const fn standard(&self) -> Standard {
+        self.standard
+    }
+    const fn reason(self) -> &'static str {
+        match self.kind {
+            Kind::AlgorithmInvalidNumericPrefix => {
+                "rejects algorithm components starting with a digit"
+            }
+            Kind::InvalidOciAlgorithm => "rejects uppercase letters in algorithms",
+            Kind::EncodedNonLowerHex => "rejects encoded digests containing non-hex characters",
+            Kind::EncodingTooShort => "rejects encoded digests shorter than 32 characters",
+            _ => "rejects this algorithm's digests",
+        }
+    }
+}
+
+impl fmt::Display for Noncompliance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let standard = match self.standard {
+            Standard::Distribution => "distribution/reference",
+            Standard::Oci => "the OCI image spec",
+        };
+        write!(f, "{standard} {} (at offset {})", self.reason(), self.index)
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+const fn index(i: usize) -> u16 {
+    i as u16 // safe since digests are at most 1280 characters long
+}
+
+/// find the first occurrence of each cause of noncompliance in order of offset,
+/// followed by any restriction from the digest's algorithm registry
+pub(super) fn explain(digest: &Digest<'_>) -> impl Iterator<Item = Noncompliance> {
+    use Standard::{Distribution, Oci};
+    let at = |i: usize, kind, standard| Noncompliance {
+        index: index(i),
+        kind,
+        standard,
+    };
+    let algorithm = digest.algorithm().to_str().as_bytes();
+    let encoded = digest.encoded().to_str().as_bytes();
+    let start = algorithm.len() + 1;
+
+    // a valid digest's algorithm can't be both numeric-prefixed and uppercase
+    let numeric_prefix = algorithm
+        .iter()
+        .enumerate()
+        .find(|&(i, c)| c.is_ascii_digit() && (i == 0 || !algorithm[i - 1].is_ascii_alphanumeric()))
+        .map(|(i, _)| at(i, Kind::AlgorithmInvalidNumericPrefix, Distribution));
+    let uppercase = algorithm
+        .iter()
+        .position(u8::is_ascii_uppercase)
+        .map(|i| at(i, Kind::InvalidOciAlgorithm, Oci));
+    let non_hex = encoded
+        .iter()
+        .position(|c| !c.is_ascii_hexdigit())
+        .map(|i| at(start + i, Kind::EncodedNonLowerHex, Distribution));
+    let too_short = (encoded.len() < 32)
+        .then(|| at(start + encoded.len(), Kind::EncodingTooShort, Distribution));
+
+    let ok = |standard| {
+        ![numeric_prefix, uppercase, non_hex, too_short]
+            .iter()
+            .flatten()
+            .any(|c| c.standard == standard)
+    };
+    let actual = digest.compliance();
+    let registry = [Distribution, Oci].map(|standard| {
+        (ok(standard) && !actual.compliant_with(standard))
+            .then(|| at(0, Kind::RegisteredAlgorithmNoncompliant, standard))
+    });
+    debug_assert!(
+        actual != Compliance::Universal || (ok(Distribution) && ok(Oci)),
+        "universally compliant digests have no causes of noncompliance"
+    );
+
+    [numeric_prefix, uppercase, non_hex, too_short]
+        .into_iter()
+        .chain(registry)
+        .flatten()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    extern crate alloc;
+    use alloc::{format, vec::Vec};
+
+    use super::*;
+    use crate::{
+        digest::encoded::Encoded,
+        digest::registry::{AlgorithmRegistry, Charset, Length, Rule},
+        Limits,
+    };
+
+    const HEX: &str = "853ff93762a06ddbf722c4ebe9ddd66d8f63ddaea97f521c3ecc20da7c976020";
+
+    fn causes(digest: Digest<'_>) -> Vec<(u16, Kind, Standard)> {
+        digest
+            .explain_compliance()
+            .map(|c| (c.index(), c.kind(), c.standard()))
+            .collect()
+    }
+    fn explain(src: &str) -> Vec<(u16, Kind, Standard)> {
+        causes(Digest::new(src).unwrap())
+    }
+
+    #[test]
+    fn universal_digests_have_no_causes() {
+        assert_eq!(explain(&format!("sha256:{HEX}")), []);
+        assert_eq!(explain(&format!("a+b.c:{}", HEX.to_ascii_uppercase())), []);
+    }
+
+    #[test]
+    fn causes_match_strict_parsers() {
+        use Standard::{Distribution, Oci};
+        let upper = format!("sha.SHA:{HEX}");
+        assert_eq!(explain(&upper), [(4, Kind::InvalidOciAlgorithm, Oci)]);
+        let numeric = format!("sha+256a:{HEX}");
+        assert_eq!(
+            explain(&numeric),
+            [(4, Kind::AlgorithmInvalidNumericPrefix, Distribution)]
+        );
+        let short = "a:abc";
+        assert_eq!(explain(short), [(5, Kind::EncodingTooShort, Distribution)]);
+        let b64 = format!("a:{}=", &HEX[..40]);
+        assert_eq!(
+            explain(&b64),
+            [(42, Kind::EncodedNonLowerHex, Distribution)]
+        );
+
+        // the offset is where a strict parser fails
+        let err = Encoded::new(&b64[2..], Compliance::Distribution)
+            .err()
+            .unwrap();
+        assert_eq!(
+            (err.index() + 2, err.kind()),
+            (42, Kind::EncodedNonLowerHex)
+        );
+    }
+
+    #[test]
+    fn registry_restrictions() {
+        const RULES: &[Rule] = &[Rule::new("b64", Charset::Base64Url, Length::AtLeast(1))];
+        let registry = AlgorithmRegistry::new(RULES);
+        let src = format!("b64:{HEX}");
+        let digest = Digest::with_registry(&src, Limits::DEFAULT, registry).unwrap();
+        assert_eq!(digest.compliance(), Compliance::Oci);
+        assert_eq!(
+            causes(digest),
+            [(
+                0,
+                Kind::RegisteredAlgorithmNoncompliant,
+                Standard::Distribution
+            )]
+        );
+    }
+}
@@ -0,0 +1,557 @@
+//! # Computing digests
+//! Dependency-free, incremental SHA-256, SHA-512, and BLAKE3 hashers that produce an
+//! [`OwnedDigest`] string such as `sha256:e3b0c442...`.
+//! ```rust
+//! use container_image_dist_ref::digest::{hash::Sha256, Digest};
+//! let mut hasher = Sha256::new();
+//! hasher.update(b"hello, ");
+//! hasher.update(b"world");
+//! let owned = hasher.digest();
+//! assert_eq!(
+//!     owned.to_str(),
+//!     "sha256:09ca7e4eaa6e8ae9c7d261167129184883644d07dfba7cbfbc4c8a2e08360d5b"
+//! );
+//! let digest: Digest = owned.digest();
+//! assert_eq!(digest.algorithm().to_str(), "sha256");
+//! ```
+//!
+//! With the `std` feature, every hasher implements `std::io::Write` so that
+//! content can be hashed with `std::io::copy`.
+
+mod blake3;
+
+use core::fmt;
+#[cfg(feature = "std")]
+use std::boxed::Box;
+
+use super::{raw, Digest};
+use crate::err::{self, Kind::EncodingTooLong};
+
+type Error = err::Error<u16>;
+
+#[doc(inline)]
+pub use self::blake3::Blake3;
+
+/// the longest digest string a hasher in this module can produce: `sha512:` + 128 hex digits
+const MAX_STR_LEN: usize = 7 + 128;
+
+/// A digest string computed by one of the hashers in this module. Unlike
+/// [`Digest`], it owns its (fixed-size) buffer.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct OwnedDigest {
+    buf: [u8; MAX_STR_LEN],
+    len: u8,
+}
+
+impl OwnedDigest {
+    /// write `algorithm`, a `:`, and `encoded_len` characters written by `encode`
+    fn build(algorithm: &str, encoded_len: usize, encode: impl FnOnce(&mut [u8])) -> Option<Self> {
+        let len = algorithm.len() + 1 + encoded_len;
+        if len > MAX_STR_LEN {
+            return None;
+        }
+        let mut buf = [0; MAX_STR_LEN];
+        let (prefix, encoded) = buf.split_at_mut(algorithm.len() + 1);
+        prefix[..algorithm.len()].copy_from_slice(algorithm.as_bytes());
+        prefix[algorithm.len()] = b':';
+        encode(&mut encoded[..encoded_len]);
+        #[allow(clippy::cast_possible_truncation)]
+        let len = len as u8; // safe since len <= MAX_STR_LEN
+        Some(Self { buf, len })
+    }
+    /// format `bytes` as lowercase hex after a registered `algorithm` and a `:`
+    pub(crate) fn new(algorithm: &str, bytes: &[u8]) -> Self {
+        match Self::build(algorithm, 2 * bytes.len(), |out| {
+            raw::encode_hex(bytes, out)
+        }) {
+            Some(digest) => digest,
+            None => unreachable!("registered algorithms' digests fit in the buffer"),
+        }
+    }
+    /// check that a built digest string is a valid digest
+    fn validated(digest: Option<Self>) -> Result<Self, Error> {
+        #[allow(clippy::cast_possible_truncation)]
+        let digest = digest.ok_or(Error::at(MAX_STR_LEN as u16, EncodingTooLong))?; // safe since MAX_STR_LEN < u16::MAX
+        Digest::new(digest.to_str())?;
+        Ok(digest)
+    }
+    /// Format `bytes` as lowercase hex after `algorithm` and a `:`. The result
+    /// must be a valid digest at most 135 characters long, the length of a
+    /// `sha512` digest string.
+    /// ```rust
+    /// use container_image_dist_ref::digest::hash::OwnedDigest;
+    /// let digest = OwnedDigest::encode_hex("md5", &[0xd4, 0x1d, 0x8c, 0xd9]).unwrap();
+    /// assert_eq!(digest.to_str(), "md5:d41d8cd9");
+    /// assert!(OwnedDigest::encode_hex("sha256", &[0xd4]).is_err());
+    /// ```
+    pub fn encode_hex(algorithm: &str, bytes: &[u8]) -> Result<Self, Error> {
+        Self::validated(Self::build(algorithm, 2 * bytes.len(), |out| {
+            raw::encode_hex(bytes, out)
+        }))
+    }
+    /// Format `bytes` as unpadded base64url after `algorithm` and a `:`. The
+    /// result must be a valid digest at most 135 characters long.
+    pub fn encode_base64url(algorithm: &str, bytes: &[u8]) -> Result<Self, Error> {
+        let len = raw::base64_len(bytes.len());
+        Self::validated(Self::build(algorithm, len, |out| {
+            raw::encode_base64(bytes, out, raw::Alphabet::Url)
+        }))
+    }
+    /// copy a digest string that's short enough to fit in the buffer. Digests
+    /// parsed with custom limits or registries are re-checked against the
+    /// defaults so that [`OwnedDigest::digest`] can't fail.
+    pub(crate) fn copy_of(digest: Digest<'_>) -> Result<Self, Error> {
+        let src = digest.to_str().as_bytes();
+        let copy = u8::try_from(src.len())
+            .ok()
+            .filter(|&len| usize::from(len) <= MAX_STR_LEN)
+            .map(|len| {
+                let mut buf = [0; MAX_STR_LEN];
+                buf[..src.len()].copy_from_slice(src);
+                Self { buf, len }
+            });
+        Self::validated(copy)
+    }
+    /// The digest string, e.g. `sha256:e3b0c442...`.
+    pub fn to_str(&self) -> &str {
+        let ascii = &self.buf[..usize::from(self.len)];
+        // SAFETY: the buffer is filled with an ascii algorithm name, a ':', and hex digits
+        unsafe { core::str::from_utf8_unchecked(ascii) }
+    }
+    /// Borrow the digest string as a parsed [`Digest`].
+    pub fn digest(&self) -> Digest<'_> {
+        match Digest::new(self.to_str()) {
+            Ok(digest) => digest,
+            // every computed digest is a registered algorithm with the right length
+            Err(e) => unreachable!("{:?} is invalid: {e:?}", self.to_str()),
+        }
+    }
+}
+
+impl fmt::Display for OwnedDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.to_str())
+    }
+}
+
+impl fmt::Debug for OwnedDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.to_str(), f)
+    }
+}
+
+/// Implement incremental hashing over a Merkle–Damgård compression function.
+macro_rules! hasher {
+    (
+        $(#[$meta:meta])*
+        $name:ident {
+            algorithm: $algorithm:literal,
+            word: $word:ty,
+            block: $block:literal,
+            output: $output:literal,
+            length_bytes: $length_bytes:literal,
+            init: $init:expr,
+            compress: $compress:ident $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone)]
+        pub struct $name {
+            state: [$word; 8],
+            buf: [u8; $block],
+            buf_len: usize,
+            /// the total number of bytes hashed so far
+            len: u128,
+        }
+
+        impl $name {
+            /// The name of the algorithm this hasher computes.
+            pub const ALGORITHM: &'static str = $algorithm;
+
+            #[allow(missing_docs)]
+            pub const fn new() -> Self {
+                Self {
+                    state: $init,
+                    buf: [0; $block],
+                    buf_len: 0,
+                    len: 0,
+                }
+            }
+            /// Hash more content.
+            pub fn update(&mut self, mut data: &[u8]) {
+                self.len = self.len.wrapping_add(data.len() as u128);
+                if self.buf_len > 0 {
+                    let n = data.len().min($block - self.buf_len);
+                    self.buf[self.buf_len..self.buf_len + n].copy_from_slice(&data[..n]);
+                    self.buf_len += n;
+                    data = &data[n..];
+                    if self.buf_len < $block {
+                        return;
+                    }
+                    $compress(&mut self.state, &self.buf);
+                    self.buf_len = 0;
+                }
+                let blocks = data.chunks_exact($block);
+                let rest = blocks.remainder();
+                blocks.for_each(|block| $compress(&mut self.state, block));
+                self.buf[..rest.len()].copy_from_slice(rest);
+                self.buf_len = rest.len();
+            }
+            /// Finish hashing and return the raw hash.
+            pub fn finalize(mut self) -> [u8; $output] {
+                let bits = self.len.wrapping_mul(8).to_be_bytes();
+                self.update(&[0x80]);
+                let zeros = [0; $block];
+                let padding = ($block * 2 - $length_bytes - self.buf_len) % $block;
+                self.update(&zeros[..padding]);
+                self.update(&bits[bits.len() - $length_bytes..]);
+                debug_assert_eq!(self.buf_len, 0);
+
+                let mut out = [0; $output];
+                let words = out.chunks_exact_mut(core::mem::size_of::<$word>());
+                for (chunk, word) in words.zip(self.state) {
+                    chunk.copy_from_slice(&word.to_be_bytes());
+                }
+                out
+            }
+            /// Finish hashing and format the result as a digest string.
+            pub fn digest(self) -> OwnedDigest {
+                OwnedDigest::new(Self::ALGORITHM, &self.finalize())
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        #[cfg(feature = "std")]
+        impl std::io::Write for $name {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.update(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+    };
+}
+
+hasher! {
+    /// An incremental SHA-256 hasher.
+    Sha256 {
+        algorithm: "sha256",
+        word: u32,
+        block: 64,
+        output: 32,
+        length_bytes: 8,
+        init: [
+            0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+            0x5be0cd19,
+        ],
+        compress: sha256_compress,
+    }
+}
+
+hasher! {
+    /// An incremental SHA-512 hasher.
+    Sha512 {
+        algorithm: "sha512",
+        word: u64,
+        block: 128,
+        output: 64,
+        length_bytes: 16,
+        init: [
+            0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+            0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+        ],
+        compress: sha512_compress,
+    }
+}
+
+/// A hasher for any of the algorithms this module can compute.
+#[cfg(feature = "std")]
+#[derive(Clone)]
+pub(crate) enum Hasher {
+    Sha256(Sha256),
+    Sha512(Sha512),
+    Blake3(Box<Blake3>),
+}
+
+#[cfg(feature = "std")]
+impl Hasher {
+    /// a hasher for the named algorithm, if it's supported
+    pub(crate) fn for_algorithm(algorithm: &str) -> Option<Self> {
+        match algorithm {
+            Sha256::ALGORITHM => Some(Self::Sha256(Sha256::new())),
+            Sha512::ALGORITHM => Some(Self::Sha512(Sha512::new())),
+            Blake3::ALGORITHM => Some(Self::Blake3(Box::default())),
+            _ => None,
+        }
+    }
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Sha256(h) => h.update(data),
+            Self::Sha512(h) => h.update(data),
+            Self::Blake3(h) => h.update(data),
+        }
+    }
+    pub(crate) fn digest(self) -> OwnedDigest {
+        match self {
+            Self::Sha256(h) => h.digest(),
+            Self::Sha512(h) => h.digest(),
+            Self::Blake3(h) => h.digest(),
+        }
+    }
+}
+
+#[rustfmt::skip]
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+#[rustfmt::skip]
+const SHA512_K: [u64; 80] = [
+    0x428a2f98d728ae22, 0x7137449123ef65cd, 0xb5c0fbcfec4d3b2f, 0xe9b5dba58189dbbc,
+    0x3956c25bf348b538, 0x59f111f1b605d019, 0x923f82a4af194f9b, 0xab1c5ed5da6d8118,
+    0xd807aa98a3030242, 0x12835b0145706fbe, 0x243185be4ee4b28c, 0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f, 0x80deb1fe3b1696b1, 0x9bdc06a725c71235, 0xc19bf174cf692694,
+    0xe49b69c19ef14ad2, 0xefbe4786384f25e3, 0x0fc19dc68b8cd5b5, 0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275, 0x4a7484aa6ea6e483, 0x5cb0a9dcbd41fbd4, 0x76f988da831153b5,
+    0x983e5152ee66dfab, 0xa831c66d2db43210, 0xb00327c898fb213f, 0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2, 0xd5a79147930aa725, 0x06ca6351e003826f, 0x142929670a0e6e70,
+    0x27b70a8546d22ffc, 0x2e1b21385c26c926, 0x4d2c6dfc5ac42aed, 0x53380d139d95b3df,
+    0x650a73548baf63de, 0x766a0abb3c77b2a8, 0x81c2c92e47edaee6, 0x92722c851482353b,
+    0xa2bfe8a14cf10364, 0xa81a664bbc423001, 0xc24b8b70d0f89791, 0xc76c51a30654be30,
+    0xd192e819d6ef5218, 0xd69906245565a910, 0xf40e35855771202a, 0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8, 0x1e376c085141ab53, 0x2748774cdf8eeb99, 0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63, 0x4ed8aa4ae3418acb, 0x5b9cca4f7763e373, 0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc, 0x78a5636f43172f60, 0x84c87814a1f0ab72, 0x8cc702081a6439ec,
+    0x90befffa23631e28, 0xa4506cebde82bde9, 0xbef9a3f7b2c67915, 0xc67178f2e372532b,
+    0xca273eceea26619c, 0xd186b8c721c0c207, 0xeada7dd6cde0eb1e, 0xf57d4f7fee6ed178,
+    0x06f067aa72176fba, 0x0a637dc5a2c898a6, 0x113f9804bef90dae, 0x1b710b35131c471b,
+    0x28db77f523047d84, 0x32caab7b40c72493, 0x3c9ebe0a15c9bebc, 0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6, 0x597f299cfc657e2a, 0x5fcb6fab3ad6faec, 0x6c44198c4a475817,
+];
+
+/// The SHA-2 compression function, shared between SHA-256 and SHA-512 which
+/// differ only in their word size, round constants, and rotation amounts.
+macro_rules! compress {
+    (
+        $name:ident, $word:ty, $k:ident,
+        sigma0: ($s00:literal, $s01:literal, $s02:literal),
+        sigma1: ($s10:literal, $s11:literal, $s12:literal),
+        big_sigma0: ($b00:literal, $b01:literal, $b02:literal),
+        big_sigma1: ($b10:literal, $b11:literal, $b12:literal) $(,)?
+    ) => {
+        /// process one block of `16 * size_of::<word>()` bytes
+        fn $name(state: &mut [$word; 8], block: &[u8]) {
+            const SIZE: usize = core::mem::size_of::<$word>();
+            let mut w = [0 as $word; $k.len()];
+            for (w, bytes) in w.iter_mut().zip(block.chunks_exact(SIZE)) {
+                let mut word = [0; SIZE];
+                word.copy_from_slice(bytes);
+                *w = <$word>::from_be_bytes(word);
+            }
+            for i in 16..w.len() {
+                let s0 = w[i - 15].rotate_right($s00)
+                    ^ w[i - 15].rotate_right($s01)
+                    ^ (w[i - 15] >> $s02);
+                let s1 =
+                    w[i - 2].rotate_right($s10) ^ w[i - 2].rotate_right($s11) ^ (w[i - 2] >> $s12);
+                w[i] = w[i - 16]
+                    .wrapping_add(s0)
+                    .wrapping_add(w[i - 7])
+                    .wrapping_add(s1);
+            }
+            let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+            for (k, w) in $k.iter().zip(w) {
+                let s1 = e.rotate_right($b10) ^ e.rotate_right($b11) ^ e.rotate_right($b12);
+                let ch = (e & f) ^ (!e & g);
+                let t1 = h
+                    .wrapping_add(s1)
+                    .wrapping_add(ch)
+                    .wrapping_add(*k)
+                    .wrapping_add(w);
+                let s0 = a.rotate_right($b00) ^ a.rotate_right($b01) ^ a.rotate_right($b02);
+                let maj = (a & b) ^ (a & c) ^ (b & c);
+                let t2 = s0.wrapping_add(maj);
+                h = g;
+                g = f;
+                f = e;
+                e = d.wrapping_add(t1);
+                d = c;
+                c = b;
+                b = a;
+                a = t1.wrapping_add(t2);
+            }
+            for (s, x) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+                *s = s.wrapping_add(x);
+            }
+        }
+    };
+}
+
+compress!(
+    sha256_compress, u32, SHA256_K,
+    sigma0: (7, 18, 3),
+    sigma1: (17, 19, 10),
+    big_sigma0: (2, 13, 22),
+    big_sigma1: (6, 11, 25),
+);
+compress!(
+    sha512_compress, u64, SHA512_K,
+    sigma0: (1, 8, 7),
+    sigma1: (19, 61, 6),
+    big_sigma0: (28, 34, 39),
+    big_sigma1: (14, 18, 41),
+);
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    const ABC_448: &[u8] = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+
+    fn sha256(data: &[u8]) -> OwnedDigest {
+        let mut hasher = Sha256::new();
+        hasher.update(data);
+        hasher.digest()
+    }
+    fn sha512(data: &[u8]) -> OwnedDigest {
+        let mut hasher = Sha512::new();
+        hasher.update(data);
+        hasher.digest()
+    }
+    fn blake3(data: &[u8]) -> OwnedDigest {
+        let mut hasher = Blake3::new();
+        hasher.update(data);
+        hasher.digest()
+    }
+
+    #[test]
+    fn known_sha256_vectors() {
+        for (data, hex) in [
+            (
+                &b""[..],
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+            (
+                b"abc",
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                ABC_448,
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            ),
+        ] {
+            assert_eq!(sha256(data).to_str().strip_prefix("sha256:"), Some(hex));
+        }
+    }
+
+    #[test]
+    fn known_sha512_vectors() {
+        for (data, hex) in [
+            (&b""[..], "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e"),
+            (b"abc", "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"),
+            (ABC_448, "204a8fc6dda82f0a0ced7beb8e08a41657c16ef468b228a8279be331a703c33596fd15c13b1b07f9aa1d3bea57789ca031ad85c7a71dd70354ec631238ca3445"),
+        ] {
+            assert_eq!(sha512(data).to_str().strip_prefix("sha512:"), Some(hex));
+        }
+    }
+
+    #[test]
+    fn known_blake3_vectors() {
+        assert_eq!(
+            blake3(b"abc").to_str(),
+            "blake3:6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+        // from the official test vectors, which hash the bytes `0, 1, ..., 250, 0, 1, ...`
+        let data: [u8; 2049] = core::array::from_fn(|i| u8::try_from(i % 251).unwrap());
+        for (len, hex) in [
+            (
+                0,
+                "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+            ),
+            (
+                1,
+                "2d3adedff11b61f14c886e35afa036736dcd87a74d27b5c1510225d0f592e213",
+            ),
+            (
+                1024,
+                "42214739f095a406f3fc83deb889744ac00df831c10daa55189b5d121c855af7",
+            ),
+            (
+                1025,
+                "d00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444",
+            ),
+            (
+                2048,
+                "e776b6028c7cd22a4d0ba182a8bf62205d2ef576467e838ed6f2529b85fba24a",
+            ),
+        ] {
+            let digest = blake3(&data[..len]);
+            assert_eq!(digest.to_str().strip_prefix("blake3:"), Some(hex), "{len}");
+        }
+    }
+
+    #[test]
+    fn incremental_updates_match_one_shot() {
+        let data: [u8; 3000] = core::array::from_fn(|i| u8::try_from(i % 251).unwrap());
+        let (one_256, one_512, one_b3) = (sha256(&data), sha512(&data), blake3(&data));
+        for chunk in [1, 3, 63, 64, 65, 127, 128, 129, 999, 1024, 1025] {
+            let (mut h256, mut h512, mut b3) = (Sha256::new(), Sha512::new(), Blake3::new());
+            data.chunks(chunk).for_each(|c| {
+                h256.update(c);
+                h512.update(c);
+                b3.update(c);
+            });
+            assert_eq!(h256.digest(), one_256, "chunk size {chunk}");
+            assert_eq!(h512.digest(), one_512, "chunk size {chunk}");
+            assert_eq!(b3.digest(), one_b3, "chunk size {chunk}");
+        }
+    }
+
+    #[test]
+    fn a_million_as() {
+        let (mut h256, mut h512) = (Sha256::new(), Sha512::new());
+        for _ in 0..1000 {
+            h256.update(&[b'a'; 1000]);
+            h512.update(&[b'a'; 1000]);
+        }
+        assert_eq!(
+            h256.digest().to_str(),
+            "sha256:cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+        );
+        assert_eq!(
+            h512.digest().to_str(),
+            "sha512:e718483d0ce769644e2e42c7bc15b4638e1f98b13b2044285632a803afa973ebde0ff244877ea60a4cb0432ce577c31beb009c5c2c49aa2e4eadb217ad8cc09b"
+        );
+    }
+
+    #[test]
+    fn round_trips_through_digest() {
+        let owned = sha512(b"abc");
+        let digest = owned.digest();
+        assert_eq!(digest.encoded().to_str().len(), 128);
+        assert_eq!(digest.to_str(), owned.to_str());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn implements_write() {
+        let mut hasher = Sha256::new();
+        std::io::copy(&mut &*ABC_448, &mut hasher).unwrap();
+        assert_eq!(hasher.digest(), sha256(ABC_448));
+    }
+}
@@ -0,0 +1,289 @@
+//! A straightforward, portable BLAKE3 hasher producing 256-bit hashes, following
+//! the structure of the [reference implementation](https://github.com/BLAKE3-team/BLAKE3/blob/1.5.0/reference_impl/reference_impl.rs).
+
+use super::OwnedDigest;
+
+const OUT_LEN: usize = 32;
+const BLOCK_LEN_U32: u32 = 64;
+const BLOCK_LEN: usize = BLOCK_LEN_U32 as usize;
+const CHUNK_LEN: usize = 1024;
+
+const CHUNK_START: u32 = 1 << 0;
+const CHUNK_END: u32 = 1 << 1;
+const PARENT: u32 = 1 << 2;
+const ROOT: u32 = 1 << 3;
+
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const MSG_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+/// the quarter-round mixing function
+const fn g(state: &mut [u32; 16], [a, b, c, d]: [usize; 4], mx: u32, my: u32) {
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(mx);
+    state[d] = (state[d] ^ state[a]).rotate_right(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(12);
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(my);
+    state[d] = (state[d] ^ state[a]).rotate_right(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(7);
+}
+
+const fn round(state: &mut [u32; 16], m: &[u32; 16]) {
+    // mix the columns
+    g(state, [0, 4, 8, 12], m[0], m[1]);
+    g(state, [1, 5, 9, 13], m[2], m[3]);
+    g(state, [2, 6, 10, 14], m[4], m[5]);
+    g(state, [3, 7, 11, 15], m[6], m[7]);
+    // mix the diagonals
+    g(state, [0, 5, 10, 15], m[8], m[9]);
+    g(state, [1, 6, 11, 12], m[10], m[11]);
+    g(state, [2, 7, 8, 13], m[12], m[13]);
+    g(state, [3, 4, 9, 14], m[14], m[15]);
+}
+
+fn compress(
+    chaining_value: &[u32; 8],
+    block_words: &[u32; 16],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+) -> [u32; 16] {
+    let mut state = [0; 16];
+    state[..8].copy_from_slice(chaining_value);
+    state[8..12].copy_from_slice(&IV[..4]);
+    #[allow(clippy::cast_possible_truncation)]
+    {
+        // split the counter into its low and high words
+        state[12] = counter as u32;
+        state[13] = (counter >> 32) as u32;
+    }
+    state[14] = block_len;
+    state[15] = flags;
+
+    let mut block = *block_words;
+    for i in 0..7 {
+        round(&mut state, &block);
+        if i < 6 {
+            block = MSG_PERMUTATION.map(|j| block[j]);
+        }
+    }
+    for i in 0..8 {
+        state[i] ^= state[i + 8];
+        state[i + 8] ^= chaining_value[i];
+    }
+    state
+}
+
+fn first_8_words(words: [u32; 16]) -> [u32; 8] {
+    let mut first = [0; 8];
+    first.copy_from_slice(&words[..8]);
+    first
+}
+
+fn words_from_le_bytes(bytes: &[u8; BLOCK_LEN]) -> [u32; 16] {
+    let mut words = [0; 16];
+    for (word, chunk) in words.iter_mut().zip(bytes.as_chunks::<4>().0) {
+        *word = u32::from_le_bytes(*chunk);
+    }
+    words
+}
+
+/// the inputs to a compression that might produce the root node's output
+struct Output {
+    input_chaining_value: [u32; 8],
+    block_words: [u32; 16],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+}
+
+impl Output {
+    fn chaining_value(&self) -> [u32; 8] {
+        first_8_words(compress(
+            &self.input_chaining_value,
+            &self.block_words,
+            self.counter,
+            self.block_len,
+            self.flags,
+        ))
+    }
+    fn root_hash(&self) -> [u8; OUT_LEN] {
+        let words = compress(
+            &self.input_chaining_value,
+            &self.block_words,
+            0,
+            self.block_len,
+            self.flags | ROOT,
+        );
+        let mut out = [0; OUT_LEN];
+        for (chunk, word) in out.as_chunks_mut::<4>().0.iter_mut().zip(words) {
+            *chunk = word.to_le_bytes();
+        }
+        out
+    }
+}
+
+#[derive(Clone)]
+struct ChunkState {
+    chaining_value: [u32; 8],
+    chunk_counter: u64,
+    block: [u8; BLOCK_LEN],
+    block_len: usize,
+    blocks_compressed: usize,
+}
+
+impl ChunkState {
+    const fn new(chunk_counter: u64) -> Self {
+        Self {
+            chaining_value: IV,
+            chunk_counter,
+            block: [0; BLOCK_LEN],
+            block_len: 0,
+            blocks_compressed: 0,
+        }
+    }
+    const fn len(&self) -> usize {
+        BLOCK_LEN * self.blocks_compressed + self.block_len
+    }
+    const fn start_flag(&self) -> u32 {
+        if self.blocks_compressed == 0 {
+            CHUNK_START
+        } else {
+            0
+        }
+    }
+    fn update(&mut self, mut input: &[u8]) {
+        while !input.is_empty() {
+            // only compress a full block once more input arrives, since the
+            // last block of a chunk needs the CHUNK_END flag
+            if self.block_len == BLOCK_LEN {
+                let block_words = words_from_le_bytes(&self.block);
+                self.chaining_value = first_8_words(compress(
+                    &self.chaining_value,
+                    &block_words,
+                    self.chunk_counter,
+                    BLOCK_LEN_U32,
+                    self.start_flag(),
+                ));
+                self.blocks_compressed += 1;
+                self.block = [0; BLOCK_LEN];
+                self.block_len = 0;
+            }
+            let take = input.len().min(BLOCK_LEN - self.block_len);
+            self.block[self.block_len..self.block_len + take].copy_from_slice(&input[..take]);
+            self.block_len += take;
+            input = &input[take..];
+        }
+    }
+    fn output(&self) -> Output {
+        #[allow(clippy::cast_possible_truncation)]
+        Output {
+            input_chaining_value: self.chaining_value,
+            block_words: words_from_le_bytes(&self.block),
+            counter: self.chunk_counter,
+            block_len: self.block_len as u32, // safe since block_len <= BLOCK_LEN
+            flags: self.start_flag() | CHUNK_END,
+        }
+    }
+}
+
+fn parent_output(left: [u32; 8], right: [u32; 8]) -> Output {
+    let mut block_words = [0; 16];
+    block_words[..8].copy_from_slice(&left);
+    block_words[8..].copy_from_slice(&right);
+    Output {
+        input_chaining_value: IV,
+        block_words,
+        counter: 0,
+        block_len: BLOCK_LEN_U32,
+        flags: PARENT,
+    }
+}
+
+/// An incremental BLAKE3 hasher producing 256-bit hashes.
+#[derive(Clone)]
+pub struct Blake3 {
+    chunk_state: ChunkState,
+    /// the chaining values of completed subtrees, enough for 2^54 chunks
+    cv_stack: [[u32; 8]; 54],
+    cv_stack_len: usize,
+}
+
+impl Blake3 {
+    /// The name of the algorithm this hasher computes.
+    pub const ALGORITHM: &'static str = "blake3";
+
+    #[allow(missing_docs)]
+    pub const fn new() -> Self {
+        Self {
+            chunk_state: ChunkState::new(0),
+            cv_stack: [[0; 8]; 54],
+            cv_stack_len: 0,
+        }
+    }
+    const fn push_stack(&mut self, cv: [u32; 8]) {
+        self.cv_stack[self.cv_stack_len] = cv;
+        self.cv_stack_len += 1;
+    }
+    const fn pop_stack(&mut self) -> [u32; 8] {
+        self.cv_stack_len -= 1;
+        self.cv_stack[self.cv_stack_len]
+    }
+    /// merge completed subtrees: the number of trailing zeros in the total
+    /// number of chunks is the number of subtrees to merge
+    fn add_chunk_chaining_value(&mut self, mut new_cv: [u32; 8], mut total_chunks: u64) {
+        while total_chunks & 1 == 0 {
+            new_cv = parent_output(self.pop_stack(), new_cv).chaining_value();
+            total_chunks >>= 1;
+        }
+        self.push_stack(new_cv);
+    }
+    /// Hash more content.
+    pub fn update(&mut self, mut input: &[u8]) {
+        while !input.is_empty() {
+            // only finish a full chunk once more input arrives, since the
+            // last chunk might be the root
+            if self.chunk_state.len() == CHUNK_LEN {
+                let chunk_cv = self.chunk_state.output().chaining_value();
+                let total_chunks = self.chunk_state.chunk_counter.wrapping_add(1);
+                self.add_chunk_chaining_value(chunk_cv, total_chunks);
+                self.chunk_state = ChunkState::new(total_chunks);
+            }
+            let take = input.len().min(CHUNK_LEN - self.chunk_state.len());
+            self.chunk_state.update(&input[..take]);
+            input = &input[take..];
+        }
+    }
+    /// Finish hashing and return the raw hash.
+    pub fn finalize(self) -> [u8; OUT_LEN] {
+        let mut output = self.chunk_state.output();
+        for left in self.cv_stack[..self.cv_stack_len].iter().rev() {
+            output = parent_output(*left, output.chaining_value());
+        }
+        output.root_hash()
+    }
+    /// Finish hashing and format the result as a digest string.
+    pub fn digest(self) -> OwnedDigest {
+        OwnedDigest::new(Self::ALGORITHM, &self.finalize())
+    }
+}
+
+impl Default for Blake3 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Write for Blake3 {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
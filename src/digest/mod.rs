@@ -25,22 +25,34 @@
 //! ```
 
 pub mod algorithm;
+pub mod content;
 pub mod encoded;
+pub mod explain;
+pub mod hash;
+pub mod notation;
+pub mod prefix;
+pub mod raw;
+pub mod registry;
+#[cfg(feature = "std")]
+pub mod verify;
 
 use core::num::NonZeroU16;
 
 use crate::{
-    err,
+    ascii, err,
     span::{Lengthy, OptionallyZero},
+    Limits,
 };
 
 use self::{
     algorithm::{Algorithm, AlgorithmSpan},
     encoded::{Encoded, EncodedSpan},
+    registry::AlgorithmRegistry,
 };
 type Error = err::Error<u16>;
 /// The standard or specification that a digest string must comply with. Used in
 /// [`Compliance::compliant_with`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Standard {
     /// Matching [0-9a-f]{32,} per distribution/reference.
     ///
@@ -54,7 +66,7 @@ pub enum Standard {
 }
 
 /// Whether a digest string is compliant with the OCI image spec, distribution/reference, or both.
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
 pub enum Compliance {
     /// Not compliant with distribution/reference: at least one algorithm component
     /// starts with a number.
@@ -62,15 +74,10 @@ pub enum Compliance {
     /// Not compliant with OCI image spec: at least one letter is uppercase.
     Distribution,
     /// Compliant with both distribution/reference and OCI image spec.
+    #[default]
     Universal,
     // non-compliance will always result in an error, so we don't need a variant
 }
-impl Default for Compliance {
-    fn default() -> Self {
-        Self::Universal
-    }
-}
-
 impl Compliance {
     /// Checks whether a given compliance level is compliant with a given standard.
     pub const fn compliant_with(self, standard: Standard) -> bool {
@@ -81,6 +88,24 @@ impl Compliance {
                 | (Compliance::Distribution, Standard::Distribution)
         )
     }
+    /// The compliance level's code: 0 for `Oci`, 1 for `Distribution`, and 2
+    /// for `Universal`.
+    pub const fn code(self) -> u8 {
+        match self {
+            Self::Oci => 0,
+            Self::Distribution => 1,
+            Self::Universal => 2,
+        }
+    }
+    /// The compliance level with the given code, if any.
+    pub const fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(Self::Oci),
+            1 => Some(Self::Distribution),
+            2 => Some(Self::Universal),
+            _ => None,
+        }
+    }
 }
 
 // Note: DigestSpan doesn't own a leading '@'; that's only implied when DigestSpan
@@ -94,24 +119,34 @@ pub(crate) struct DigestSpan<'src> {
 }
 
 impl<'src> DigestSpan<'src> {
-    pub(crate) fn new(src: &'src str) -> Result<Self, Error> {
-        let (algorithm_span, compliance) = AlgorithmSpan::new(src)?;
+    pub(crate) fn new(
+        src: &'src [u8],
+        limits: Limits,
+        registry: AlgorithmRegistry<'_>,
+    ) -> Result<Self, Error> {
+        let (algorithm_span, compliance) = AlgorithmSpan::new(src, limits.algorithm())?;
         let mut len = algorithm_span.short_len().widen(); // max 255
 
-        len = match src.as_bytes().get(len.as_usize()) {
+        len = match src.get(len.as_usize()) {
             Some(b':') => len.checked_add(1).ok_or(err::Kind::AlgorithmTooLong),
             None => Err(err::Kind::AlgorithmMissing),
             _ => Err(err::Kind::AlgorithmInvalidChar),
         }
         .map_err(|kind| Error::at(len.into(), kind))?;
-        let (encoded, compliance) = EncodedSpan::new(&src[len.as_usize()..], compliance)
-            .map_err(|e| Error::at(e.index().saturating_add(len.into()), e.kind()))?; // safe since len can be at most 256 and e.index() can be at most 1024
+        let (encoded, compliance) =
+            EncodedSpan::new(&src[len.as_usize()..], compliance, limits.encoded())
+                .map_err(|e| Error::at(e.index().saturating_add(len.into()), e.kind()))?; // safe since len can be at most 256 and e.index() can be at most 1024
 
-        {
-            let algorithm = Algorithm::from_span(src, algorithm_span);
-            let encoded = Encoded::from_span(&src[len.as_usize()..], encoded);
-            encoded.validate_algorithm(&algorithm, compliance)?;
-        }
+        let compliance = {
+            // both sections were checked to be ascii above
+            let algorithm =
+                Algorithm::from_span(ascii::prefix(src, algorithm_span.len()), algorithm_span);
+            let encoded = Encoded::from_span(
+                ascii::prefix(&src[len.as_usize()..], encoded.len()),
+                encoded,
+            );
+            encoded.validate_with_registry(&algorithm, compliance, registry)?
+        };
 
         Ok(Self {
             algorithm: algorithm_span,
@@ -119,6 +154,10 @@ impl<'src> DigestSpan<'src> {
             compliance,
         })
     }
+    #[cfg(feature = "alloc")]
+    pub(crate) const fn compliance(self) -> Compliance {
+        self.compliance
+    }
 }
 impl Lengthy<'_, u16, NonZeroU16> for DigestSpan<'_> {
     fn short_len(&self) -> NonZeroU16 {
@@ -137,6 +176,7 @@ impl Lengthy<'_, u16, NonZeroU16> for DigestSpan<'_> {
 /// A parsed digest string. Includes the algorithm and encoded digest value,
 /// along with information about whether the digest is compliant with the OCI image spec,
 /// distribution/reference, or both.
+#[derive(Clone, Copy)]
 pub struct Digest<'src> {
     src: &'src str,
     span: DigestSpan<'src>,
@@ -145,8 +185,33 @@ pub struct Digest<'src> {
 impl<'src> Digest<'src> {
     /// Parse a digest string NOT starting with a leading '@'. Parsing continues to the end of the string.
     pub fn new(src: &'src str) -> Result<Self, Error> {
-        let span = DigestSpan::new(src)?;
-        Ok(Self::from_span(&src[0..span.len()], span))
+        Self::with_limits(src, Limits::DEFAULT)
+    }
+    /// Parse a digest string NOT starting with a leading '@', with custom limits
+    /// on the length of the algorithm and encoded sections.
+    pub fn with_limits(src: &'src str, limits: Limits) -> Result<Self, Error> {
+        Self::with_registry(src, limits, AlgorithmRegistry::DEFAULT)
+    }
+    /// Parse a digest string NOT starting with a leading '@', validating the
+    /// encoded section against the rules in a custom [`AlgorithmRegistry`].
+    pub fn with_registry(
+        src: &'src str,
+        limits: Limits,
+        registry: AlgorithmRegistry<'_>,
+    ) -> Result<Self, Error> {
+        Self::parse(src.as_bytes(), limits, registry)
+    }
+    /// Parse a digest from bytes without validating them as UTF-8.
+    pub fn from_bytes(src: &'src [u8]) -> Result<Self, Error> {
+        Self::parse(src, Limits::DEFAULT, AlgorithmRegistry::DEFAULT)
+    }
+    pub(crate) fn parse(
+        src: &'src [u8],
+        limits: Limits,
+        registry: AlgorithmRegistry<'_>,
+    ) -> Result<Self, Error> {
+        let span = DigestSpan::new(src, limits, registry)?;
+        Ok(Self::from_span(ascii::prefix(src, span.len()), span))
     }
     #[inline]
     pub(crate) const fn from_span(src: &'src str, span: DigestSpan<'src>) -> Self {
@@ -173,4 +238,159 @@ impl<'src> Digest<'src> {
     pub const fn compliance(&self) -> Compliance {
         self.span.compliance
     }
+    /// Explain why this digest isn't [`Compliance::Universal`]. Yields
+    /// nothing for universally compliant digests. See [`explain`].
+    pub fn explain_compliance(&self) -> impl Iterator<Item = explain::Noncompliance> {
+        explain::explain(self)
+    }
+    /// The first [`prefix::SHORT_LEN`] characters of the encoded section, as
+    /// shown by `docker images`. Shorter encoded sections are returned whole.
+    pub fn short_id(&self) -> &'src str {
+        let encoded = self.encoded().to_str();
+        &encoded[..encoded.len().min(prefix::SHORT_LEN)]
+    }
+    /// Whether two encoded sections may differ in case: hex digits are
+    /// case-insensitive when both encoded sections match distribution/reference's
+    /// `[0-9a-fA-F]{32,}` grammar and [`AlgorithmRegistry::DEFAULT`] doesn't
+    /// restrict the algorithm to lowercase hex. This only depends on the digests'
+    /// text, not the registry they were parsed with, so `==` stays transitive.
+    fn case_insensitive(&self, other: &Digest<'_>) -> bool {
+        // scan every byte so that `ct_eq` doesn't leak where the hex digits end
+        let hex = |encoded: &str| {
+            encoded.len() >= 32
+                && encoded
+                    .bytes()
+                    .fold(true, |hex, c| hex & c.is_ascii_hexdigit())
+        };
+        let lowercase_only = AlgorithmRegistry::DEFAULT
+            .get(self.algorithm().to_str())
+            .is_some_and(|rule| rule.charset() == registry::Charset::LowerHex);
+        !lowercase_only & hex(self.encoded().to_str()) & hex(other.encoded().to_str())
+    }
+    /// Compare two digests using the same rules as `==`, in time that depends
+    /// only on the lengths of the digests and not on their contents. Use this
+    /// to compare untrusted digests against expected values.
+    /// ```rust
+    /// use container_image_dist_ref::digest::Digest;
+    /// let expected = Digest::new("md5:d41d8cd98f00b204e9800998ecf8427e").unwrap();
+    /// let upper = Digest::new("md5:D41D8CD98F00B204E9800998ECF8427E").unwrap();
+    /// assert!(expected.ct_eq(&upper));
+    /// assert_eq!(expected, upper);
+    /// let other = Digest::new("md5:d41d8cd98f00b204e9800998ecf84270").unwrap();
+    /// assert!(!expected.ct_eq(&other));
+    /// ```
+    pub fn ct_eq(&self, other: &Digest<'_>) -> bool {
+        let (a, b) = (self.to_str().as_bytes(), other.to_str().as_bytes());
+        let algorithm_len = self.algorithm().len();
+        if a.len() != b.len() || algorithm_len != other.algorithm().len() {
+            return false;
+        }
+        // setting 0x20 lowercases hex letters and leaves digits unchanged
+        let fold = if self.case_insensitive(other) {
+            0x20
+        } else {
+            0
+        };
+        let diff = a.iter().zip(b).enumerate().fold(0, |diff, (i, (x, y))| {
+            let fold = if i > algorithm_len { fold } else { 0 };
+            diff | ((x | fold) ^ (y | fold))
+        });
+        core::hint::black_box(diff) == 0
+    }
+    /// Wrap a reader or writer so that everything passing through it is hashed
+    /// and checked against this digest. Returns `None` if this crate can't
+    /// compute the digest's algorithm. See [`verify::Verifier`].
+    #[cfg(feature = "std")]
+    pub fn verifier<T>(self, inner: T) -> Option<verify::Verifier<T>> {
+        verify::Verifier::new(self, inner)
+    }
+}
+
+/// Digests are equal if their algorithms are identical and their encoded
+/// sections are identical, ignoring the case of hex digits if both encoded
+/// sections match `[0-9a-fA-F]{32,}` and the algorithm isn't registered as
+/// lowercase-only. See [`Digest::ct_eq`] for a constant-time
+/// comparison.
+impl PartialEq<Digest<'_>> for Digest<'_> {
+    fn eq(&self, other: &Digest<'_>) -> bool {
+        let (a, b) = (self.encoded().to_str(), other.encoded().to_str());
+        self.algorithm().to_str() == other.algorithm().to_str()
+            && if self.case_insensitive(other) {
+                a.eq_ignore_ascii_case(b)
+            } else {
+                a == b
+            }
+    }
+}
+impl Eq for Digest<'_> {}
+
+impl core::fmt::Debug for Digest<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Digest").field(&self.src).finish()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    const HEX: &str = "853ff93762a06ddbf722c4ebe9ddd66d8f63ddaea97f521c3ecc20da7c976020";
+
+    fn digest(src: &str) -> Digest<'_> {
+        Digest::new(src).unwrap()
+    }
+    /// check that `==` and `ct_eq` agree
+    fn eq(a: &str, b: &str) -> bool {
+        let (a, b) = (digest(a), digest(b));
+        assert_eq!(a == b, a.ct_eq(&b), "{a:?} vs {b:?}");
+        assert_eq!(b.ct_eq(&a), a == b, "symmetry");
+        a == b
+    }
+
+    #[test]
+    fn equality() {
+        extern crate alloc;
+        use alloc::format;
+
+        let sha256 = format!("sha256:{HEX}");
+        assert!(eq(&sha256, &sha256));
+        assert!(!eq(&sha256, &format!("sha256:{}1", &HEX[..63])));
+        assert!(!eq(&sha256, &format!("blake3:{HEX}")));
+        assert!(!eq(&sha256, &format!("sha512:{HEX}{HEX}")));
+
+        // hex following distribution/reference's grammar is case-insensitive
+        let upper = format!("Upper:{}", HEX.to_ascii_uppercase());
+        assert_eq!(digest(&upper).compliance(), Compliance::Distribution);
+        assert!(eq(&upper, &format!("Upper:{HEX}")));
+        // ... but algorithms aren't
+        assert!(!eq(&upper, &format!("upper:{HEX}")));
+        // OCI-only encodings are case-sensitive
+        assert!(eq("b64:aBcDg", "b64:aBcDg"));
+        assert!(!eq("b64:aBcDg", "b64:abcdg"));
+        assert!(!eq("b64:aBcD", "b64:abcd"));
+        // the ':' isn't case-folded either
+        assert!(!eq("a:bcd", "ab:cd"));
+        // registered lowercase-only algorithms are case-sensitive
+        let upper = format!("sha256:{}", HEX.to_ascii_uppercase());
+        let upper = Digest::with_registry(&upper, Limits::DEFAULT, AlgorithmRegistry::EMPTY);
+        assert_ne!(upper.unwrap(), digest(&sha256));
+    }
+
+    #[test]
+    fn equality_ignores_the_registry() {
+        use registry::{Charset, Length, Rule};
+        const RULES: &[Rule] =
+            &[Rule::new("md5", Charset::LowerHex, Length::Exact(32))
+                .with_compliance(Compliance::Oci)];
+        let lower = "md5:d41d8cd98f00b204e9800998ecf8427e";
+        let oci = Digest::with_registry(lower, Limits::DEFAULT, AlgorithmRegistry::new(RULES));
+        let oci = oci.unwrap();
+        assert_eq!(oci.compliance(), Compliance::Oci);
+        let upper = digest("md5:D41D8CD98F00B204E9800998ECF8427E");
+        assert_eq!(upper, digest(lower));
+        assert_eq!(oci, digest(lower));
+        assert_eq!(oci, upper);
+        assert!(oci.ct_eq(&upper));
+    }
 }
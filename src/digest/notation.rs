@@ -0,0 +1,406 @@
+//! # Other digest notations
+//! Conversions between digests and the notations used by other tooling:
+//! - [subresource integrity](https://www.w3.org/TR/SRI/) strings like
+//!   `sha256-<base64>`, as used by npm and Nix
+//! - bare hex digits with a separately-known algorithm
+//! - checksum-file lines like those printed by `sha256sum` and `sha512sum`
+//!
+//! Parse errors use the same [`err::Kind`]s as [`Digest`]
+//! parsing, at offsets within the parsed string.
+//! ```rust
+//! use container_image_dist_ref::digest::{
+//!     notation::{from_hex, ChecksumLine, Sri},
+//!     Digest,
+//! };
+//! let digest = Digest::new(
+//!     "sha256:09ca7e4eaa6e8ae9c7d261167129184883644d07dfba7cbfbc4c8a2e08360d5b",
+//! )
+//! .unwrap();
+//!
+//! let sri = Sri::from_digest(digest).unwrap();
+//! assert_eq!(sri.to_string(), "sha256-Ccp+TqpuiunH0mEWcSkYSINkTQffuny/vEyKLgg2DVs=");
+//! assert_eq!(Sri::parse(&sri.to_string()).unwrap().to_digest().to_str(), digest.to_str());
+//!
+//! let hex = digest.encoded().to_str();
+//! assert_eq!(from_hex("sha256", hex).unwrap().to_str(), digest.to_str());
+//!
+//! let src = format!("{hex}  hello.txt");
+//! let line = ChecksumLine::parse(&src).unwrap();
+//! assert_eq!(line.digest().to_str(), digest.to_str());
+//! assert_eq!(line.path(), "hello.txt");
+//! ```
+
+use core::fmt;
+
+use super::{
+    hash::OwnedDigest,
+    raw::{self, Alphabet},
+    Digest,
+};
+use crate::err::{
+    self,
+    Kind::{
+        AlgorithmMissing, DecodeBufferTooSmall, DecodeInvalidLength, DecodeWrongAlgorithm,
+        EncodedInvalidChar, EncodedMissing, EncodingTooLong,
+        OciRegisteredAlgorithmWrongDigestLength, PathInvalidChar, PathMissing,
+    },
+};
+
+type Error = err::Error<u16>;
+
+/// the longest raw digest any notation in this module handles: 512 bits
+const MAX_BYTES: usize = 64;
+
+#[allow(clippy::cast_possible_truncation)]
+const fn index(i: usize) -> u16 {
+    i as u16 // safe since every index is checked against a short maximum first
+}
+
+/// The algorithms subresource integrity strings may use, and their lengths in bytes.
+const SRI_ALGORITHMS: [(&str, usize); 3] = [("sha256", 32), ("sha384", 48), ("sha512", 64)];
+
+/// A parsed [subresource integrity](https://www.w3.org/TR/SRI/) hash such as
+/// `sha256-<base64>`. Displays as its padded, standard-base64 string.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Sri {
+    algorithm: &'static str,
+    bytes: [u8; MAX_BYTES],
+    len: u8,
+}
+
+impl Sri {
+    fn new(
+        algorithm: &str,
+        decode: impl FnOnce(&mut [u8]) -> Result<usize, Error>,
+    ) -> Result<Self, Error> {
+        let (algorithm, len) = SRI_ALGORITHMS
+            .into_iter()
+            .find(|(name, _)| *name == algorithm)
+            .ok_or(Error::at(0, DecodeWrongAlgorithm))?;
+        let mut bytes = [0; MAX_BYTES];
+        // anything too long for the buffer is too long for every algorithm
+        let decoded = decode(&mut bytes).map_err(|e| match e.kind() {
+            DecodeBufferTooSmall => Error::at(0, DecodeInvalidLength),
+            _ => e,
+        })?;
+        #[allow(clippy::cast_possible_truncation)]
+        if decoded == len {
+            Ok(Self {
+                algorithm,
+                bytes,
+                len: len as u8, // safe since len <= MAX_BYTES
+            })
+        } else {
+            Err(Error::at(0, DecodeInvalidLength))
+        }
+    }
+    /// Parse a single subresource integrity hash using `sha256`, `sha384`, or `sha512`.
+    pub fn parse(src: &str) -> Result<Self, Error> {
+        let dash = src.find('-').ok_or(Error::at(
+            index(src.len().min(u8::MAX.into())),
+            AlgorithmMissing,
+        ))?;
+        let start = dash + 1;
+        let encoded = &src[start..];
+        let max = raw::base64_len(MAX_BYTES).next_multiple_of(4);
+        if encoded.len() > max || dash > usize::from(u8::MAX) {
+            return Error::at(index(start.min(u8::MAX.into()) + max), EncodingTooLong).into();
+        }
+        Self::new(&src[..dash], |out| {
+            raw::decode_base64(encoded, out, Alphabet::Standard)
+        })
+        .map_err(|e| match e.kind() {
+            DecodeWrongAlgorithm => e,
+            DecodeInvalidLength => {
+                Error::at(index(src.len()), OciRegisteredAlgorithmWrongDigestLength)
+            }
+            _ => Error::at(e.index().saturating_add(index(start)), e.kind()),
+        })
+    }
+    /// Convert a hex-encoded `sha256`, `sha384`, or `sha512` digest.
+    pub fn from_digest(digest: Digest<'_>) -> Result<Self, Error> {
+        let start = index(digest.algorithm().len() + 1);
+        Self::new(digest.algorithm().to_str(), |out| {
+            digest.encoded().decode_hex_into(out)
+        })
+        .map_err(|e| match e.kind() {
+            DecodeWrongAlgorithm => e,
+            DecodeInvalidLength => Error::at(index(digest.to_str().len()), DecodeInvalidLength),
+            _ => Error::at(e.index().saturating_add(start), e.kind()),
+        })
+    }
+    #[allow(missing_docs)]
+    pub const fn algorithm(&self) -> &'static str {
+        self.algorithm
+    }
+    /// The raw hash.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..usize::from(self.len)]
+    }
+    /// Format as a hex-encoded digest string.
+    pub fn to_digest(&self) -> OwnedDigest {
+        OwnedDigest::new(self.algorithm, self.as_bytes())
+    }
+}
+
+impl fmt::Display for Sri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const MAX: usize = raw::base64_len(MAX_BYTES).next_multiple_of(4);
+        let mut buf = [b'='; MAX];
+        let len = raw::base64_len(self.as_bytes().len());
+        raw::encode_base64(self.as_bytes(), &mut buf[..len], Alphabet::Standard);
+        let padded = &buf[..len.next_multiple_of(4)];
+        f.write_str(self.algorithm)?;
+        f.write_str("-")?;
+        // SAFETY: the buffer holds base64 characters and `=` padding, which are ascii
+        f.write_str(unsafe { core::str::from_utf8_unchecked(padded) })
+    }
+}
+
+/// Build a digest from bare hex digits (of either case) and an algorithm known
+/// from context. The hex digits are normalized to lowercase. Errors in the hex
+/// digits, including violations of a registered algorithm's rules, are reported
+/// at offsets within `hex`. Other errors, such as an invalid `algorithm`, are
+/// reported at offsets within the `algorithm:hex` digest string.
+pub fn from_hex(algorithm: &str, hex: &str) -> Result<OwnedDigest, Error> {
+    let max = 2 * MAX_BYTES;
+    if hex.is_empty() {
+        return Error::at(0, EncodedMissing).into();
+    }
+    if hex.len() > max {
+        return Error::at(index(max), EncodingTooLong).into();
+    }
+    let mut bytes = [0; MAX_BYTES];
+    let len = raw::decode_hex(hex, &mut bytes)?;
+    OwnedDigest::encode_hex(algorithm, &bytes[..len])
+}
+
+/// One line of a checksum file, as printed by `sha256sum` or `sha512sum`:
+/// hex digits, a space, a space or `*` (for binary mode), then a path.
+/// ```rust
+/// use container_image_dist_ref::digest::notation::ChecksumLine;
+/// let hex = "a".repeat(128);
+/// let src = format!("{hex} *bin/app\n");
+/// let line = ChecksumLine::parse(&src).unwrap();
+/// assert_eq!(line.digest().algorithm().to_str(), "sha512");
+/// assert!(line.binary());
+/// assert_eq!(line.to_string(), format!("{hex} *bin/app"));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ChecksumLine<'a> {
+    digest: OwnedDigest,
+    path: &'a str,
+    binary: bool,
+}
+
+impl<'a> ChecksumLine<'a> {
+    /// Parse a line, inferring the algorithm from the number of hex digits:
+    /// 64 for `sha256` and 128 for `sha512`. A trailing newline is ignored.
+    pub fn parse(line: &'a str) -> Result<Self, Error> {
+        let hex_len = line
+            .bytes()
+            .take(2 * MAX_BYTES + 1)
+            .position(|c| !c.is_ascii_hexdigit())
+            .unwrap_or(line.len().min(2 * MAX_BYTES + 1));
+        let algorithm = match hex_len {
+            64 => "sha256",
+            128 => "sha512",
+            _ if line.as_bytes().get(hex_len).is_some_and(|&c| c != b' ') => {
+                return Error::at(index(hex_len), EncodedInvalidChar).into()
+            }
+            _ => return Error::at(index(hex_len), OciRegisteredAlgorithmWrongDigestLength).into(),
+        };
+        Self::parse_with_algorithm(line, algorithm)
+    }
+    /// Parse a line of hex digits computed with `algorithm`, such as a line
+    /// printed by `b3sum`. A trailing newline is ignored.
+    pub fn parse_with_algorithm(line: &'a str, algorithm: &str) -> Result<Self, Error> {
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let hex_len = line
+            .bytes()
+            .take(2 * MAX_BYTES + 1)
+            .position(|c| !c.is_ascii_hexdigit())
+            .unwrap_or(line.len().min(2 * MAX_BYTES + 1));
+        let digest = from_hex(algorithm, &line[..hex_len])?;
+        let bytes = line.as_bytes();
+        let binary = match (bytes.get(hex_len), bytes.get(hex_len + 1)) {
+            (Some(b' '), Some(b' ')) => false,
+            (Some(b' '), Some(b'*')) => true,
+            (Some(b' '), Some(_)) => return Error::at(index(hex_len + 1), PathInvalidChar).into(),
+            (Some(b' '), None) | (None, _) => {
+                return Error::at(index(line.len()), PathMissing).into()
+            }
+            (Some(_), _) => return Error::at(index(hex_len), EncodedInvalidChar).into(),
+        };
+        let path = &line[hex_len + 2..];
+        if path.is_empty() {
+            return Error::at(index(line.len()), PathMissing).into();
+        }
+        Ok(Self {
+            digest,
+            path,
+            binary,
+        })
+    }
+    /// A line for a digest computed in text mode. Fails if the digest string
+    /// is longer than a `sha512` digest string or isn't valid under
+    /// [`AlgorithmRegistry::DEFAULT`](super::registry::AlgorithmRegistry::DEFAULT).
+    pub fn new(digest: Digest<'_>, path: &'a str) -> Result<Self, Error> {
+        let digest = OwnedDigest::copy_of(digest)?;
+        Ok(Self {
+            digest,
+            path,
+            binary: false,
+        })
+    }
+    /// Mark the line as binary mode, which prefixes the path with `*`.
+    pub const fn with_binary(self, binary: bool) -> Self {
+        Self { binary, ..self }
+    }
+    #[allow(missing_docs)]
+    pub fn digest(&self) -> Digest<'_> {
+        self.digest.digest()
+    }
+    #[allow(missing_docs)]
+    pub const fn path(&self) -> &'a str {
+        self.path
+    }
+    /// Whether the checksum was computed in binary mode.
+    pub const fn binary(&self) -> bool {
+        self.binary
+    }
+}
+
+impl fmt::Display for ChecksumLine<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = if self.binary { " *" } else { "  " };
+        write!(f, "{}{mode}{}", self.digest().encoded().to_str(), self.path)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    extern crate alloc;
+    use alloc::{format, string::ToString};
+
+    use super::*;
+    use crate::err::Kind;
+
+    /// `printf 'hello, world\n' | sha256sum`
+    const SHA256_HELLO: &str = "853ff93762a06ddbf722c4ebe9ddd66d8f63ddaea97f521c3ecc20da7c976020";
+
+    fn sri_err(src: &str) -> (u16, Kind) {
+        let e = Sri::parse(src).err().unwrap();
+        (e.index(), e.kind())
+    }
+    fn line_err(src: &str) -> (u16, Kind) {
+        let e = ChecksumLine::parse(src).err().unwrap();
+        (e.index(), e.kind())
+    }
+
+    #[test]
+    fn sri_round_trips() {
+        // `printf 'hello, world\n' | openssl dgst -sha384 -binary | base64`
+        let sha384 = "sha384-bQAdqRm5Zdw6RnK5193ON00WVFKiKF8nU5iIQgkuprmUZkU3XP8+3ompkclpi/zq";
+        let sri = Sri::parse(sha384).unwrap();
+        assert_eq!(sri.algorithm(), "sha384");
+        assert_eq!(sri.as_bytes().len(), 48);
+        assert_eq!(sri.to_string(), sha384);
+        let digest = sri.to_digest();
+        assert_eq!(Sri::from_digest(digest.digest()).unwrap(), sri);
+
+        let sha256 = format!("sha256:{SHA256_HELLO}");
+        let sri = Sri::from_digest(Digest::new(&sha256).unwrap()).unwrap();
+        assert_eq!(
+            sri.to_string(),
+            "sha256-hT/5N2Kgbdv3IsTr6d3WbY9j3a6pf1IcPswg2nyXYCA="
+        );
+        assert_eq!(sri.to_digest().to_str(), sha256);
+    }
+
+    #[test]
+    fn sri_errors() {
+        assert_eq!(sri_err("sha256"), (6, Kind::AlgorithmMissing));
+        assert_eq!(
+            sri_err("md5-1B2M2Y8AsgTpgAmY7PhCfg=="),
+            (0, Kind::DecodeWrongAlgorithm)
+        );
+        assert_eq!(sri_err("sha256-abc$"), (10, Kind::EncodedInvalidChar));
+        assert_eq!(
+            sri_err("sha256-abcd"),
+            (11, Kind::OciRegisteredAlgorithmWrongDigestLength)
+        );
+        let long = format!("sha512-{}", "A".repeat(100));
+        assert_eq!(sri_err(&long), (7 + 88, Kind::EncodingTooLong));
+        // 88 characters fit the length check but decode to 66 bytes
+        let overflow = format!("sha256-{}", "A".repeat(88));
+        assert_eq!(
+            sri_err(&overflow),
+            (95, Kind::OciRegisteredAlgorithmWrongDigestLength)
+        );
+        let sha384 = format!("sha384:{}", "0".repeat(200));
+        let e = Sri::from_digest(Digest::new(&sha384).unwrap())
+            .err()
+            .unwrap();
+        assert_eq!((e.index(), e.kind()), (207, Kind::DecodeInvalidLength));
+
+        let md5 = Digest::new("md5:d41d8cd98f00b204e9800998ecf8427e").unwrap();
+        let e = Sri::from_digest(md5).err().unwrap();
+        assert_eq!(e.kind(), Kind::DecodeWrongAlgorithm);
+    }
+
+    #[test]
+    fn bare_hex() {
+        let upper = SHA256_HELLO.to_ascii_uppercase();
+        let digest = from_hex("sha256", &upper).unwrap();
+        assert_eq!(digest.digest().encoded().to_str(), SHA256_HELLO);
+        let e = from_hex("sha256", &SHA256_HELLO[..62]).err().unwrap();
+        assert_eq!(
+            (e.index(), e.kind()),
+            (62, Kind::OciRegisteredAlgorithmWrongDigestLength)
+        );
+        let e = from_hex("sha256", "abc").err().unwrap();
+        assert_eq!((e.index(), e.kind()), (3, Kind::DecodeInvalidLength));
+        let e = from_hex("sha256", "ab-d").err().unwrap();
+        assert_eq!((e.index(), e.kind()), (2, Kind::EncodedInvalidChar));
+        let e = from_hex("sha256", "").err().unwrap();
+        assert_eq!((e.index(), e.kind()), (0, Kind::EncodedMissing));
+        let e = from_hex("sha 256", SHA256_HELLO).err().unwrap();
+        assert_eq!((e.index(), e.kind()), (3, Kind::AlgorithmInvalidChar));
+    }
+
+    #[test]
+    fn checksum_lines() {
+        let fixtures = include_str!("../../tests/fixtures/blob_digests.txt");
+        for fixture in fixtures.lines() {
+            let (digest, path) = fixture.split_once("  ").unwrap();
+            let digest = Digest::new(digest).unwrap();
+            let line = format!("{}  {path}\n", digest.encoded().to_str());
+            let parsed = match digest.algorithm().to_str() {
+                "blake3" => ChecksumLine::parse_with_algorithm(&line, "blake3"),
+                _ => ChecksumLine::parse(&line),
+            }
+            .unwrap();
+            assert_eq!(parsed.digest().to_str(), digest.to_str());
+            assert_eq!(parsed.path(), path);
+            assert!(!parsed.binary());
+            assert_eq!(format!("{parsed}\n"), line);
+            assert_eq!(ChecksumLine::new(digest, path).unwrap(), parsed);
+        }
+    }
+
+    #[test]
+    fn checksum_line_errors() {
+        let hex = SHA256_HELLO;
+        assert_eq!(
+            line_err(&hex[..60]),
+            (60, Kind::OciRegisteredAlgorithmWrongDigestLength)
+        );
+        assert_eq!(line_err(&format!("{hex}g")), (64, Kind::EncodedInvalidChar));
+        assert_eq!(line_err(hex), (64, Kind::PathMissing));
+        assert_eq!(line_err(&format!("{hex}  ")), (66, Kind::PathMissing));
+        assert_eq!(line_err(&format!("{hex} -x")), (65, Kind::PathInvalidChar));
+    }
+}
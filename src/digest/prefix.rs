@@ -0,0 +1,195 @@
+//! # Short digest prefixes
+//! Container tools let users refer to images and blobs by a prefix of their
+//! digest, like `docker rmi 3f57d9` or `docker rmi sha256:3f57d9`. A
+//! [`DigestPrefix`] parses such a prefix and looks it up in a set of full
+//! digests.
+//!
+//! Prefixes may be much shorter than a valid digest: the encoded section only
+//! needs to be one character long. The algorithm, if present, must still be a
+//! complete, valid algorithm.
+//! ```rust
+//! use container_image_dist_ref::digest::{
+//!     prefix::{DigestPrefix, Match},
+//!     Digest,
+//! };
+//! let hello = Digest::new(
+//!     "sha256:09ca7e4eaa6e8ae9c7d261167129184883644d07dfba7cbfbc4c8a2e08360d5b",
+//! )
+//! .unwrap();
+//! let other = Digest::new(
+//!     "sha256:09cbb0ae45a36bbb6f4ad5f2ecd09ca2e1b6c6cd2a8ab3e2e5ac3c3f3fe17a0a",
+//! )
+//! .unwrap();
+//! let digests = [hello, other];
+//!
+//! let prefix = DigestPrefix::new("sha256:09ca").unwrap();
+//! assert!(matches!(prefix.find(digests), Match::Unique(d) if d.to_str() == hello.to_str()));
+//! let prefix = DigestPrefix::new("09c").unwrap();
+//! assert!(matches!(prefix.find(digests), Match::Ambiguous(..)));
+//! let prefix = DigestPrefix::new("sha512:09c").unwrap();
+//! assert!(matches!(prefix.find(digests), Match::None));
+//!
+//! assert_eq!(hello.short_id(), "09ca7e4eaa6e");
+//! ```
+
+use super::{algorithm::Algorithm, encoded::Encoded, Compliance, Digest};
+use crate::err;
+
+type Error = err::Error<u16>;
+
+/// The number of encoded characters conventionally shown for a short image ID.
+pub const SHORT_LEN: usize = 12;
+
+/// A possibly-truncated digest: an optional `algorithm:` followed by the start
+/// of an encoded section.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DigestPrefix<'src> {
+    src: &'src str,
+    algorithm: Option<Algorithm<'src>>,
+    encoded: Encoded<'src>,
+}
+
+/// The result of looking up a [`DigestPrefix`] in a set of digests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Match<'d> {
+    /// No digest starts with the prefix.
+    None,
+    /// Exactly one distinct digest starts with the prefix.
+    Unique(Digest<'d>),
+    /// At least two distinct digests start with the prefix; these are the first two.
+    Ambiguous(Digest<'d>, Digest<'d>),
+}
+
+impl<'src> DigestPrefix<'src> {
+    /// Parse a prefix like `sha256:3f57d9` or `3f57d9`. The encoded section
+    /// may contain any characters allowed by the OCI image spec, but no length
+    /// checks are applied.
+    pub fn new(src: &'src str) -> Result<Self, Error> {
+        let (algorithm, start) = match src.find(':') {
+            Some(colon) => {
+                let (algorithm, _) = Algorithm::from_exact_match(&src[..colon])?;
+                (Some(algorithm), colon + 1)
+            }
+            None => (None, 0),
+        };
+        #[allow(clippy::cast_possible_truncation)]
+        let encoded = Encoded::new(&src[start..], Compliance::Universal)
+            .map_err(|e| Error::at(e.index().saturating_add(start as u16), e.kind()))?; // safe since algorithms are at most 255 characters
+        Ok(Self {
+            src,
+            algorithm,
+            encoded,
+        })
+    }
+    /// The original prefix string.
+    pub const fn to_str(&self) -> &'src str {
+        self.src
+    }
+    /// The algorithm, if the prefix included one.
+    pub const fn algorithm(&self) -> Option<&Algorithm<'src>> {
+        self.algorithm.as_ref()
+    }
+    /// The start of the encoded section.
+    pub const fn encoded(&self) -> &Encoded<'src> {
+        &self.encoded
+    }
+    /// Whether `digest` starts with this prefix.
+    pub fn matches(&self, digest: Digest<'_>) -> bool {
+        self.algorithm
+            .as_ref()
+            .is_none_or(|a| a.to_str() == digest.algorithm().to_str())
+            && digest.encoded().to_str().starts_with(self.encoded.to_str())
+    }
+    /// Find the digests starting with this prefix. Repeated copies of the same
+    /// digest count as one match.
+    pub fn find<'d>(&self, digests: impl IntoIterator<Item = Digest<'d>>) -> Match<'d> {
+        let mut found = Match::None;
+        for digest in digests.into_iter().filter(|d| self.matches(*d)) {
+            found = match found {
+                Match::None => Match::Unique(digest),
+                Match::Unique(first) if first.to_str() == digest.to_str() => found,
+                Match::Unique(first) => return Match::Ambiguous(first, digest),
+                Match::Ambiguous(..) => unreachable!(), // returned above
+            };
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::err::Kind;
+
+    const DIGESTS: [&str; 4] = [
+        "sha256:853ff93762a06ddbf722c4ebe9ddd66d8f63ddaea97f521c3ecc20da7c976020",
+        "sha256:853f0f2192b8178c648da3d7ab4b2c3d4f2c8b3be2c2f3e6f1a7f6a3d57d2b1c",
+        "sha512:853ff93762a06ddbf722c4ebe9ddd66d8f63ddaea97f521c3ecc20da7c9760200000000000000000000000000000000000000000000000000000000000000000",
+        "sha256:f0dabff2192b8178c648da3d7ab4b2c3d4f2c8b3be2c2f3e6f1a7f6a3d57d2b1",
+    ];
+
+    fn find(prefix: &str) -> Match<'static> {
+        DigestPrefix::new(prefix)
+            .unwrap()
+            .find(DIGESTS.map(|d| Digest::new(d).unwrap()))
+    }
+    fn unique(prefix: &str) -> &'static str {
+        match find(prefix) {
+            Match::Unique(d) => d.to_str(),
+            _ => panic!("{prefix} should match exactly one digest"),
+        }
+    }
+    fn err(prefix: &str) -> (u16, Kind) {
+        let e = DigestPrefix::new(prefix).err().unwrap();
+        (e.index(), e.kind())
+    }
+
+    #[test]
+    fn matching() {
+        assert_eq!(unique("f"), DIGESTS[3]);
+        assert_eq!(unique("sha256:853ff"), DIGESTS[0]);
+        assert_eq!(unique("sha512:8"), DIGESTS[2]);
+        assert_eq!(unique("853f0"), DIGESTS[1]);
+        assert!(matches!(
+            find("853ff"),
+            Match::Ambiguous(a, b) if a.to_str() == DIGESTS[0] && b.to_str() == DIGESTS[2]
+        ));
+        assert!(matches!(find("sha256:853"), Match::Ambiguous(..)));
+        assert!(matches!(find("853fF"), Match::None));
+        assert!(matches!(find("blake3:8"), Match::None));
+        assert!(matches!(find(DIGESTS[3]), Match::Unique(_)));
+    }
+
+    #[test]
+    fn duplicates_are_one_match() {
+        let d = Digest::new(DIGESTS[0]).unwrap();
+        let prefix = DigestPrefix::new("853ff93762a0").unwrap();
+        assert_eq!(prefix.find([d, d]), Match::Unique(d));
+        assert_eq!(prefix.find([]), Match::None);
+        let copy = prefix;
+        assert_eq!(copy, prefix);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(err(""), (0, Kind::EncodedMissing));
+        assert_eq!(err("sha256:"), (7, Kind::EncodedMissing));
+        assert_eq!(err(":853f"), (0, Kind::AlgorithmMissing));
+        assert_eq!(err("sha256:85/3"), (9, Kind::EncodedInvalidChar));
+        assert_eq!(err("sha 256:85"), (3, Kind::AlgorithmInvalidChar));
+    }
+
+    #[test]
+    fn short_ids() {
+        let d = Digest::new(DIGESTS[0]).unwrap();
+        assert_eq!(d.short_id(), "853ff93762a0");
+        let d = Digest::new("md5:d41d8cd9").unwrap();
+        assert_eq!(d.short_id(), "d41d8cd9");
+        // prefixes built from short IDs always match their digest
+        for src in DIGESTS {
+            let d = Digest::new(src).unwrap();
+            assert!(DigestPrefix::new(d.short_id()).unwrap().matches(d));
+        }
+    }
+}
@@ -0,0 +1,392 @@
+//! # Raw digest values
+//! Conversions between encoded digest values and the raw bytes they encode,
+//! without a third-party hex or base64 crate.
+//! ```rust
+//! use container_image_dist_ref::digest::{raw::Sha256Digest, Digest};
+//! let src = "sha256:09ca7e4eaa6e8ae9c7d261167129184883644d07dfba7cbfbc4c8a2e08360d5b";
+//! let key = Sha256Digest::try_from(Digest::new(src).unwrap()).unwrap();
+//! assert_eq!(key.as_bytes()[..4], [0x09, 0xca, 0x7e, 0x4e]);
+//! assert_eq!(key.to_digest().to_str(), src);
+//!
+//! let mut buf = [0u8; 64];
+//! let encoded = Digest::new("b64u:LCa0a2j_xo_5m0U8HTBBNBNCLXBkg7-g-YpeiGJm564").unwrap().encoded();
+//! let n = encoded.decode_base64url_into(&mut buf).unwrap();
+//! assert_eq!(n, 32);
+//! ```
+
+use core::fmt;
+
+use super::{hash::OwnedDigest, Digest};
+use crate::err::{
+    self,
+    Kind::{DecodeBufferTooSmall, DecodeInvalidLength, DecodeWrongAlgorithm, EncodedInvalidChar},
+};
+
+type Error = err::Error<u16>;
+
+const HEX: &[u8; 16] = b"0123456789abcdef";
+
+/// The two base64 alphabets from RFC 4648, which differ in their last two characters.
+#[derive(Clone, Copy)]
+pub(crate) enum Alphabet {
+    /// `+` and `/`, as used in subresource integrity strings
+    Standard,
+    /// `-` and `_`, as used in OCI digests
+    Url,
+}
+
+impl Alphabet {
+    const fn table(self) -> &'static [u8; 64] {
+        match self {
+            Alphabet::Standard => {
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/"
+            }
+            Alphabet::Url => b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_",
+        }
+    }
+    #[allow(clippy::arithmetic_side_effects)] // safe since each arm bounds c
+    const fn value(self, c: u8) -> Option<u8> {
+        match (c, self) {
+            (b'A'..=b'Z', _) => Some(c - b'A'),
+            (b'a'..=b'z', _) => Some(c - b'a' + 26),
+            (b'0'..=b'9', _) => Some(c - b'0' + 52),
+            (b'+', Alphabet::Standard) | (b'-', Alphabet::Url) => Some(62),
+            (b'/', Alphabet::Standard) | (b'_', Alphabet::Url) => Some(63),
+            _ => None,
+        }
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+const fn index(i: usize) -> u16 {
+    i as u16 // safe since encoded sections are at most Limits::MAX_ENCODED long
+}
+
+/// write `bytes` as lowercase hex into `out`, which must be twice as long
+pub(crate) fn encode_hex(bytes: &[u8], out: &mut [u8]) {
+    for (pair, byte) in out.as_chunks_mut::<2>().0.iter_mut().zip(bytes) {
+        *pair = [HEX[usize::from(byte >> 4)], HEX[usize::from(byte & 0xf)]];
+    }
+}
+
+/// the length of the unpadded base64 encoding of `n` bytes
+pub(crate) const fn base64_len(n: usize) -> usize {
+    (n * 4).div_ceil(3)
+}
+
+/// write `bytes` as unpadded base64 into `out`, which must be
+/// [`base64_len`] characters long
+#[allow(clippy::arithmetic_side_effects)] // bits stays below 14
+pub(crate) fn encode_base64(bytes: &[u8], out: &mut [u8], alphabet: Alphabet) {
+    let table = alphabet.table();
+    let (mut acc, mut bits, mut n) = (0u32, 0u32, 0);
+    for &byte in bytes {
+        acc = (acc << 8) | u32::from(byte);
+        bits += 8;
+        while bits >= 6 {
+            bits -= 6;
+            out[n] = table[((acc >> bits) & 0x3f) as usize];
+            n += 1;
+        }
+    }
+    if bits > 0 {
+        out[n] = table[((acc << (6 - bits)) & 0x3f) as usize];
+    }
+}
+
+#[allow(clippy::arithmetic_side_effects)] // safe since each arm bounds c
+const fn hex_value(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// decode hex digits of either case into `out`, returning the number of bytes written
+pub(crate) fn decode_hex(src: &str, out: &mut [u8]) -> Result<usize, Error> {
+    let src = src.as_bytes();
+    if !src.len().is_multiple_of(2) {
+        return Error::at(index(src.len()), DecodeInvalidLength).into();
+    }
+    let len = src.len() / 2;
+    if out.len() < len {
+        return Error::at(index(src.len()), DecodeBufferTooSmall).into();
+    }
+    for (i, (pair, byte)) in src
+        .as_chunks::<2>()
+        .0
+        .iter()
+        .zip(out.iter_mut())
+        .enumerate()
+    {
+        let high = hex_value(pair[0]).ok_or(Error::at(index(2 * i), EncodedInvalidChar))?;
+        let low = hex_value(pair[1]).ok_or(Error::at(index(2 * i + 1), EncodedInvalidChar))?;
+        *byte = (high << 4) | low;
+    }
+    Ok(len)
+}
+
+/// decode padded or unpadded base64 into `out`, returning the number of bytes written
+#[allow(clippy::arithmetic_side_effects)] // bits stays below 14
+pub(crate) fn decode_base64(src: &str, out: &mut [u8], alphabet: Alphabet) -> Result<usize, Error> {
+    let src = src.as_bytes();
+    let data_len = src.iter().rposition(|&c| c != b'=').map_or(0, |i| i + 1);
+    let padding = src.len() - data_len;
+    if padding > 2 || (padding > 0 && !src.len().is_multiple_of(4)) || data_len % 4 == 1 {
+        return Error::at(index(src.len()), DecodeInvalidLength).into();
+    }
+    let len = data_len * 3 / 4;
+    if out.len() < len {
+        return Error::at(index(src.len()), DecodeBufferTooSmall).into();
+    }
+    let (mut acc, mut bits, mut n) = (0u32, 0u32, 0);
+    for (i, &c) in src[..data_len].iter().enumerate() {
+        let value = alphabet
+            .value(c)
+            .ok_or(Error::at(index(i), EncodedInvalidChar))?;
+        acc = (acc << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            #[allow(clippy::cast_possible_truncation)]
+            {
+                out[n] = (acc >> bits) as u8; // truncation intended: keep the low 8 bits
+            }
+            n += 1;
+        }
+    }
+    // reject non-canonical encodings with leftover bits set in the last character
+    if acc & ((1 << bits) - 1) != 0 {
+        return Error::at(index(data_len - 1), EncodedInvalidChar).into();
+    }
+    Ok(n)
+}
+
+/// Implement a fixed-size raw digest for a hex-encoded algorithm.
+macro_rules! raw_digest {
+    ($(#[$meta:meta])* $name:ident, $algorithm:literal, $len:literal) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+        pub struct $name(pub [u8; $len]);
+
+        impl $name {
+            /// The name of the algorithm this digest was computed with.
+            pub const ALGORITHM: &'static str = $algorithm;
+
+            #[allow(missing_docs)]
+            pub const fn as_bytes(&self) -> &[u8; $len] {
+                &self.0
+            }
+            /// Format this digest as a digest string.
+            pub fn to_digest(&self) -> OwnedDigest {
+                OwnedDigest::new(Self::ALGORITHM, &self.0)
+            }
+        }
+
+        impl From<[u8; $len]> for $name {
+            fn from(bytes: [u8; $len]) -> Self {
+                Self(bytes)
+            }
+        }
+
+        impl TryFrom<Digest<'_>> for $name {
+            type Error = Error;
+            /// Decode a digest string using this digest's algorithm.
+            fn try_from(digest: Digest<'_>) -> Result<Self, Error> {
+                if digest.algorithm().to_str() != Self::ALGORITHM {
+                    return Error::at(0, DecodeWrongAlgorithm).into();
+                }
+                let encoded = digest.encoded();
+                let mut bytes = [0; $len];
+                let len = encoded
+                    .decode_hex_into(&mut bytes)
+                    .map_err(|e| Error::at(e.index().saturating_add(index(Self::ALGORITHM.len() + 1)), e.kind()))?;
+                if len != $len {
+                    return Error::at(index(digest.to_str().len()), DecodeInvalidLength).into();
+                }
+                Ok(Self(bytes))
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Display::fmt(&self.to_digest(), f)
+            }
+        }
+    };
+}
+
+raw_digest!(
+    /// The raw bytes of a `sha256` digest.
+    Sha256Digest,
+    "sha256",
+    32
+);
+raw_digest!(
+    /// The raw bytes of a `sha512` digest.
+    Sha512Digest,
+    "sha512",
+    64
+);
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::digest::{hash::Sha512, registry::AlgorithmRegistry, Compliance};
+    use crate::{err::Kind, Limits};
+
+    fn decode_hex_err(src: &str, out: &mut [u8]) -> (u16, Kind) {
+        let e = decode_hex(src, out).err().unwrap();
+        (e.index(), e.kind())
+    }
+    fn decode_b64_err(src: &str, out: &mut [u8]) -> (u16, Kind) {
+        let e = decode_base64(src, out, Alphabet::Url).err().unwrap();
+        (e.index(), e.kind())
+    }
+
+    #[test]
+    fn hex_round_trips() {
+        let bytes: [u8; 256] = core::array::from_fn(|i| u8::try_from(i).unwrap());
+        let mut hex = [0; 512];
+        encode_hex(&bytes, &mut hex);
+        let hex = core::str::from_utf8(&hex).unwrap();
+        assert!(hex.starts_with("000102"));
+        assert!(hex.ends_with("fdfeff"));
+        let mut out = [0; 256];
+        assert_eq!(decode_hex(hex, &mut out).unwrap(), 256);
+        assert_eq!(out, bytes);
+        assert_eq!(decode_hex("ABcd", &mut out).unwrap(), 2);
+        assert_eq!(out[..2], [0xab, 0xcd]);
+    }
+
+    #[test]
+    fn hex_errors() {
+        let mut out = [0; 4];
+        assert_eq!(
+            decode_hex_err("abc", &mut out),
+            (3, Kind::DecodeInvalidLength)
+        );
+        assert_eq!(
+            decode_hex_err("abcdefabcd", &mut out),
+            (10, Kind::DecodeBufferTooSmall)
+        );
+        assert_eq!(
+            decode_hex_err("ab_d", &mut out),
+            (2, Kind::EncodedInvalidChar)
+        );
+        assert_eq!(
+            decode_hex_err("abcg", &mut out),
+            (3, Kind::EncodedInvalidChar)
+        );
+    }
+
+    #[test]
+    fn base64url_round_trips() {
+        // RFC 4648 test vectors, translated to the url-safe alphabet
+        for (bytes, encoded) in [
+            (&b""[..], ""),
+            (b"f", "Zg"),
+            (b"fo", "Zm8"),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg"),
+            (b"fooba", "Zm9vYmE"),
+            (b"foobar", "Zm9vYmFy"),
+            (&[0xfb, 0xff], "-_8"),
+        ] {
+            let mut buf = [0; 8];
+            let len = base64_len(bytes.len());
+            encode_base64(bytes, &mut buf[..len], Alphabet::Url);
+            assert_eq!(&buf[..len], encoded.as_bytes());
+
+            let mut out = [0; 6];
+            assert_eq!(
+                decode_base64(encoded, &mut out, Alphabet::Url).unwrap(),
+                bytes.len()
+            );
+            assert_eq!(&out[..bytes.len()], bytes);
+            let padded = [encoded, "==", "="].concat();
+            let padded = &padded[..encoded.len().next_multiple_of(4)];
+            assert_eq!(
+                decode_base64(padded, &mut out, Alphabet::Url).unwrap(),
+                bytes.len()
+            );
+        }
+    }
+
+    #[test]
+    fn base64url_errors() {
+        let mut out = [0; 4];
+        assert_eq!(
+            decode_b64_err("Z", &mut out),
+            (1, Kind::DecodeInvalidLength)
+        );
+        assert_eq!(
+            decode_b64_err("Zg=", &mut out),
+            (3, Kind::DecodeInvalidLength)
+        );
+        assert_eq!(
+            decode_b64_err("Zg===", &mut out),
+            (5, Kind::DecodeInvalidLength)
+        );
+        assert_eq!(
+            decode_b64_err("Zm9vYmFy", &mut out),
+            (8, Kind::DecodeBufferTooSmall)
+        );
+        assert_eq!(
+            decode_b64_err("Z=g=", &mut out),
+            (1, Kind::EncodedInvalidChar)
+        );
+        assert_eq!(
+            decode_b64_err("Zh", &mut out),
+            (1, Kind::EncodedInvalidChar)
+        );
+    }
+
+    #[test]
+    fn typed_digests() {
+        let owned = {
+            let mut hasher = Sha512::new();
+            hasher.update(b"abc");
+            hasher.digest()
+        };
+        let src = owned.to_str();
+        let digest = owned.digest();
+        let raw = Sha512Digest::try_from(digest).unwrap();
+        assert_eq!(raw.to_digest().to_str(), src);
+        assert_eq!(Sha512Digest::from(raw.0), raw);
+
+        let err = Sha256Digest::try_from(digest).err().unwrap();
+        assert_eq!((err.index(), err.kind()), (0, Kind::DecodeWrongAlgorithm));
+
+        // without the default registry, the encoded section might not be 64 hex digits
+        let short = Digest::with_registry("sha256:abcd", Limits::DEFAULT, AlgorithmRegistry::EMPTY)
+            .unwrap();
+        let err = Sha256Digest::try_from(short).err().unwrap();
+        assert_eq!((err.index(), err.kind()), (11, Kind::DecodeInvalidLength));
+        assert_eq!(short.compliance(), Compliance::Oci);
+    }
+
+    #[test]
+    fn bytes_to_digest_strings() {
+        let bytes: [u8; 32] = core::array::from_fn(|i| u8::try_from(i * 7).unwrap());
+        let owned = OwnedDigest::encode_base64url("sha256+b64u", &bytes).unwrap();
+        assert_eq!(owned.to_str().len(), "sha256+b64u:".len() + 43);
+        let mut out = [0; 32];
+        let encoded = owned.digest().encoded();
+        assert_eq!(encoded.decode_base64url_into(&mut out).unwrap(), 32);
+        assert_eq!(out, bytes);
+
+        let owned = OwnedDigest::encode_hex("sha256", &bytes).unwrap();
+        assert_eq!(Sha256Digest::try_from(owned.digest()).unwrap().0, bytes);
+        // registered algorithms still need the right length and charset
+        let err = OwnedDigest::encode_base64url("sha256", &bytes)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), Kind::OciRegisteredDigestInvalidChar);
+        let err = OwnedDigest::encode_hex("a", &[0; 68]).err().unwrap();
+        assert_eq!(err.kind(), Kind::EncodingTooLong);
+    }
+}
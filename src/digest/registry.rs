@@ -0,0 +1,258 @@
+//! # Algorithm registries
+//! An [`AlgorithmRegistry`] is a table of rules for the encoded section of
+//! digests using particular algorithms. Digests using an algorithm without a
+//! rule are validated by the OCI image spec and `distribution/reference`
+//! grammars alone.
+//!
+//! The [default registry](AlgorithmRegistry::DEFAULT) contains the algorithms
+//! registered by the [OCI image spec](https://github.com/opencontainers/image-spec/blob/v1.1.0/descriptor.md#registered-algorithms):
+//! `sha256`, `sha512`, and `blake3`.
+//! ```rust
+//! use container_image_dist_ref::{
+//!     digest::registry::{AlgorithmRegistry, Charset, Length, Rule},
+//!     digest::Digest,
+//!     err, Limits,
+//! };
+//! const RULES: &[Rule] = &[
+//!     Rule::SHA256,
+//!     Rule::new("sha256+b64u", Charset::Base64Url, Length::Exact(43)),
+//! ];
+//! let registry = AlgorithmRegistry::new(RULES);
+//!
+//! let src = "sha256+b64u:LCa0a2j_xo_5m0U8HTBBNBNCLXBkg7-g-YpeiGJm564";
+//! assert!(Digest::with_registry(src, Limits::DEFAULT, registry).is_ok());
+//! let err = Digest::with_registry("sha256+b64u:abc", Limits::DEFAULT, registry)
+//!     .err()
+//!     .unwrap();
+//! assert_eq!(err.kind(), err::Kind::OciRegisteredAlgorithmWrongDigestLength);
+//! ```
+
+use super::Compliance;
+use crate::err;
+
+type Error = err::Error<u16>;
+
+/// The characters allowed in an encoded digest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Charset {
+    /// `[0-9a-f]`
+    LowerHex,
+    /// `[A-Za-z0-9_-]`, optionally followed by `=` padding.
+    Base64Url,
+}
+
+/// The number of characters allowed in an encoded digest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Length {
+    #[allow(missing_docs)]
+    Exact(u16),
+    #[allow(missing_docs)]
+    AtLeast(u16),
+}
+
+/// The constraints on the encoded section of digests using one algorithm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rule<'a> {
+    algorithm: &'a str,
+    charset: Charset,
+    length: Length,
+    compliance: Compliance,
+    /// the error reported for an encoded section of the wrong length
+    wrong_length: err::Kind,
+}
+
+impl<'a> Rule<'a> {
+    /// `sha256`: 64 lowercase hex digits.
+    pub const SHA256: Rule<'static> = Rule::new("sha256", Charset::LowerHex, Length::Exact(64));
+    /// `sha512`: 128 lowercase hex digits.
+    pub const SHA512: Rule<'static> = Rule::new("sha512", Charset::LowerHex, Length::Exact(128));
+    /// `blake3`: 64 lowercase hex digits.
+    pub const BLAKE3: Rule<'static> = Rule {
+        wrong_length: err::Kind::Blake3WrongDigestLength,
+        ..Rule::new("blake3", Charset::LowerHex, Length::Exact(64))
+    };
+
+    /// A rule for digests using `algorithm`. Hex-encoded digests may comply
+    /// with both the OCI image spec and `distribution/reference`; base64url-encoded
+    /// digests may only comply with the OCI image spec.
+    pub const fn new(algorithm: &'a str, charset: Charset, length: Length) -> Self {
+        Self {
+            algorithm,
+            charset,
+            length,
+            compliance: match charset {
+                Charset::LowerHex => Compliance::Universal,
+                Charset::Base64Url => Compliance::Oci,
+            },
+            wrong_length: err::Kind::OciRegisteredAlgorithmWrongDigestLength,
+        }
+    }
+    /// Restrict which standards digests using this rule's algorithm may comply with.
+    pub const fn with_compliance(self, compliance: Compliance) -> Self {
+        Self { compliance, ..self }
+    }
+    #[allow(missing_docs)]
+    pub const fn algorithm(self) -> &'a str {
+        self.algorithm
+    }
+    #[allow(missing_docs)]
+    pub const fn charset(self) -> Charset {
+        self.charset
+    }
+    #[allow(missing_docs)]
+    pub const fn length(self) -> Length {
+        self.length
+    }
+    #[allow(missing_docs)]
+    pub const fn compliance(self) -> Compliance {
+        self.compliance
+    }
+
+    /// check the charset and length of an encoded section, reporting errors
+    /// relative to the start of `encoded`.
+    pub(crate) fn validate(self, encoded: &str) -> Result<(), Error> {
+        let mut padding = false;
+        for (i, c) in encoded.bytes().enumerate() {
+            let ok = match self.charset {
+                Charset::LowerHex => matches!(c, b'a'..=b'f' | b'0'..=b'9'),
+                Charset::Base64Url => {
+                    padding |= c == b'=';
+                    if padding {
+                        c == b'='
+                    } else {
+                        matches!(c, b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-')
+                    }
+                }
+            };
+            if !ok {
+                #[allow(clippy::cast_possible_truncation)]
+                return Error::at(
+                    i as u16, // safe since encoded sections are at most Limits::MAX_ENCODED long
+                    err::Kind::OciRegisteredDigestInvalidChar,
+                )
+                .into();
+            }
+        }
+        let len = encoded.len();
+        let ok = match self.length {
+            Length::Exact(n) => len == usize::from(n),
+            Length::AtLeast(n) => len >= usize::from(n),
+        };
+        if ok {
+            Ok(())
+        } else {
+            #[allow(clippy::cast_possible_truncation)]
+            Error::at(len as u16, self.wrong_length).into() // safe since len <= Limits::MAX_ENCODED
+        }
+    }
+}
+
+/// A table of [`Rule`]s, looked up by exact algorithm name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AlgorithmRegistry<'a> {
+    rules: &'a [Rule<'a>],
+}
+
+impl<'a> AlgorithmRegistry<'a> {
+    /// The algorithms registered by the OCI image spec.
+    pub const DEFAULT: AlgorithmRegistry<'static> =
+        AlgorithmRegistry::new(&[Rule::SHA256, Rule::SHA512, Rule::BLAKE3]);
+    /// A registry without any rules.
+    pub const EMPTY: AlgorithmRegistry<'static> = AlgorithmRegistry::new(&[]);
+
+    /// A registry using `rules`. If two rules share an algorithm, the first wins.
+    pub const fn new(rules: &'a [Rule<'a>]) -> Self {
+        Self { rules }
+    }
+    #[allow(missing_docs)]
+    pub const fn rules(self) -> &'a [Rule<'a>] {
+        self.rules
+    }
+    /// The rule for an algorithm, if any.
+    pub fn get(self, algorithm: &str) -> Option<Rule<'a>> {
+        self.rules
+            .iter()
+            .find(|rule| rule.algorithm == algorithm)
+            .copied()
+    }
+}
+
+impl Default for AlgorithmRegistry<'_> {
+    fn default() -> Self {
+        AlgorithmRegistry::DEFAULT
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::{digest::Digest, err::Kind, ImgRef, Limits};
+
+    fn err(src: &str, registry: AlgorithmRegistry) -> (u16, Kind) {
+        let e = Digest::with_registry(src, Limits::DEFAULT, registry)
+            .err()
+            .unwrap();
+        (e.index(), e.kind())
+    }
+
+    #[test]
+    fn default_registry_matches_oci_registered_algorithms() {
+        let names: [&str; 3] =
+            core::array::from_fn(|i| AlgorithmRegistry::DEFAULT.rules()[i].algorithm());
+        assert_eq!(names, ["sha256", "sha512", "blake3"]);
+        assert!(Digest::new("sha256:abc").is_err());
+        assert!(
+            Digest::with_registry("sha256:abc", Limits::DEFAULT, AlgorithmRegistry::EMPTY).is_ok()
+        );
+    }
+
+    #[test]
+    fn custom_rules() {
+        const RULES: &[Rule] = &[
+            Rule::new("b64", Charset::Base64Url, Length::Exact(8)),
+            Rule::new("multihash+hex", Charset::LowerHex, Length::AtLeast(4)),
+            Rule::new("dist", Charset::LowerHex, Length::AtLeast(1))
+                .with_compliance(Compliance::Distribution),
+        ];
+        let registry = AlgorithmRegistry::new(RULES);
+        let ok = |src| {
+            Digest::with_registry(src, Limits::DEFAULT, registry)
+                .unwrap()
+                .compliance()
+        };
+
+        assert_eq!(ok("b64:aB-_09=="), Compliance::Oci);
+        assert_eq!(ok("b64:abcdef01"), Compliance::Oci);
+        assert_eq!(
+            err("b64:aB-_0=9=", registry),
+            (6, Kind::OciRegisteredDigestInvalidChar)
+        );
+        assert_eq!(
+            err("b64:aB-_09", registry),
+            (6, Kind::OciRegisteredAlgorithmWrongDigestLength)
+        );
+
+        assert_eq!(ok("multihash+hex:1220"), Compliance::Oci);
+        assert_eq!(
+            err("multihash+hex:122", registry).1,
+            Kind::OciRegisteredAlgorithmWrongDigestLength
+        );
+        assert_eq!(
+            err("multihash+hex:12AB", registry).1,
+            Kind::OciRegisteredDigestInvalidChar
+        );
+
+        assert_eq!(
+            ok("dist:0123456789abcdef0123456789abcdef"),
+            Compliance::Distribution
+        );
+        assert_eq!(err("dist:abc", registry).1, Kind::EncodingTooShort);
+
+        // registries apply to digests within references too
+        let img = ImgRef::with_registry("repo@b64:aB-_09==", Limits::DEFAULT, registry).unwrap();
+        assert_eq!(img.digest().unwrap().to_str(), "b64:aB-_09==");
+        assert!(ImgRef::new("repo@sha256:abc").is_err());
+        assert!(ImgRef::with_registry("repo@sha256:abc", Limits::DEFAULT, registry).is_ok());
+    }
+}
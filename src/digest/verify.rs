@@ -0,0 +1,229 @@
+//! # Verifying content against a digest
+//! A [`Verifier`] wraps a reader or writer, hashing every byte that passes
+//! through it. A reader fails at EOF if the content doesn't match the expected
+//! digest; a writer is checked by calling [`Verifier::finish`].
+//! ```rust
+//! use std::io::Read;
+//! use container_image_dist_ref::digest::Digest;
+//! let expected = Digest::new(
+//!     "sha256:09ca7e4eaa6e8ae9c7d261167129184883644d07dfba7cbfbc4c8a2e08360d5b",
+//! )
+//! .unwrap();
+//!
+//! let mut content = String::new();
+//! let mut reader = expected.verifier(&b"hello, world"[..]).unwrap();
+//! reader.read_to_string(&mut content).unwrap();
+//! assert_eq!(content, "hello, world");
+//!
+//! let mut reader = expected.verifier(&b"goodbye, world"[..]).unwrap();
+//! let err = reader.read_to_string(&mut content).unwrap_err();
+//! assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+//! ```
+
+use core::fmt;
+use std::{boxed::Box, io};
+
+use super::{
+    hash::{Hasher, OwnedDigest},
+    Digest,
+};
+
+/// The error returned when content doesn't match the expected digest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    expected: OwnedDigest,
+    actual: OwnedDigest,
+}
+
+impl Mismatch {
+    /// The digest the content was expected to have.
+    pub fn expected(&self) -> Digest<'_> {
+        self.expected.digest()
+    }
+    /// The digest the content actually had.
+    pub fn actual(&self) -> Digest<'_> {
+        self.actual.digest()
+    }
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "digest mismatch: expected {}, got {}",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for Mismatch {}
+
+/// A reader or writer that hashes everything passing through it. Created by
+/// [`Digest::verifier`].
+///
+/// When used as a [`io::Read`], reaching EOF with content that doesn't match
+/// the expected digest returns an [`io::ErrorKind::InvalidData`] error wrapping
+/// a [`Mismatch`]. When used as a [`io::Write`], call [`Verifier::finish`] after
+/// writing all the content.
+#[derive(Clone)]
+pub struct Verifier<T> {
+    inner: T,
+    expected: OwnedDigest,
+    hasher: Hasher,
+}
+
+impl<T> Verifier<T> {
+    pub(crate) fn new(expected: Digest<'_>, inner: T) -> Option<Self> {
+        let hasher = Hasher::for_algorithm(expected.algorithm().to_str())?;
+        // every supported algorithm's digests fit in an OwnedDigest, but digests
+        // parsed with a custom registry may not be valid under the default one
+        let expected = OwnedDigest::copy_of(expected).ok()?;
+        Some(Self {
+            inner,
+            expected,
+            hasher,
+        })
+    }
+    /// check the content hashed so far against the expected digest
+    fn check(&self) -> Result<(), Box<Mismatch>> {
+        let actual = self.hasher.clone().digest();
+        if actual.digest() == self.expected.digest() {
+            Ok(())
+        } else {
+            Err(Box::new(Mismatch {
+                expected: self.expected,
+                actual,
+            }))
+        }
+    }
+    /// Check all the content hashed so far against the expected digest,
+    /// returning the wrapped reader or writer if it matches.
+    pub fn finish(self) -> Result<T, Box<Mismatch>> {
+        self.check()?;
+        Ok(self.inner)
+    }
+    /// Borrow the wrapped reader or writer.
+    pub const fn get_ref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<R: io::Read> io::Read for Verifier<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if n == 0 && !buf.is_empty() {
+            self.check()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, *e))?;
+        }
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+impl<W: io::Write> io::Write for Verifier<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::{
+        fs::File,
+        io::Read,
+        path::PathBuf,
+        string::{String, ToString},
+        vec::Vec,
+    };
+
+    use super::*;
+
+    /// `(digest, path)` pairs for each file in `tests/fixtures/blobs`
+    fn fixtures() -> impl Iterator<Item = (&'static str, PathBuf)> {
+        include_str!("../../tests/fixtures/blob_digests.txt")
+            .lines()
+            .map(|line| {
+                let (digest, name) = line.split_once("  ").unwrap();
+                let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                    .join("tests/fixtures/blobs")
+                    .join(name);
+                (digest, path)
+            })
+    }
+
+    /// replace the last character of the digest with a different hex digit
+    fn corrupt(digest: &str) -> String {
+        let (head, last) = digest.split_at(digest.len() - 1);
+        let mut corrupted = String::from(head);
+        corrupted.push(if last == "0" { '1' } else { '0' });
+        corrupted
+    }
+
+    #[test]
+    fn reading_fixtures() {
+        for (digest, path) in fixtures() {
+            let expected = std::fs::read(&path).unwrap();
+            let digest = Digest::new(digest).unwrap();
+            let mut content = Vec::new();
+            let mut reader = digest.verifier(File::open(&path).unwrap()).unwrap();
+            reader.read_to_end(&mut content).unwrap();
+            assert_eq!(content, expected, "{path:?}");
+
+            let wrong = corrupt(digest.to_str());
+            let wrong = Digest::new(&wrong).unwrap();
+            let mut reader = wrong.verifier(File::open(&path).unwrap()).unwrap();
+            let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            let mismatch = err.into_inner().unwrap().downcast::<Mismatch>().unwrap();
+            assert_eq!(mismatch.expected().to_str(), wrong.to_str());
+            assert_eq!(mismatch.actual().to_str(), digest.to_str());
+        }
+    }
+
+    #[test]
+    fn writing_fixtures() {
+        for (digest, path) in fixtures() {
+            let digest = Digest::new(digest).unwrap();
+            let mut writer = digest.verifier(Vec::new()).unwrap();
+            io::copy(&mut File::open(&path).unwrap(), &mut writer).unwrap();
+            assert_eq!(writer.finish().unwrap(), std::fs::read(&path).unwrap());
+
+            let wrong = corrupt(digest.to_str());
+            let mut writer = Digest::new(&wrong).unwrap().verifier(io::sink()).unwrap();
+            io::copy(&mut File::open(&path).unwrap(), &mut writer).unwrap();
+            let mismatch = writer.finish().err().unwrap();
+            assert_eq!(mismatch.actual().to_str(), digest.to_str());
+            assert!(mismatch.to_string().contains(&wrong));
+        }
+    }
+
+    #[test]
+    fn custom_registries() {
+        use crate::{digest::registry::AlgorithmRegistry, Limits};
+        let registry = AlgorithmRegistry::EMPTY;
+        let short = Digest::with_registry("sha256:abc", Limits::DEFAULT, registry).unwrap();
+        assert!(short.verifier(io::empty()).is_none());
+        let upper = "sha256:E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855";
+        let upper = Digest::with_registry(upper, Limits::DEFAULT, registry).unwrap();
+        assert!(upper.verifier(io::empty()).is_none());
+
+        let hex = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b856";
+        let wrong = Digest::with_registry(hex, Limits::DEFAULT, registry).unwrap();
+        let mut reader = wrong.verifier(io::empty()).unwrap();
+        let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        let mismatch = err.into_inner().unwrap().downcast::<Mismatch>().unwrap();
+        assert_eq!(mismatch.expected().to_str(), hex);
+    }
+
+    #[test]
+    fn unsupported_algorithms() {
+        let digest = Digest::new("md5:d41d8cd98f00b204e9800998ecf8427e").unwrap();
+        assert!(digest.verifier(io::empty()).is_none());
+    }
+}
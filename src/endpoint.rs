@@ -0,0 +1,263 @@
+//! # Registry HTTP API endpoints
+//! Build URLs for the [distribution v2 API](https://github.com/opencontainers/distribution-spec/blob/v1.1.0/spec.md#endpoints):
+//! ```text
+//! /v2/
+//! /v2/<name>/manifests/<tag or digest>
+//! /v2/<name>/blobs/<digest>
+//! /v2/<name>/tags/list
+//! /v2/<name>/referrers/<digest>
+//! ```
+//! Like distribution/reference, a reference's first component is only its
+//! registry if it looks like a hostname. References without a registry, or
+//! with `docker.io` or `index.docker.io`, are served by
+//! [`DOCKER_HUB_REGISTRY`], with `library/` in front of single-component
+//! names. IPv6 hosts keep their brackets, as URLs require.
+//!
+//! URLs are written to any [`fmt::Write`], so they can be built without
+//! allocating.
+//! ```rust
+//! use container_image_dist_ref::{endpoint::{Endpoint, Route, Scheme}, ImgRef};
+//! let img = ImgRef::new("alpine:3.19").unwrap();
+//! let endpoint = Endpoint::from_ref(&img);
+//! assert_eq!(
+//!     endpoint.url(Route::manifest_of(&img)).to_string(),
+//!     "https://registry-1.docker.io/v2/library/alpine/manifests/3.19"
+//! );
+//!
+//! let img = ImgRef::new("[::1]:5000/app").unwrap();
+//! let endpoint = Endpoint::from_ref(&img).with_scheme(Scheme::Http);
+//! let mut url = String::new();
+//! endpoint.write(Route::TagsList, &mut url).unwrap();
+//! assert_eq!(url, "http://[::1]:5000/v2/app/tags/list");
+//! ```
+
+use core::fmt;
+
+use crate::{
+    digest::Digest,
+    domain::{is_registry, Domain},
+    path::Path,
+    span::Lengthy,
+    ImgRef,
+};
+
+/// The host serving Docker Hub's registry API.
+pub const DOCKER_HUB_REGISTRY: &str = "registry-1.docker.io";
+
+/// Names of Docker Hub that references use, which aren't API hosts.
+const DOCKER_HUB_ALIASES: [&str; 3] = ["docker.io", "index.docker.io", DOCKER_HUB_REGISTRY];
+
+/// Whether to reach a registry over HTTPS or plain HTTP.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Scheme {
+    /// The default.
+    #[default]
+    Https,
+    /// For insecure registries.
+    Http,
+}
+
+impl Scheme {
+    /// The scheme as written in a URL.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Https => "https",
+            Self::Http => "http",
+        }
+    }
+}
+
+/// A registry API endpoint under `/v2/`.
+#[derive(Clone, Copy)]
+pub enum Route<'a> {
+    /// `/v2/`, which checks that the registry supports the API.
+    Base,
+    /// `/v2/<name>/manifests/<reference>`, where the reference is a tag or a digest.
+    Manifest(&'a str),
+    /// `/v2/<name>/blobs/<digest>`.
+    Blob(Digest<'a>),
+    /// `/v2/<name>/tags/list`.
+    TagsList,
+    /// `/v2/<name>/referrers/<digest>`.
+    Referrers(Digest<'a>),
+}
+
+impl<'a> Route<'a> {
+    /// The manifest an image reference points to: by digest if it has one,
+    /// otherwise by tag, defaulting to `latest`.
+    pub fn manifest_of(img: &'a ImgRef<'_>) -> Self {
+        match img.span.digest_range() {
+            Some(range) => Self::Manifest(&img.src[range]),
+            None => Self::Manifest(img.tag().unwrap_or("latest")),
+        }
+    }
+}
+
+/// The registry and repository name that API URLs are built from.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Endpoint<'src> {
+    scheme: Scheme,
+    authority: &'src str,
+    /// whether the repository is an official Docker Hub image missing its `library/`
+    library: bool,
+    name: &'src str,
+}
+
+impl<'src> Endpoint<'src> {
+    /// The endpoint for a repository `path` on the registry at `domain`, or on
+    /// Docker Hub if there's no domain.
+    pub fn new(domain: Option<&Domain<'src>>, path: &Path<'src>) -> Self {
+        Self::from_parts(domain.map(Domain::to_str), path.to_str())
+    }
+    /// The endpoint for an image reference's repository.
+    pub fn from_ref(img: &ImgRef<'src>) -> Self {
+        let domain = img.span.name.domain.map(|d| &img.src[..d.len()]);
+        match domain {
+            Some(domain) if is_registry(domain) => {
+                Self::from_parts(Some(domain), &img.src[img.span.path_range()])
+            }
+            _ => Self::from_parts(None, &img.src[img.span.name_range()]),
+        }
+    }
+    fn from_parts(domain: Option<&'src str>, name: &'src str) -> Self {
+        let authority = domain
+            .filter(|d| !DOCKER_HUB_ALIASES.contains(d))
+            .unwrap_or(DOCKER_HUB_REGISTRY);
+        Self {
+            scheme: Scheme::Https,
+            authority,
+            library: authority == DOCKER_HUB_REGISTRY && !name.contains('/'),
+            name,
+        }
+    }
+    /// Use a different scheme, such as [`Scheme::Http`] for insecure registries.
+    pub const fn with_scheme(self, scheme: Scheme) -> Self {
+        Self { scheme, ..self }
+    }
+    /// The scheme URLs will use.
+    pub const fn scheme(&self) -> Scheme {
+        self.scheme
+    }
+    /// The host and optional port of the registry's API. IPv6 hosts are bracketed.
+    pub const fn authority(&self) -> &'src str {
+        self.authority
+    }
+    /// Write the repository's name as the API expects it, such as `library/alpine`.
+    pub fn write_name(&self, out: &mut impl fmt::Write) -> fmt::Result {
+        if self.library {
+            out.write_str("library/")?;
+        }
+        out.write_str(self.name)
+    }
+    /// Write a route's path, starting with `/v2/`.
+    pub fn write_path(&self, route: Route<'_>, out: &mut impl fmt::Write) -> fmt::Result {
+        out.write_str("/v2/")?;
+        if let Route::Base = route {
+            return Ok(());
+        }
+        self.write_name(out)?;
+        match route {
+            Route::Base => Ok(()),
+            Route::Manifest(reference) => write!(out, "/manifests/{reference}"),
+            Route::Blob(digest) => write!(out, "/blobs/{}", digest.to_str()),
+            Route::TagsList => out.write_str("/tags/list"),
+            Route::Referrers(digest) => write!(out, "/referrers/{}", digest.to_str()),
+        }
+    }
+    /// Write a route's full URL.
+    pub fn write(&self, route: Route<'_>, out: &mut impl fmt::Write) -> fmt::Result {
+        write!(out, "{}://{}", self.scheme.name(), self.authority)?;
+        self.write_path(route, out)
+    }
+    /// A route's full URL, which can be formatted with `{}`.
+    pub const fn url<'a>(&'a self, route: Route<'a>) -> Url<'a> {
+        Url {
+            endpoint: self,
+            route,
+        }
+    }
+}
+
+/// A route's URL; see [`Endpoint::url`].
+pub struct Url<'a> {
+    endpoint: &'a Endpoint<'a>,
+    route: Route<'a>,
+}
+
+impl fmt::Display for Url<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.endpoint.write(self.route, f)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    extern crate alloc;
+    use alloc::{format, string::ToString};
+
+    use super::*;
+
+    const HEX: &str = "853ff93762a06ddbf722c4ebe9ddd66d8f63ddaea97f521c3ecc20da7c976020";
+
+    fn manifest(src: &str) -> alloc::string::String {
+        let img = ImgRef::new(src).unwrap();
+        Endpoint::from_ref(&img)
+            .url(Route::manifest_of(&img))
+            .to_string()
+    }
+
+    #[test]
+    fn docker_hub() {
+        let hub = "https://registry-1.docker.io/v2/library/alpine/manifests/latest";
+        assert_eq!(manifest("alpine"), hub);
+        assert_eq!(manifest("docker.io/alpine"), hub);
+        assert_eq!(manifest("index.docker.io/library/alpine"), hub);
+        assert_eq!(
+            manifest("bitnami/redis:7"),
+            "https://registry-1.docker.io/v2/bitnami/redis/manifests/7"
+        );
+    }
+
+    #[test]
+    fn other_registries() {
+        assert_eq!(
+            manifest(&format!("ghcr.io/org/app:v1@sha256:{HEX}")),
+            format!("https://ghcr.io/v2/org/app/manifests/sha256:{HEX}")
+        );
+        assert_eq!(
+            manifest("localhost/app"),
+            "https://localhost/v2/app/manifests/latest"
+        );
+        assert_eq!(
+            manifest("[2001:db8::1]:5000/app:x"),
+            "https://[2001:db8::1]:5000/v2/app/manifests/x"
+        );
+    }
+
+    #[test]
+    fn routes() {
+        let src = format!("sha256:{HEX}");
+        let digest = Digest::new(&src).unwrap();
+        let domain = Domain::new("127.0.0.1:5000").unwrap();
+        let path = Path::new("a/b").unwrap();
+        let endpoint = Endpoint::new(Some(&domain), &path).with_scheme(Scheme::Http);
+        assert_eq!(endpoint.authority(), "127.0.0.1:5000");
+        let base = "http://127.0.0.1:5000/v2/";
+        assert_eq!(endpoint.url(Route::Base).to_string(), base);
+        assert_eq!(
+            endpoint.url(Route::Blob(digest)).to_string(),
+            format!("{base}a/b/blobs/{src}")
+        );
+        assert_eq!(
+            endpoint.url(Route::Referrers(digest)).to_string(),
+            format!("{base}a/b/referrers/{src}")
+        );
+        assert_eq!(
+            endpoint.url(Route::TagsList).to_string(),
+            format!("{base}a/b/tags/list")
+        );
+        let hub = Endpoint::new(None, &Path::new("alpine").unwrap());
+        assert_eq!(hub.authority(), DOCKER_HUB_REGISTRY);
+    }
+}
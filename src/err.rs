@@ -3,6 +3,23 @@
 //! Each `Error` includes a variant of `Kind` and the index of the first invalid
 //! ascii character in the source string.
 
+/// Define `Kind` along with a table of its variants in declaration order, so
+/// that codes can be mapped back to kinds.
+macro_rules! kinds {
+    (
+        $(#[$attr:meta])*
+        pub enum Kind { $($(#[$doc:meta])* $variant:ident,)* }
+    ) => {
+        $(#[$attr])*
+        #[repr(u8)]
+        pub enum Kind { $($(#[$doc])* $variant,)* }
+        impl Kind {
+            const ALL: &'static [Self] = &[$(Self::$variant),*];
+        }
+    };
+}
+
+kinds! {
 #[allow(missing_docs)]
 // TODO: more docs
 // FIXME: reduce number of **public** errors.
@@ -52,6 +69,9 @@ pub enum Kind {
     PathComponentInvalidEnd,
     PathInvalidChar,
     PathTooLong,
+    /// A repository name without a domain is 64 lowercase hex digits, which
+    /// would be mistaken for an image ID.
+    PathIsIdentifier,
     // tag -----------------------------------------------------
     /// 129 or more characters after the ":".
     TagTooLong,
@@ -72,6 +92,11 @@ pub enum Kind {
     /// Either a sha256 or sha512 algorithm was expected, but the digest was
     /// not 64 or 128 hex digits long.
     OciRegisteredAlgorithmWrongDigestLength,
+    /// A blake3 algorithm was expected, but the digest was not 64 hex digits long.
+    Blake3WrongDigestLength,
+    /// The digest doesn't comply with the standard required by its algorithm's
+    /// rule in an [`AlgorithmRegistry`](crate::digest::registry::AlgorithmRegistry).
+    RegisteredAlgorithmNoncompliant,
     AlgorithmInvalidChar,
     /// 256 or more characters in the algorithm section.
     AlgorithmTooLong,
@@ -89,9 +114,110 @@ pub enum Kind {
     /// this repository. However, it is reasonable: 1024 hex digits can encode
     /// 4096-bit hashes, which is enough for an RSA key.
     EncodingTooLong,
+    // digest::raw ----------------------------------------------
+    /// The encoded section's length can't be decoded: an odd number of hex
+    /// digits, misplaced base64 padding, or the wrong number of bytes for the
+    /// algorithm.
+    DecodeInvalidLength,
+    /// The buffer is too small to hold the decoded bytes or digest string.
+    DecodeBufferTooSmall,
+    /// The digest's algorithm doesn't match the raw digest type.
+    DecodeWrongAlgorithm,
+    // digest::content ------------------------------------------
+    /// A content digest's algorithm isn't registered.
+    ContentDigestUnregistered,
+    /// A response's digest doesn't match the digest the request was pinned to.
+    ContentDigestMismatch,
+    /// A descriptor's size is negative.
+    DescriptorSizeNegative,
+    /// Content doesn't have the size its descriptor promised.
+    DescriptorSizeMismatch,
     // reference ----------------------------------------
     /// empty string or non-canonical reference
     RefMissing,
+    // transport ----------------------------------------
+    /// The transport prefix is missing or isn't a supported transport.
+    TransportUnknown,
+    /// A transport that requires a path has an empty one.
+    TransportPathMissing,
+    /// The image reference, ID, or index isn't allowed by the transport.
+    TransportImageInvalid,
+    /// A `containers-storage:` store specifier has no closing `]`.
+    StoreSpecUnterminated,
+    /// A `containers-storage:` store specifier, or its driver, is empty.
+    StoreSpecInvalid,
+    /// A `containers-storage:` graph root or run root isn't an absolute path.
+    StorePathNotAbsolute,
+    // helm ---------------------------------------------
+    /// A Helm chart reference doesn't start with `oci://`.
+    HelmSchemeMissing,
+    /// A semantic version is missing a numeric component or an identifier.
+    SemVerMissing,
+    /// A semantic version contains a character that isn't allowed where it is.
+    SemVerInvalidChar,
+    /// A numeric semantic version component or pre-release identifier has a leading zero.
+    SemVerLeadingZero,
+    /// A semantic version is too long to store as a tag.
+    SemVerTooLong,
+    // purl ---------------------------------------------
+    /// A package URL doesn't start with `pkg:`.
+    PurlSchemeMissing,
+    /// A package URL's type isn't `oci` or `docker`.
+    PurlTypeUnsupported,
+    /// A package URL has an empty name.
+    PurlNameMissing,
+    /// A `pkg:oci` package URL has a namespace.
+    PurlNamespaceUnexpected,
+    /// A `%` isn't followed by two hex digits, or decodes to invalid UTF-8.
+    PurlInvalidEncoding,
+    /// A qualifier is missing its `=`, has an invalid or duplicate key, or
+    /// contradicts the rest of the package URL.
+    PurlQualifierInvalid,
+    /// A `pkg:oci` package URL or the reference it describes has no digest.
+    PurlDigestMissing,
+    // url ----------------------------------------------
+    /// A URL isn't a registry API or web UI URL for an image.
+    UrlUnrecognized,
+    // scope --------------------------------------------
+    /// A scope's resource type or class is empty or isn't `[a-z0-9]+`, or the
+    /// class is missing its closing `)`.
+    ScopeResourceTypeInvalid,
+    /// A scope is missing the `:` before its actions.
+    ScopeActionsMissing,
+    /// A scope's action isn't `[a-z*]*`.
+    ScopeActionInvalid,
+    // layout -------------------------------------------
+    /// A ref name or one of its `/`-separated components is empty.
+    RefNameMissing,
+    /// A ref name contains a character other than `[A-Za-z0-9/]` or a separator.
+    RefNameInvalidChar,
+    /// A ref name's separator isn't between two alphanumeric characters.
+    RefNameInvalidSeparator,
+    /// A layout file isn't valid JSON.
+    JsonInvalid,
+    /// An `index.json` is missing a required field, or a field has the wrong type.
+    IndexInvalid,
+    /// A blob path isn't `blobs/<algorithm>/<encoded>`.
+    BlobPathInvalid,
+    /// A blob path has a `.` or `..` component, a `\`, or a `/` in its
+    /// encoded section.
+    BlobPathTraversal,
+}
+}
+
+// u8::MAX is left free for callers to use as a "no error" sentinel
+const _: () = assert!(Kind::ALL.len() < u8::MAX as usize);
+
+impl Kind {
+    /// The kind's code: its position in the declaration order of `Kind`. Codes
+    /// may change between versions of this crate.
+    pub const fn code(self) -> u8 {
+        self as u8
+    }
+    /// The kind with the given code, if any.
+    pub fn from_code(code: u8) -> Option<Self> {
+        Self::ALL.get(usize::from(code)).copied()
+    }
 }
 
 /// The `Error` type contains an `err::Kind` and an index within the source string.
@@ -124,21 +250,18 @@ where
     }
 }
 
-impl<Int, Size> core::ops::Add<Int> for Error<Size>
-where
-    Size: core::ops::Add<Output = Size> + Into<usize>,
-    Int: Into<Size>,
-{
-    type Output = Self;
-    #[allow(clippy::arithmetic_side_effects)] // FIXME: check for overflow
-    fn add(self, rhs: Int) -> Self {
-        #[cfg(debug_assertions)]
-        let len = self.0 + rhs.into();
-        #[cfg(not(debug_assertions))]
-        let len = self.0.saturating_add(rhs.into());
-        Self(len, self.1)
-    }
+/// Shift an error's index, saturating at the largest index the size can hold.
+macro_rules! saturating_add {
+    ($($size:ty),*) => {$(
+        impl<Int: Into<$size>> core::ops::Add<Int> for Error<$size> {
+            type Output = Self;
+            fn add(self, rhs: Int) -> Self {
+                Self(self.0.saturating_add(rhs.into()), self.1)
+            }
+        }
+    )*};
 }
+saturating_add!(u8, u16);
 
 impl<T, Size: Into<usize>> From<Error<Size>> for Result<T, Error<Size>> {
     #[inline(always)]
@@ -146,3 +269,29 @@ impl<T, Size: Into<usize>> From<Error<Size>> for Result<T, Error<Size>> {
         Err(value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_round_trip() {
+        for (i, &kind) in Kind::ALL.iter().enumerate() {
+            assert_eq!(usize::from(kind.code()), i);
+            assert_eq!(Kind::from_code(kind.code()), Some(kind));
+        }
+        assert_eq!(Kind::from_code(u8::MAX), None);
+    }
+
+    #[test]
+    fn add_saturates() {
+        let e = Error::at(250u8, Kind::TagTooLong) + 10u8;
+        assert_eq!((e.index(), e.kind()), (u8::MAX, Kind::TagTooLong));
+        let e = Error::at(1u16, Kind::PathTooLong) + 2u8;
+        assert_eq!(e.index(), 3);
+        assert_eq!(
+            (Error::at(u16::MAX - 1, Kind::PathTooLong) + 2u16).index(),
+            u16::MAX
+        );
+    }
+}
@@ -0,0 +1,367 @@
+//! # Helm chart references
+//! Helm stores charts in OCI registries under references like
+//! `oci://registry.example.com/charts/mychart`, tagged with the chart's
+//! [Semantic Versioning 2.0.0](https://semver.org/spec/v2.0.0.html) version. Since
+//! [tags](crate::tag) can't contain `+`, Helm writes the `+` before a
+//! version's build metadata as `_`: version `1.2.3+build.4` is stored under
+//! the tag `1.2.3_build.4`.
+//! ```rust
+//! use container_image_dist_ref::helm::{ChartRef, ChartVersion};
+//! let chart = ChartRef::new("oci://registry.example.com/charts/mychart:1.2.3_build.4").unwrap();
+//! assert_eq!(chart.domain().to_str(), "registry.example.com");
+//! assert_eq!(chart.path().to_str(), "charts/mychart");
+//! assert_eq!(chart.name(), "mychart");
+//! let version = chart.version().unwrap();
+//! assert_eq!(version.to_str(), "1.2.3+build.4");
+//! assert_eq!(version.tag().to_str(), "1.2.3_build.4");
+//!
+//! let version = ChartVersion::new("0.1.0-rc.1+sha.5114f85").unwrap();
+//! assert_eq!(version.tag().to_str(), "0.1.0-rc.1_sha.5114f85");
+//! assert!(ChartVersion::new("1.2").is_err());
+//! assert!(ChartVersion::new("01.2.3").is_err());
+//! ```
+
+use core::fmt;
+
+use crate::{
+    err::{self, Kind},
+    name::{domain::Domain, path::Path},
+    tag::{self, Tag},
+};
+
+type Error = err::Error<u16>;
+
+/// The prefix of every Helm chart reference.
+pub const SCHEME: &str = "oci://";
+
+const MAX_LEN: usize = tag::MAX_LEN.get() as usize;
+
+fn index(i: usize) -> u16 {
+    u16::try_from(i).unwrap_or(u16::MAX)
+}
+
+/// check a dot-separated list of identifiers starting at `i`, returning the
+/// index just past it
+fn identifiers(src: &[u8], mut i: usize, numeric_leading_zeros: bool) -> Result<usize, Error> {
+    loop {
+        let start = i;
+        while src
+            .get(i)
+            .is_some_and(|&c| c.is_ascii_alphanumeric() || c == b'-')
+        {
+            i += 1;
+        }
+        let identifier = &src[start..i];
+        if identifier.is_empty() {
+            let kind = match src.get(i) {
+                None | Some(b'.') => Kind::SemVerMissing,
+                Some(_) => Kind::SemVerInvalidChar,
+            };
+            return Error::at(index(i), kind).into();
+        }
+        if !numeric_leading_zeros
+            && identifier.len() > 1
+            && identifier[0] == b'0'
+            && identifier.iter().all(u8::is_ascii_digit)
+        {
+            return Error::at(index(start), Kind::SemVerLeadingZero).into();
+        }
+        match src.get(i) {
+            Some(b'.') => i += 1,
+            _ => return Ok(i),
+        }
+    }
+}
+
+/// check that `src` is a semantic version
+fn validate(src: &[u8]) -> Result<(), Error> {
+    let mut i = 0;
+    for component in 0..3 {
+        let start = i;
+        while src.get(i).is_some_and(u8::is_ascii_digit) {
+            i += 1;
+        }
+        if i == start {
+            let kind = match src.get(i) {
+                None | Some(b'.') => Kind::SemVerMissing,
+                Some(_) => Kind::SemVerInvalidChar,
+            };
+            return Error::at(index(i), kind).into();
+        }
+        if src[start] == b'0' && i - start > 1 {
+            return Error::at(index(start), Kind::SemVerLeadingZero).into();
+        }
+        if component < 2 {
+            match src.get(i) {
+                Some(b'.') => i += 1,
+                None => return Error::at(index(i), Kind::SemVerMissing).into(),
+                Some(_) => return Error::at(index(i), Kind::SemVerInvalidChar).into(),
+            }
+        }
+    }
+    if src.get(i) == Some(&b'-') {
+        i = identifiers(src, i + 1, false)?;
+    }
+    if src.get(i) == Some(&b'+') {
+        i = identifiers(src, i + 1, true)?;
+    }
+    if i == src.len() {
+        Ok(())
+    } else {
+        Error::at(index(i), Kind::SemVerInvalidChar).into()
+    }
+}
+
+/// A chart's semantic version, along with the tag Helm stores it under.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct ChartVersion {
+    version: [u8; MAX_LEN],
+    tag: [u8; MAX_LEN],
+    len: u8,
+}
+
+impl ChartVersion {
+    /// swap `from` for `to`, then validate the version
+    fn convert(src: &str, from: u8, to: u8) -> Result<Self, Error> {
+        if src.len() > MAX_LEN {
+            return Error::at(index(MAX_LEN), Kind::SemVerTooLong).into();
+        }
+        let len = src.len();
+        let mut original = [0; MAX_LEN];
+        original[..len].copy_from_slice(src.as_bytes());
+        let mut converted = original;
+        for c in &mut converted[..len] {
+            if *c == from {
+                *c = to;
+            }
+        }
+        let (version, tag) = if from == b'+' {
+            (original, converted)
+        } else {
+            (converted, original)
+        };
+        validate(&version[..len])?;
+        Ok(Self {
+            version,
+            tag,
+            len: u8::try_from(len).unwrap_or(u8::MAX),
+        })
+    }
+    /// Parse a semantic version. Versions longer than 128 characters are
+    /// rejected since they can't be stored as tags.
+    pub fn new(version: &str) -> Result<Self, Error> {
+        Self::convert(version, b'+', b'_')
+    }
+    /// Recover a chart version from the tag Helm stored it under. Errors are
+    /// reported at offsets within the tag.
+    pub fn from_tag(tag: &str) -> Result<Self, Error> {
+        Self::convert(tag, b'_', b'+')
+    }
+    /// The version, such as `1.2.3+build.4`.
+    pub fn to_str(&self) -> &str {
+        // SAFETY: the version was validated as ascii SemVer
+        unsafe { core::str::from_utf8_unchecked(&self.version[..usize::from(self.len)]) }
+    }
+    /// The tag the version is stored under, such as `1.2.3_build.4`.
+    #[allow(clippy::unwrap_used)]
+    pub fn tag(&self) -> Tag<'_> {
+        // SAFETY: the tag is a valid version with `+` swapped for `_`
+        let tag = unsafe { core::str::from_utf8_unchecked(&self.tag[..usize::from(self.len)]) };
+        Tag::new(tag).unwrap() // semantic versions of at most 128 characters are valid tags
+    }
+}
+
+impl fmt::Debug for ChartVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ChartVersion").field(&self.to_str()).finish()
+    }
+}
+
+impl fmt::Display for ChartVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.to_str())
+    }
+}
+
+/// A reference to a Helm chart in an OCI registry, optionally tagged with a
+/// version.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ChartRef<'src> {
+    src: &'src str,
+    domain: Domain<'src>,
+    path: Path<'src>,
+    version: Option<ChartVersion>,
+}
+
+impl<'src> ChartRef<'src> {
+    /// Parse `oci://<domain>/<path>`, optionally followed by `:<tag>`. The tag
+    /// must be a chart version converted by [`ChartVersion::tag`].
+    pub fn new(src: &'src str) -> Result<Self, Error> {
+        let shift = |start: usize| {
+            move |e: Error| Error::at(e.index().saturating_add(index(start)), e.kind())
+        };
+        let rest = src
+            .strip_prefix(SCHEME)
+            .ok_or(Error::at(0, Kind::HelmSchemeMissing))?;
+        let mut i = SCHEME.len();
+        let domain = Domain::new(rest).map_err(shift(i))?;
+        i += domain.len();
+        if src.as_bytes().get(i) != Some(&b'/') {
+            return Error::at(index(i), Kind::PathMissing).into();
+        }
+        i += 1;
+        let path = Path::new(&src[i..])
+            .map_err(Error::from)
+            .map_err(shift(i))?;
+        i += path.to_str().len();
+        let version = match src.as_bytes().get(i) {
+            None => None,
+            Some(b':') => {
+                i += 1;
+                let tag = Tag::new(&src[i..]).map_err(Error::from).map_err(shift(i))?;
+                if tag.to_str().len() != src.len() - i {
+                    let at = i + tag.to_str().len();
+                    return Error::at(index(at), Kind::TagInvalidChar).into();
+                }
+                Some(ChartVersion::from_tag(tag.to_str()).map_err(shift(i))?)
+            }
+            Some(_) => return Error::at(index(i), Kind::PathInvalidChar).into(),
+        };
+        Ok(Self {
+            src,
+            domain,
+            path,
+            version,
+        })
+    }
+    /// The original reference string.
+    pub const fn to_str(&self) -> &'src str {
+        self.src
+    }
+    /// The registry's domain.
+    pub const fn domain(&self) -> &Domain<'src> {
+        &self.domain
+    }
+    /// The repository path, including the chart's name.
+    pub const fn path(&self) -> &Path<'src> {
+        &self.path
+    }
+    /// The chart's name: the last component of the path.
+    pub fn name(&self) -> &'src str {
+        let path = self.path.to_str();
+        path.rsplit('/').next().unwrap_or(path)
+    }
+    /// The chart's version, if the reference was tagged.
+    pub const fn version(&self) -> Option<&ChartVersion> {
+        self.version.as_ref()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    extern crate alloc;
+    use alloc::{format, string::String};
+
+    use super::*;
+
+    fn err(version: &str) -> (u16, Kind) {
+        let e = ChartVersion::new(version).err().unwrap();
+        (e.index(), e.kind())
+    }
+    fn ref_err(src: &str) -> (u16, Kind) {
+        let e = ChartRef::new(src).err().unwrap();
+        (e.index(), e.kind())
+    }
+
+    #[test]
+    fn versions_round_trip() {
+        for version in [
+            "0.0.0",
+            "1.2.3",
+            "10.20.30",
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-0.3.7",
+            "1.0.0-x.7.z.92",
+            "1.0.0-x-y-z.--",
+            "1.0.0+20130313144700",
+            "1.0.0-beta+exp.sha.5114f85",
+            "1.0.0+21AF26D3----117B344092BD",
+            "1.0.0+001",
+        ] {
+            let parsed = ChartVersion::new(version).unwrap();
+            assert_eq!(parsed.to_str(), version);
+            let tag = parsed.tag();
+            assert!(!tag.to_str().contains('+'));
+            let back = ChartVersion::from_tag(tag.to_str()).unwrap();
+            assert_eq!(back, parsed);
+            assert_eq!(back.to_str(), version);
+        }
+    }
+
+    #[test]
+    fn invalid_versions() {
+        assert_eq!(err(""), (0, Kind::SemVerMissing));
+        assert_eq!(err("1"), (1, Kind::SemVerMissing));
+        assert_eq!(err("1.2"), (3, Kind::SemVerMissing));
+        assert_eq!(err("1.2."), (4, Kind::SemVerMissing));
+        assert_eq!(err("v1.2.3"), (0, Kind::SemVerInvalidChar));
+        assert_eq!(err("1.02.3"), (2, Kind::SemVerLeadingZero));
+        assert_eq!(err("1.2.3-01"), (6, Kind::SemVerLeadingZero));
+        assert_eq!(err("1.2.3-"), (6, Kind::SemVerMissing));
+        assert_eq!(err("1.2.3-a..b"), (8, Kind::SemVerMissing));
+        assert_eq!(err("1.2.3+a_b"), (7, Kind::SemVerInvalidChar));
+        assert_eq!(err("1.2.3+a+b"), (7, Kind::SemVerInvalidChar));
+        assert_eq!(err("1.2.3.4"), (5, Kind::SemVerInvalidChar));
+        let mut long = String::from("1.2.3-");
+        long.push_str(&"a".repeat(123));
+        assert_eq!(err(&long), (128, Kind::SemVerTooLong));
+        long.pop();
+        assert_eq!(ChartVersion::new(&long).unwrap().tag().to_str(), long);
+
+        // tags that aren't versions, or that would need two `+`s
+        let e = ChartVersion::from_tag("latest").err().unwrap();
+        assert_eq!((e.index(), e.kind()), (0, Kind::SemVerInvalidChar));
+        let e = ChartVersion::from_tag("1.2.3_a_b").err().unwrap();
+        assert_eq!((e.index(), e.kind()), (7, Kind::SemVerInvalidChar));
+    }
+
+    #[test]
+    fn chart_refs() {
+        let chart = ChartRef::new("oci://localhost:5000/mychart").unwrap();
+        assert_eq!(chart.domain().port(), Some("5000"));
+        assert_eq!(chart.name(), "mychart");
+        assert!(chart.version().is_none());
+
+        assert_eq!(ref_err("https://x.io/chart"), (0, Kind::HelmSchemeMissing));
+        assert_eq!(ref_err("oci://x.io"), (10, Kind::PathMissing));
+        assert_eq!(
+            ref_err("oci://x.io/chart@sha256:abc"),
+            (16, Kind::PathInvalidChar)
+        );
+        assert_eq!(
+            ref_err("oci://x.io/chart:latest"),
+            (17, Kind::SemVerInvalidChar)
+        );
+        assert_eq!(
+            ref_err("oci://x.io/chart:1.2.3+b"),
+            (22, Kind::TagInvalidChar)
+        );
+        assert_eq!(
+            ref_err("oci://x.io/chart:1.02.3"),
+            (19, Kind::SemVerLeadingZero)
+        );
+    }
+
+    #[test]
+    fn copies_compare_equal() {
+        let chart = ChartRef::new("oci://x.io/charts/app:1.2.3").unwrap();
+        let copy = chart;
+        assert_eq!(copy, chart);
+        assert_ne!(chart, ChartRef::new("oci://x.io/charts/app").unwrap());
+        let debug = format!("{chart:?}");
+        assert!(debug.contains(r#"domain: Domain("x.io")"#), "{debug}");
+        assert!(debug.contains(r#"path: Path("charts/app")"#), "{debug}");
+    }
+}
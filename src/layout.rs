@@ -0,0 +1,151 @@
+//! # OCI image layouts
+//! An [OCI image layout](https://github.com/opencontainers/image-spec/blob/v1.1.0/image-layout.md)
+//! is a directory holding an `index.json` and content-addressed blobs. The
+//! index tags manifests with the `org.opencontainers.image.ref.name`
+//! annotation, which has its own [grammar](https://github.com/opencontainers/image-spec/blob/v1.1.0/annotations.md#pre-defined-annotation-keys):
+//! ```ebnf
+//! ref       ::= component ("/" component)*
+//! component ::= alphanum (separator alphanum)*
+//! alphanum  ::= [A-Za-z0-9]+
+//! separator ::= [-._:@+] | "--"
+//! ```
+//! Unlike a [tag](crate::tag), a ref name may contain `/`, `:`, `@`, and `+`,
+//! has no length limit, and can't start or end with a separator.
+//! [`blob`] maps digests to the paths of their blobs, and [`index`] resolves
+//! ref names and digests to manifests (with the `alloc` feature).
+//! ```rust
+//! use container_image_dist_ref::{err, layout::RefName};
+//! let name = RefName::new("example.com/app:v1.2.3+build").unwrap();
+//! assert_eq!(name.components().collect::<Vec<_>>(), ["example.com", "app:v1.2.3+build"]);
+//! let err = RefName::new("v1.-rc").err().unwrap();
+//! assert_eq!((err.index(), err.kind()), (3, err::Kind::RefNameInvalidSeparator));
+//! ```
+
+pub mod blob;
+#[cfg(feature = "alloc")]
+pub mod index;
+#[cfg(feature = "alloc")]
+mod json;
+
+use core::fmt;
+
+use crate::{err::Kind, Error};
+
+/// The annotation naming a manifest in a layout's `index.json`.
+pub const REF_NAME_ANNOTATION: &str = "org.opencontainers.image.ref.name";
+
+fn index(i: usize) -> u16 {
+    u16::try_from(i).unwrap_or(u16::MAX)
+}
+
+const fn is_separator(c: u8) -> bool {
+    matches!(c, b'-' | b'.' | b'_' | b':' | b'@' | b'+')
+}
+
+/// check one `/`-separated component starting at offset `start`
+fn component(src: &[u8], start: usize) -> Result<(), Error> {
+    if src.is_empty() {
+        return Error::at(index(start), Kind::RefNameMissing).into();
+    }
+    let mut i = 0;
+    loop {
+        let run = i;
+        while src.get(i).is_some_and(u8::is_ascii_alphanumeric) {
+            i += 1;
+        }
+        let Some(&c) = src.get(i) else {
+            return Ok(());
+        };
+        if !is_separator(c) {
+            return Error::at(index(start + i), Kind::RefNameInvalidChar).into();
+        }
+        // a separator must follow an alphanumeric run and precede another
+        let separator = if c == b'-' && src.get(i + 1) == Some(&b'-') {
+            2
+        } else {
+            1
+        };
+        let next = src.get(i + separator);
+        if i == run || !next.is_some_and(u8::is_ascii_alphanumeric) {
+            let at = if i == run { i } else { i + separator };
+            let kind = match next {
+                Some(&c) if !is_separator(c) && i != run => Kind::RefNameInvalidChar,
+                _ => Kind::RefNameInvalidSeparator,
+            };
+            return Error::at(index(start + at.min(src.len())), kind).into();
+        }
+        i += separator;
+    }
+}
+
+/// A valid `org.opencontainers.image.ref.name` annotation value.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RefName<'src>(&'src str);
+
+impl<'src> RefName<'src> {
+    /// Validate a whole ref name.
+    pub fn new(src: &'src str) -> Result<Self, Error> {
+        let mut start = 0;
+        for part in src.split('/') {
+            component(part.as_bytes(), start)?;
+            start += part.len() + 1;
+        }
+        Ok(Self(src))
+    }
+    /// The ref name.
+    pub const fn to_str(&self) -> &'src str {
+        self.0
+    }
+    /// The `/`-separated components of the ref name.
+    pub fn components(&self) -> impl Iterator<Item = &'src str> {
+        self.0.split('/')
+    }
+}
+
+impl fmt::Display for RefName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn err(src: &str) -> (u16, Kind) {
+        let e = RefName::new(src).err().unwrap();
+        (e.index(), e.kind())
+    }
+
+    #[test]
+    fn valid_ref_names() {
+        for name in [
+            "latest",
+            "v1.2.3",
+            "1.0.0+build.4",
+            "Stable_Release",
+            "a--b",
+            "example.com:5000/app@v1",
+            "registry/ns/app:tag",
+        ] {
+            assert_eq!(RefName::new(name).unwrap().to_str(), name);
+        }
+    }
+
+    #[test]
+    fn invalid_ref_names() {
+        assert_eq!(err(""), (0, Kind::RefNameMissing));
+        assert_eq!(err("a//b"), (2, Kind::RefNameMissing));
+        assert_eq!(err("a/"), (2, Kind::RefNameMissing));
+        assert_eq!(err("-a"), (0, Kind::RefNameInvalidSeparator));
+        assert_eq!(err("a/.b"), (2, Kind::RefNameInvalidSeparator));
+        assert_eq!(err("a-"), (2, Kind::RefNameInvalidSeparator));
+        assert_eq!(err("a---b"), (3, Kind::RefNameInvalidSeparator));
+        assert_eq!(err("a._b"), (2, Kind::RefNameInvalidSeparator));
+        assert_eq!(err("a b"), (1, Kind::RefNameInvalidChar));
+        assert_eq!(err("a.b!"), (3, Kind::RefNameInvalidChar));
+        assert_eq!(err("a.!"), (2, Kind::RefNameInvalidChar));
+        assert_eq!(err("ü"), (0, Kind::RefNameInvalidChar));
+    }
+}
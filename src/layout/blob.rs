@@ -0,0 +1,215 @@
+//! # Blob paths
+//! A layout stores each blob at `blobs/<algorithm>/<encoded>`, relative to the
+//! layout's root. [`BlobPath`] maps digests to those paths and back. Parsing a
+//! path rejects `.` and `..` components and separators inside the encoded
+//! section, so a parsed path can't escape the `blobs` directory.
+//! ```rust
+//! use container_image_dist_ref::{
+//!     digest::Digest,
+//!     err,
+//!     layout::blob::{BlobPath, MAX_DIGEST_LEN},
+//! };
+//! let hex = "09ca7e4eaa6e8ae9c7d261167129184883644d07dfba7cbfbc4c8a2e08360d5b";
+//! let digest = format!("sha256:{hex}");
+//! let digest = Digest::new(&digest).unwrap();
+//! let path = BlobPath::from_digest(&digest);
+//! assert_eq!(path.to_string(), format!("blobs/sha256/{hex}"));
+//!
+//! let path = format!("blobs/sha256/{hex}");
+//! let path = BlobPath::new(&path).unwrap();
+//! let mut buf = [0; MAX_DIGEST_LEN];
+//! assert_eq!(path.digest_into(&mut buf).unwrap(), digest);
+//!
+//! let err = BlobPath::new("blobs/sha256/../../etc/passwd").err().unwrap();
+//! assert_eq!((err.index(), err.kind()), (13, err::Kind::BlobPathTraversal));
+//! ```
+
+use core::fmt;
+
+use super::index;
+use crate::{
+    digest::{
+        algorithm::{self, Algorithm},
+        encoded::{self, Encoded},
+        registry::AlgorithmRegistry,
+        Digest,
+    },
+    err::Kind,
+    Error,
+};
+
+/// The directory holding a layout's blobs.
+pub const BLOBS_DIR: &str = "blobs";
+
+/// The longest digest string a [`BlobPath`] can hold.
+pub const MAX_DIGEST_LEN: usize = algorithm::MAX_LEN as usize + 1 + encoded::MAX_LEN as usize;
+
+/// The path of a blob within an image layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlobPath<'src> {
+    algorithm: Algorithm<'src>,
+    encoded: Encoded<'src>,
+}
+
+/// move an error to an offset within the whole path
+fn shift(start: usize) -> impl Fn(Error) -> Error {
+    move |e| Error::at(e.index().saturating_add(index(start)), e.kind())
+}
+
+impl<'src> BlobPath<'src> {
+    /// The path of a digest's blob. Valid digests can't contain `/` or `..`.
+    pub fn from_digest(digest: &Digest<'src>) -> Self {
+        Self {
+            algorithm: digest.algorithm(),
+            encoded: digest.encoded(),
+        }
+    }
+    /// Parse a `blobs/<algorithm>/<encoded>` path relative to a layout's root.
+    /// Encoded sections of registered algorithms must follow their algorithm's
+    /// rule. Errors are reported at offsets within `path`.
+    pub fn new(path: &'src str) -> Result<Self, Error> {
+        let mut start = 0;
+        for part in path.split(['/', '\\']) {
+            if part == "." || part == ".." {
+                return Error::at(index(start), Kind::BlobPathTraversal).into();
+            }
+            start += part.len() + 1;
+        }
+        if let Some(i) = path.find('\\') {
+            return Error::at(index(i), Kind::BlobPathTraversal).into();
+        }
+        let rest = path
+            .strip_prefix(BLOBS_DIR)
+            .and_then(|rest| rest.strip_prefix('/'))
+            .ok_or(Error::at(0, Kind::BlobPathInvalid))?;
+        let (algorithm, encoded) = rest
+            .split_once('/')
+            .ok_or(Error::at(index(path.len()), Kind::BlobPathInvalid))?;
+
+        let algorithm_start = BLOBS_DIR.len() + 1;
+        let encoded_start = algorithm_start + algorithm.len() + 1;
+        if let Some(i) = encoded.find('/') {
+            return Error::at(index(encoded_start + i), Kind::BlobPathTraversal).into();
+        }
+        let (algorithm, compliance) =
+            Algorithm::from_exact_match(algorithm).map_err(shift(algorithm_start))?;
+        let encoded = Encoded::new(encoded, compliance).map_err(shift(encoded_start))?;
+        encoded
+            .validate_with_registry(&algorithm, compliance, AlgorithmRegistry::DEFAULT)
+            .map_err(shift(encoded_start))?;
+        Ok(Self { algorithm, encoded })
+    }
+    /// The algorithm directory's name.
+    pub const fn algorithm(&self) -> &Algorithm<'src> {
+        &self.algorithm
+    }
+    /// The blob's file name.
+    pub const fn encoded(&self) -> &Encoded<'src> {
+        &self.encoded
+    }
+    /// The length of the blob's digest string.
+    pub const fn digest_len(&self) -> usize {
+        self.algorithm.len() + 1 + self.encoded.to_str().len()
+    }
+    /// Write the blob's digest string into `buf`, which must hold at least
+    /// [`digest_len`](Self::digest_len) bytes, and parse it.
+    pub fn digest_into<'buf>(&self, buf: &'buf mut [u8]) -> Result<Digest<'buf>, Error> {
+        let algorithm = self.algorithm.to_str().as_bytes();
+        let encoded = self.encoded.to_str().as_bytes();
+        let len = self.digest_len();
+        let out = buf
+            .get_mut(..len)
+            .ok_or(Error::at(index(len), Kind::DecodeBufferTooSmall))?;
+        let (prefix, rest) = out.split_at_mut(algorithm.len());
+        prefix.copy_from_slice(algorithm);
+        rest[0] = b':';
+        rest[1..].copy_from_slice(encoded);
+        Digest::from_bytes(out)
+    }
+}
+
+impl fmt::Display for BlobPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{BLOBS_DIR}/{}/{}",
+            self.algorithm.to_str(),
+            self.encoded.to_str()
+        )
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    extern crate alloc;
+    use alloc::{format, string::ToString};
+
+    use super::*;
+
+    const HEX: &str = "853ff93762a06ddbf722c4ebe9ddd66d8f63ddaea97f521c3ecc20da7c976020";
+
+    fn err(path: &str) -> (u16, Kind) {
+        let e = BlobPath::new(path).err().unwrap();
+        (e.index(), e.kind())
+    }
+
+    #[test]
+    fn round_trips() {
+        for digest in [
+            format!("sha256:{HEX}"),
+            format!("sha512:{HEX}{HEX}"),
+            "md5:d41d8cd98f00b204e9800998ecf8427e".to_string(),
+            "multihash+base58:QmRZxt2b1FVZPNqd8hsiykDL3TdBDeTSPX9Kv46HmX4Gx8".to_string(),
+        ] {
+            let parsed = Digest::new(&digest).unwrap();
+            let path = BlobPath::from_digest(&parsed).to_string();
+            let (algorithm, encoded) = digest.split_once(':').unwrap();
+            assert_eq!(path, format!("blobs/{algorithm}/{encoded}"));
+
+            let blob = BlobPath::new(&path).unwrap();
+            assert_eq!(blob, BlobPath::from_digest(&parsed));
+            assert_eq!(blob.algorithm().to_str(), algorithm);
+            assert_eq!(blob.encoded().to_str(), encoded);
+            assert_eq!(blob.digest_len(), digest.len());
+            let mut buf = [0; MAX_DIGEST_LEN];
+            assert_eq!(blob.digest_into(&mut buf).unwrap().to_str(), digest);
+        }
+    }
+
+    #[test]
+    fn small_buffers() {
+        let path = format!("blobs/sha256/{HEX}");
+        let blob = BlobPath::new(&path).unwrap();
+        let mut buf = [0; 70];
+        let e = blob.digest_into(&mut buf).err().unwrap();
+        assert_eq!((e.index(), e.kind()), (71, Kind::DecodeBufferTooSmall));
+    }
+
+    #[test]
+    fn traversal() {
+        assert_eq!(err("../blobs/sha256/x"), (0, Kind::BlobPathTraversal));
+        assert_eq!(err("blobs/./sha256/x"), (6, Kind::BlobPathTraversal));
+        assert_eq!(err("blobs/sha256/.."), (13, Kind::BlobPathTraversal));
+        assert_eq!(err("blobs/sha256/a/b"), (14, Kind::BlobPathTraversal));
+        assert_eq!(err("blobs/sha256/a/"), (14, Kind::BlobPathTraversal));
+        assert_eq!(err("blobs\\sha256\\a"), (5, Kind::BlobPathTraversal));
+        assert_eq!(err("blobs/sha256/a\\..\\b"), (15, Kind::BlobPathTraversal));
+    }
+
+    #[test]
+    fn invalid_paths() {
+        let hex = |alg: &str| format!("blobs/{alg}/{HEX}");
+        assert_eq!(err("sha256/x"), (0, Kind::BlobPathInvalid));
+        assert_eq!(err("/blobs/sha256/x"), (0, Kind::BlobPathInvalid));
+        assert_eq!(err("blobs/sha256"), (12, Kind::BlobPathInvalid));
+        assert_eq!(err(&hex("sha 256")), (9, Kind::AlgorithmInvalidChar));
+        assert_eq!(err(&hex("sha256:")).0, 12);
+        assert_eq!(err("blobs/sha256/"), (13, Kind::EncodedMissing));
+        assert_eq!(err("blobs/sha256/x.y"), (14, Kind::EncodedInvalidChar));
+        assert_eq!(
+            err("blobs/sha256/abc"),
+            (16, Kind::OciRegisteredAlgorithmWrongDigestLength)
+        );
+    }
+}
@@ -0,0 +1,274 @@
+//! # Resolving references in `index.json`
+//! A layout's [`index.json`](https://github.com/opencontainers/image-spec/blob/v1.1.0/image-layout.md#indexjson-file)
+//! is an image index listing the layout's manifests. [`Index`] reads the
+//! fields needed to find a manifest by its [`REF_NAME_ANNOTATION`] or by its
+//! digest, validating each descriptor's digest, size, and ref name.
+//! ```rust
+//! use container_image_dist_ref::{digest::Digest, layout::index::Index};
+//! let hex = "09ca7e4eaa6e8ae9c7d261167129184883644d07dfba7cbfbc4c8a2e08360d5b";
+//! let json = format!(r#"{{
+//!   "schemaVersion": 2,
+//!   "manifests": [{{
+//!     "mediaType": "application/vnd.oci.image.manifest.v1+json",
+//!     "digest": "sha256:{hex}",
+//!     "size": 7143,
+//!     "annotations": {{ "org.opencontainers.image.ref.name": "v1.0" }}
+//!   }}]
+//! }}"#);
+//! let index = Index::new(&json).unwrap();
+//! let manifest = index.by_ref_name("v1.0").unwrap();
+//! assert_eq!(manifest.size(), 7143);
+//! assert_eq!(manifest.digest().encoded().to_str(), hex);
+//!
+//! let digest = format!("sha256:{hex}");
+//! let digest = Digest::new(&digest).unwrap();
+//! assert_eq!(index.by_digest(digest), Some(manifest));
+//! assert!(index.resolve("v2.0").is_none());
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use super::{
+    index as offset,
+    json::{self, Node},
+    RefName, REF_NAME_ANNOTATION,
+};
+use crate::{
+    digest::{content::Descriptor, Digest},
+    err::Kind,
+    Error,
+};
+
+/// An image layout's `index.json`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Index {
+    manifests: Vec<ManifestDescriptor>,
+}
+
+/// A descriptor in an [`Index`]'s `manifests` array.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ManifestDescriptor {
+    media_type: String,
+    digest: String,
+    size: u64,
+    annotations: Vec<(String, String)>,
+}
+
+/// an error at the start of a JSON value
+fn invalid(node: &Node<'_>) -> Error {
+    Error::at(offset(node.start), Kind::IndexInvalid)
+}
+
+/// a required string field of `object`
+fn string<'a>(object: &'a Node<'_>, key: &str) -> Result<(&'a Node<'a>, &'a str), Error> {
+    let node = object.get(key).ok_or(invalid(object))?;
+    Ok((node, node.as_str().ok_or(invalid(node))?))
+}
+
+/// move an error within a JSON string's contents to its offset in the document
+fn shift(node: &Node<'_>) -> impl Fn(Error) -> Error {
+    let start = offset(node.start.saturating_add(1));
+    move |e| Error::at(e.index().saturating_add(start), e.kind())
+}
+
+impl ManifestDescriptor {
+    fn new(node: &Node<'_>) -> Result<Self, Error> {
+        let (_, media_type) = string(node, "mediaType")?;
+        let (digest_node, digest) = string(node, "digest")?;
+        let size_node = node.get("size").ok_or(invalid(node))?;
+        let size = size_node
+            .as_number()
+            .and_then(|n| n.parse::<i64>().ok())
+            .ok_or(invalid(size_node))?;
+        let size = Descriptor::new(digest, size)
+            .map_err(|e| match e.kind() {
+                Kind::DescriptorSizeNegative => Error::at(offset(size_node.start), e.kind()),
+                _ => shift(digest_node)(e),
+            })?
+            .size();
+
+        let mut annotations = Vec::new();
+        if let Some(object) = node.get("annotations") {
+            for (key, value) in object.as_object().ok_or(invalid(object))? {
+                let value_str = value.as_str().ok_or(invalid(value))?;
+                if key == REF_NAME_ANNOTATION {
+                    RefName::new(value_str).map_err(shift(value))?;
+                }
+                annotations.push((key.clone(), value_str.into()));
+            }
+        }
+        Ok(Self {
+            media_type: media_type.into(),
+            digest: digest.into(),
+            size,
+            annotations,
+        })
+    }
+    /// The manifest's media type, such as `application/vnd.oci.image.manifest.v1+json`.
+    pub fn media_type(&self) -> &str {
+        &self.media_type
+    }
+    /// The manifest's digest.
+    #[allow(clippy::unwrap_used)]
+    pub fn digest(&self) -> Digest<'_> {
+        Digest::new(&self.digest).unwrap() // validated in `Index::new`
+    }
+    /// The manifest's size in bytes.
+    pub const fn size(&self) -> u64 {
+        self.size
+    }
+    /// The value of an annotation, if present.
+    pub fn annotation(&self, key: &str) -> Option<&str> {
+        self.annotations
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+    /// The descriptor's annotations, in order.
+    pub fn annotations(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.annotations
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+    /// The manifest's `org.opencontainers.image.ref.name` annotation, if present.
+    #[allow(clippy::unwrap_used)]
+    pub fn ref_name(&self) -> Option<RefName<'_>> {
+        self.annotation(REF_NAME_ANNOTATION)
+            .map(|name| RefName::new(name).unwrap()) // validated in `Index::new`
+    }
+}
+
+impl Index {
+    /// Parse an `index.json`. The `schemaVersion` must be 2, and each manifest
+    /// needs a `mediaType`, a valid `digest`, and a non-negative `size`; other
+    /// fields are ignored. Errors are reported at offsets within `json`, though
+    /// offsets within strings containing escapes may be off.
+    pub fn new(json: &str) -> Result<Self, Error> {
+        let root = json::parse(json)?;
+        let version = root.get("schemaVersion").ok_or(invalid(&root))?;
+        if version.as_number() != Some("2") {
+            return Err(invalid(version));
+        }
+        let manifests = root.get("manifests").ok_or(invalid(&root))?;
+        let manifests = manifests
+            .as_array()
+            .ok_or(invalid(manifests))?
+            .iter()
+            .map(ManifestDescriptor::new)
+            .collect::<Result<_, _>>()?;
+        Ok(Self { manifests })
+    }
+    /// The descriptors in the `manifests` array, in order.
+    pub fn manifests(&self) -> &[ManifestDescriptor] {
+        &self.manifests
+    }
+    /// The first manifest with the given ref name.
+    pub fn by_ref_name(&self, name: &str) -> Option<&ManifestDescriptor> {
+        self.manifests
+            .iter()
+            .find(|m| m.annotation(REF_NAME_ANNOTATION) == Some(name))
+    }
+    /// The first manifest with the given digest.
+    pub fn by_digest(&self, digest: Digest<'_>) -> Option<&ManifestDescriptor> {
+        self.manifests.iter().find(|m| m.digest() == digest)
+    }
+    /// Find a manifest by ref name, or by digest if no ref name matches. Ref
+    /// names are tried first since a name like `sha256:...` could be either.
+    pub fn resolve(&self, reference: &str) -> Option<&ManifestDescriptor> {
+        self.by_ref_name(reference).or_else(|| {
+            Digest::new(reference)
+                .ok()
+                .and_then(|digest| self.by_digest(digest))
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use alloc::format;
+
+    use super::*;
+
+    const HEX: &str = "853ff93762a06ddbf722c4ebe9ddd66d8f63ddaea97f521c3ecc20da7c976020";
+    const MANIFEST: &str = "application/vnd.oci.image.manifest.v1+json";
+
+    fn manifest(digest: &str, size: &str, annotations: &str) -> String {
+        format!(r#"{{"mediaType":"{MANIFEST}","digest":"{digest}","size":{size}{annotations}}}"#)
+    }
+    fn index(manifests: &str) -> String {
+        format!(r#"{{"schemaVersion":2,"manifests":[{manifests}]}}"#)
+    }
+    fn err(json: &str) -> (u16, Kind) {
+        let e = Index::new(json).err().unwrap();
+        (e.index(), e.kind())
+    }
+
+    #[test]
+    fn resolution() {
+        let sha256 = format!("sha256:{HEX}");
+        let sha512 = format!("sha512:{HEX}{HEX}");
+        let named =
+            |name: &str| format!(r#","annotations":{{"{REF_NAME_ANNOTATION}":"{name}","x":"y"}}"#);
+        let json = index(&format!(
+            "{},{},{}",
+            manifest(&sha256, "1", &named("latest")),
+            manifest(&sha512, "2", &named(&sha256)),
+            manifest(&sha256, "3", ""),
+        ));
+        let index = Index::new(&json).unwrap();
+        assert_eq!(index.manifests().len(), 3);
+
+        let latest = index.by_ref_name("latest").unwrap();
+        assert_eq!(latest.media_type(), MANIFEST);
+        assert_eq!(latest.ref_name().unwrap().to_str(), "latest");
+        assert_eq!(latest.annotation("x"), Some("y"));
+        assert_eq!(latest.annotations().count(), 2);
+        assert!(index.manifests()[2].ref_name().is_none());
+
+        let digest = Digest::new(&sha512).unwrap();
+        assert_eq!(index.by_digest(digest).unwrap().size(), 2);
+        // ref names take precedence over digests
+        assert_eq!(index.resolve(&sha256).unwrap().size(), 2);
+        assert_eq!(index.resolve(&sha512).unwrap().size(), 2);
+        assert!(index.resolve("missing").is_none());
+        assert!(Index::new(&self::index("")).unwrap().manifests().is_empty());
+    }
+
+    #[test]
+    fn invalid_indexes() {
+        let sha256 = format!("sha256:{HEX}");
+        assert_eq!(err("{"), (1, Kind::JsonInvalid));
+        assert_eq!(err(r#"{"manifests":[]}"#), (0, Kind::IndexInvalid));
+        assert_eq!(
+            err(r#"{"schemaVersion":1,"manifests":[]}"#),
+            (17, Kind::IndexInvalid)
+        );
+        assert_eq!(
+            err(r#"{"schemaVersion":2,"manifests":{}}"#),
+            (31, Kind::IndexInvalid)
+        );
+        let start = r#"{"schemaVersion":2,"manifests":["#.len();
+        assert_eq!(
+            err(&index(r#"{"digest":"x","size":1}"#)),
+            (offset(start), Kind::IndexInvalid)
+        );
+        let digest_at = offset(start + manifest("", "", "").find(r#""digest":""#).unwrap() + 10);
+        assert_eq!(
+            err(&index(&manifest("sha256:abc", "1", ""))),
+            (digest_at + 3, Kind::OciRegisteredAlgorithmWrongDigestLength)
+        );
+        let size = manifest(&sha256, "-1", "");
+        let size_at = offset(start + size.find("-1").unwrap());
+        assert_eq!(err(&index(&size)), (size_at, Kind::DescriptorSizeNegative));
+        let size = manifest(&sha256, "1.5", "");
+        assert_eq!(err(&index(&size)), (size_at, Kind::IndexInvalid));
+        let name = manifest(
+            &sha256,
+            "1",
+            &format!(r#","annotations":{{"{REF_NAME_ANNOTATION}":"a b"}}"#),
+        );
+        let name_at = offset(start + name.find("a b").unwrap());
+        assert_eq!(err(&index(&name)), (name_at + 1, Kind::RefNameInvalidChar));
+    }
+}
@@ -0,0 +1,324 @@
+//! A small JSON reader for documents like `index.json`. It keeps each value's
+//! offset for error reporting, borrows numbers from the source, and limits
+//! nesting so untrusted input can't overflow the stack.
+
+use alloc::{string::String, vec::Vec};
+
+use super::index as offset;
+use crate::{err::Kind, Error};
+
+/// The deepest nesting of arrays and objects accepted.
+const MAX_DEPTH: usize = 64;
+
+/// A value and the offset where it starts.
+pub(crate) struct Node<'src> {
+    pub(crate) start: usize,
+    pub(crate) value: Value<'src>,
+}
+
+pub(crate) enum Value<'src> {
+    Null,
+    True,
+    False,
+    /// the number as written, since JSON numbers have no fixed precision
+    Number(&'src str),
+    String(String),
+    Array(Vec<Node<'src>>),
+    Object(Vec<(String, Node<'src>)>),
+}
+
+impl<'src> Node<'src> {
+    /// the value of the first member named `key`, if this is an object
+    pub(crate) fn get(&self, key: &str) -> Option<&Node<'src>> {
+        match &self.value {
+            Value::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match &self.value {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+    pub(crate) const fn as_number(&self) -> Option<&'src str> {
+        match self.value {
+            Value::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+    pub(crate) fn as_array(&self) -> Option<&[Node<'src>]> {
+        match &self.value {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+    pub(crate) fn as_object(&self) -> Option<&[(String, Node<'src>)]> {
+        match &self.value {
+            Value::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+}
+
+struct Parser<'src> {
+    src: &'src str,
+    i: usize,
+}
+
+impl<'src> Parser<'src> {
+    fn peek(&self) -> Option<u8> {
+        self.src.as_bytes().get(self.i).copied()
+    }
+    fn invalid(&self) -> Error {
+        Error::at(offset(self.i), Kind::JsonInvalid)
+    }
+    fn whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.i += 1;
+        }
+    }
+    fn expect(&mut self, c: u8) -> Result<(), Error> {
+        self.whitespace();
+        if self.peek() == Some(c) {
+            self.i += 1;
+            Ok(())
+        } else {
+            Err(self.invalid())
+        }
+    }
+    fn literal(&mut self, word: &str, value: Value<'src>) -> Result<Value<'src>, Error> {
+        if self.src[self.i..].starts_with(word) {
+            self.i += word.len();
+            Ok(value)
+        } else {
+            Err(self.invalid())
+        }
+    }
+    fn value(&mut self, depth: usize) -> Result<Node<'src>, Error> {
+        self.whitespace();
+        let start = self.i;
+        let value = match self.peek() {
+            Some(b'{' | b'[') if depth == MAX_DEPTH => return Err(self.invalid()),
+            Some(b'{') => Value::Object(self.members(b'}', |p| {
+                p.whitespace();
+                if p.peek() != Some(b'"') {
+                    return Err(p.invalid());
+                }
+                let key = p.string()?;
+                p.expect(b':')?;
+                Ok((key, p.value(depth + 1)?))
+            })?),
+            Some(b'[') => Value::Array(self.members(b']', |p| p.value(depth + 1))?),
+            Some(b'"') => Value::String(self.string()?),
+            Some(b't') => self.literal("true", Value::True)?,
+            Some(b'f') => self.literal("false", Value::False)?,
+            Some(b'n') => self.literal("null", Value::Null)?,
+            Some(b'-' | b'0'..=b'9') => Value::Number(self.number()?),
+            _ => return Err(self.invalid()),
+        };
+        Ok(Node { start, value })
+    }
+    /// parse comma-separated items up to `close`, starting at the opening bracket
+    fn members<T>(
+        &mut self,
+        close: u8,
+        mut item: impl FnMut(&mut Self) -> Result<T, Error>,
+    ) -> Result<Vec<T>, Error> {
+        self.i += 1;
+        let mut items = Vec::new();
+        self.whitespace();
+        if self.peek() == Some(close) {
+            self.i += 1;
+            return Ok(items);
+        }
+        loop {
+            items.push(item(self)?);
+            self.whitespace();
+            match self.peek() {
+                Some(b',') => self.i += 1,
+                Some(c) if c == close => {
+                    self.i += 1;
+                    return Ok(items);
+                }
+                _ => return Err(self.invalid()),
+            }
+        }
+    }
+    fn digits(&mut self) -> usize {
+        let start = self.i;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.i += 1;
+        }
+        self.i - start
+    }
+    fn number(&mut self) -> Result<&'src str, Error> {
+        let start = self.i;
+        if self.peek() == Some(b'-') {
+            self.i += 1;
+        }
+        match self.peek() {
+            Some(b'0') => self.i += 1,
+            Some(b'1'..=b'9') => {
+                self.digits();
+            }
+            _ => return Err(self.invalid()),
+        }
+        if self.peek() == Some(b'.') {
+            self.i += 1;
+            if self.digits() == 0 {
+                return Err(self.invalid());
+            }
+        }
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            self.i += 1;
+            if matches!(self.peek(), Some(b'+' | b'-')) {
+                self.i += 1;
+            }
+            if self.digits() == 0 {
+                return Err(self.invalid());
+            }
+        }
+        Ok(&self.src[start..self.i])
+    }
+    fn hex4(&mut self) -> Result<u32, Error> {
+        let hex = self
+            .src
+            .get(self.i..self.i + 4)
+            .filter(|h| h.bytes().all(|c| c.is_ascii_hexdigit()))
+            .ok_or(self.invalid())?;
+        self.i += 4;
+        u32::from_str_radix(hex, 16).map_err(|_| self.invalid())
+    }
+    /// decode a `\u` escape, starting just past the `u`
+    fn unicode(&mut self, escape: usize) -> Result<char, Error> {
+        let invalid = Error::at(offset(escape), Kind::JsonInvalid);
+        let high = self.hex4()?;
+        let code = match high {
+            0xD800..=0xDBFF => {
+                if !self.src[self.i..].starts_with("\\u") {
+                    return Err(invalid);
+                }
+                self.i += 2;
+                let low = self.hex4()?;
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    return Err(invalid);
+                }
+                0x10000_u32.checked_add((high & 0x3FF) << 10 | (low & 0x3FF))
+            }
+            0xDC00..=0xDFFF => None,
+            code => Some(code),
+        };
+        code.and_then(char::from_u32).ok_or(invalid)
+    }
+    /// parse a string, starting at the opening quote
+    fn string(&mut self) -> Result<String, Error> {
+        self.i += 1;
+        let mut out = String::new();
+        loop {
+            let run = self.i;
+            while self
+                .peek()
+                .is_some_and(|c| c != b'"' && c != b'\\' && c >= 0x20)
+            {
+                self.i += 1;
+            }
+            out.push_str(&self.src[run..self.i]);
+            match self.peek() {
+                Some(b'"') => {
+                    self.i += 1;
+                    return Ok(out);
+                }
+                Some(b'\\') => {
+                    let escape = self.i;
+                    self.i += 2;
+                    let c = match self.src.as_bytes().get(escape + 1) {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => self.unicode(escape)?,
+                        _ => return Error::at(offset(escape), Kind::JsonInvalid).into(),
+                    };
+                    out.push(c);
+                }
+                _ => return Err(self.invalid()),
+            }
+        }
+    }
+}
+
+/// parse a whole JSON document
+pub(crate) fn parse(src: &str) -> Result<Node<'_>, Error> {
+    let mut parser = Parser { src, i: 0 };
+    let root = parser.value(0)?;
+    parser.whitespace();
+    if parser.i == src.len() {
+        Ok(root)
+    } else {
+        Err(parser.invalid())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    fn err(src: &str) -> u16 {
+        let e = parse(src).err().unwrap();
+        assert_eq!(e.kind(), Kind::JsonInvalid);
+        e.index()
+    }
+
+    #[test]
+    fn values() {
+        let root = parse(r#" {"a": [1, -2.5e+3, true, false, null], "b": {"c": "d"}} "#).unwrap();
+        assert_eq!(root.start, 1);
+        let a = root.get("a").unwrap().as_array().unwrap();
+        assert_eq!(a.len(), 5);
+        assert_eq!(a[1].as_number(), Some("-2.5e+3"));
+        assert_eq!(a[1].start, 11);
+        assert!(matches!(a[2].value, Value::True));
+        assert!(matches!(a[3].value, Value::False));
+        assert!(matches!(a[4].value, Value::Null));
+        let b = root.get("b").unwrap();
+        assert_eq!(b.as_object().unwrap().len(), 1);
+        assert_eq!(b.get("c").unwrap().as_str(), Some("d"));
+        assert!(root.get("z").is_none());
+        assert!(parse("[]").unwrap().as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn strings() {
+        let s = parse(r#""a\"\\\/\b\f\n\r\té😀ü""#).unwrap();
+        assert_eq!(s.as_str(), Some("a\"\\/\u{8}\u{c}\n\r\té😀ü"));
+        assert_eq!(err(r#""\x""#), 1);
+        assert_eq!(err(r#""\u12""#), 3);
+        assert_eq!(err(r#""\ud83d""#), 1);
+        assert_eq!(err(r#""\ude00""#), 1);
+        assert_eq!(err("\"a\nb\""), 2);
+        assert_eq!(err("\"abc"), 4);
+    }
+
+    #[test]
+    fn invalid_documents() {
+        assert_eq!(err(""), 0);
+        assert_eq!(err("[1,]"), 3);
+        assert_eq!(err("[1 2]"), 3);
+        assert_eq!(err(r#"{"a" 1}"#), 5);
+        assert_eq!(err("{1: 2}"), 1);
+        assert_eq!(err("01"), 1);
+        assert_eq!(err("1."), 2);
+        assert_eq!(err("tru"), 0);
+        assert_eq!(err("{} {}"), 3);
+        let deep = "[".repeat(MAX_DEPTH + 1);
+        assert_eq!(err(&deep), MAX_DEPTH.to_string().parse().unwrap());
+    }
+}
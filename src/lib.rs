@@ -31,20 +31,45 @@
 // #![warn(clippy::indexing_slicing)] // used too often to enable
 // #![warn(clippy::unreachable)]      // used too often to enable
 // #![warn(clippy::or_fun_call)]      // warns about ok_or(Error::at(...))
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
 pub(crate) mod ambiguous;
+mod any_ref;
+mod ascii;
+#[cfg(feature = "alloc")]
+pub mod batch;
 pub mod digest;
+pub mod endpoint;
 pub mod err;
+pub mod helm;
+pub mod layout;
+mod limits;
 pub mod name;
+#[cfg(feature = "alloc")]
+pub mod purl;
+pub mod scope;
+mod simd;
 mod span;
 pub mod tag;
+pub mod transport;
+#[cfg(feature = "alloc")]
+pub mod url;
+mod validate;
 
+#[doc(inline)]
+pub use any_ref::AnyRef;
+#[doc(inline)]
+pub use limits::Limits;
 #[doc(inline)]
 pub use name::{domain, path};
 use name::{domain::Domain, path::Path, Name, NameSpan};
 
 use core::ops::{Range, RangeFrom};
 
-use digest::Digest;
+use digest::{registry::AlgorithmRegistry, Digest};
 
 use crate::span::OptionallyZero;
 
@@ -55,7 +80,7 @@ use self::{
 pub(crate) type Error = err::Error<u16>;
 /// A reference to a container image. Must contain at least a name, but it may
 /// also contain a tag and/or digest.
-#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 struct RefSpan<'src> {
     /// the name of the image. This is the domain and path, but not the tag or digest.
     name: NameSpan<'src>,
@@ -65,36 +90,41 @@ struct RefSpan<'src> {
 }
 
 impl<'src> RefSpan<'src> {
-    fn new(src: &'src str) -> Result<Self, Error> {
+    fn new(
+        src: &'src [u8],
+        limits: Limits,
+        registry: AlgorithmRegistry<'_>,
+    ) -> Result<Self, Error> {
         if src.is_empty() {
             return Error::at(0, err::Kind::RefMissing).into();
         };
-        let prefix = DomainOrRefSpan::new(src)?;
+        let prefix = DomainOrRefSpan::new(src, limits)?;
         let domain = match prefix {
             DomainOrRefSpan::Domain(domain) => Some(domain),
             DomainOrRefSpan::TaggedRef(_) => None,
         };
         let mut index: u16 = domain.map(|d| d.short_len().upcast()).unwrap_or(0); // current max: 256
-        let path = match src.as_bytes().get(prefix.len()) {
+        let path = match src.get(prefix.len()) {
             Some(b'/') => match prefix {
                 DomainOrRefSpan::TaggedRef((path_start, tag)) => match tag {
                     Some(_) => unreachable!(),
                     //         ^^^^^^^^^^^^ if a tag is present and is followed
                     //                      by a `/`, it's PortInvalidChar error
-                    None => path_start.extend(&src[prefix.len()..]),
+                    None => path_start.extend(&src[prefix.len()..], limits.name()),
                     // e.g. "cant_be_host/more_path" needs to entirely match as path
                 },
                 DomainOrRefSpan::Domain(_) => {
                     index = index.saturating_add(1); // consume the leading slash; ok since index <= 256
                     let rest = &src[prefix.len().saturating_add(1)..];
-                    PathSpan::new(rest)
+                    PathSpan::new(rest, limits.name())
                 }
             }
-            .map_err(|e: err::Error<u8>| {
-                Error::at(
-                    prefix.short_len().upcast().saturating_add(e.index() as u16),
-                    e.kind(),
-                )
+            .map_err(|e: Error| {
+                let start = match prefix {
+                    DomainOrRefSpan::Domain(_) => index, // just past the leading '/'
+                    DomainOrRefSpan::TaggedRef(_) => prefix.short_len().upcast(),
+                };
+                Error::at(start.saturating_add(e.index()), e.kind())
             }),
             Some(b'@') | Some(b':') | None => match prefix {
                 DomainOrRefSpan::TaggedRef((name, _)) => Ok(name),
@@ -103,33 +133,44 @@ impl<'src> RefSpan<'src> {
             },
             Some(_) => Error::at(index, err::Kind::PathInvalidChar).into(),
         }?; // TODO: check correctness
-        index = index.saturating_add(path.short_len().upcast().into()); // ok since index <= 256, path <= 256
-        if index > name::MAX_LEN.into() {
-            return Error::at(255, err::Kind::NameTooLong).into();
+        index = index.saturating_add(path.short_len().upcast()); // ok since domain <= 256 and path <= Limits::MAX_NAME
+        if index > limits.name() {
+            return Error::at(limits.name(), err::Kind::NameTooLong).into();
         }
         // let rest = &src[index as usize..];
         let tag = match prefix {
             DomainOrRefSpan::TaggedRef((_, right)) => match right {
                 Some(tag) => Ok(Some(tag)),
-                None => match src.as_bytes().get(index as usize) {
-                    Some(b':') => TagSpan::new(&src[(index as usize).saturating_add(1)..])
-                        .map_err(|e| e.into())
-                        .map(Some),
+                None => match src.get(index as usize) {
+                    Some(b':') => {
+                        TagSpan::new(&src[(index as usize).saturating_add(1)..], limits.tag())
+                            .map_err(|e| {
+                                Error::at(
+                                    index
+                                    .saturating_add(1u16) // +1 to account for the leading ':'
+                                    .saturating_add(e.index().into()),
+                                    e.kind(),
+                                )
+                            })
+                            .map(Some)
+                    }
                     Some(b'@') | None => Ok(None),
                     Some(_) => Error::at(0, err::Kind::PathInvalidChar).into(),
                 },
             },
-            DomainOrRefSpan::Domain(_) => match src.as_bytes().get(index as usize) {
-                Some(b':') => TagSpan::new(&src[(index as usize).saturating_add(1)..])
-                    .map(Some)
-                    .map_err(|e| {
-                        Error::at(
-                            index
+            DomainOrRefSpan::Domain(_) => match src.get(index as usize) {
+                Some(b':') => {
+                    TagSpan::new(&src[(index as usize).saturating_add(1)..], limits.tag())
+                        .map(Some)
+                        .map_err(|e| {
+                            Error::at(
+                                index
                         .saturating_add(1u16) // +1 to account for the leading ':'
                         .saturating_add(e.index().into()),
-                            e.kind(),
-                        )
-                    }),
+                                e.kind(),
+                            )
+                        })
+                }
                 Some(_) | None => Ok(None),
             },
         }?;
@@ -140,10 +181,10 @@ impl<'src> RefSpan<'src> {
                 .map(|t: u16| t.saturating_add(1)) // +1 for the leading ':'
                 .unwrap_or(0_u16),
         );
-        let digest = match src.as_bytes().get(index as usize) {
+        let digest = match src.get(index as usize) {
             Some(b'@') => {
                 index = index.saturating_add(1); // max 385
-                DigestSpan::new(&src[index as usize..])
+                DigestSpan::new(&src[index as usize..], limits, registry)
                     .map(Some)
                     .map_err(|e| Error::at(index.saturating_add(e.index()), e.kind()))
                 // safe since e.index() <= 1024
@@ -229,7 +270,7 @@ impl<'src> RefSpan<'src> {
 /// let digest = img_ref.digest().unwrap();
 /// assert_eq!(digest.to_str(), "algo:encoded");
 /// ```
-#[derive(PartialEq)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct ImgRef<'src> {
     src: &'src str,
     span: RefSpan<'src>,
@@ -238,8 +279,55 @@ impl<'src> ImgRef<'src> {
     /// Parse an image reference string. The entire source string must be one
     /// valid image reference.
     pub fn new(src: &'src str) -> Result<Self, Error> {
-        let span = RefSpan::new(src)?;
-        Ok(Self { src, span })
+        Self::with_limits(src, Limits::DEFAULT)
+    }
+    /// Parse an image reference string with custom limits on the length of
+    /// each section. See [`Limits`] for details.
+    pub fn with_limits(src: &'src str, limits: Limits) -> Result<Self, Error> {
+        Self::with_registry(src, limits, AlgorithmRegistry::DEFAULT)
+    }
+    /// Parse an image reference string, validating any digest against the rules
+    /// in a custom [`AlgorithmRegistry`].
+    pub fn with_registry(
+        src: &'src str,
+        limits: Limits,
+        registry: AlgorithmRegistry<'_>,
+    ) -> Result<Self, Error> {
+        Self::parse(src.as_bytes(), limits, registry)
+    }
+    /// Parse an image reference from bytes without validating them as UTF-8.
+    /// Non-ascii bytes are rejected with the same error as `ImgRef::new`.
+    /// ```rust
+    /// use container_image_dist_ref::{err, ImgRef};
+    /// let img_ref = ImgRef::from_bytes(b"host.com/repo:tag").unwrap();
+    /// assert_eq!(img_ref.tag(), Some("tag"));
+    /// let err = ImgRef::from_bytes(b"host.com/repo:t\xffg").err().unwrap();
+    /// assert_eq!((err.index(), err.kind()), (15, err::Kind::TagInvalidChar));
+    /// ```
+    pub fn from_bytes(src: &'src [u8]) -> Result<Self, Error> {
+        Self::parse(src, Limits::DEFAULT, AlgorithmRegistry::DEFAULT)
+    }
+    pub(crate) fn parse(
+        src: &'src [u8],
+        limits: Limits,
+        registry: AlgorithmRegistry<'_>,
+    ) -> Result<Self, Error> {
+        let span = RefSpan::new(src, limits, registry)?;
+        // a valid reference consumes the entire source
+        Ok(Self {
+            src: ascii::prefix(src, src.len()),
+            span,
+        })
+    }
+    /// Check whether a string is a valid image reference without recording the
+    /// location of any of its sections. Always agrees with `ImgRef::new(src).is_ok()`.
+    /// ```rust
+    /// use container_image_dist_ref::ImgRef;
+    /// assert!(ImgRef::is_valid("host.com/repo:tag@algo:encoded"));
+    /// assert!(!ImgRef::is_valid("host.com/repo:tag/"));
+    /// ```
+    pub fn is_valid(src: &str) -> bool {
+        validate::reference(src).is_some()
     }
 
     fn name_str(&self) -> &str {
@@ -308,6 +396,12 @@ impl PartialOrd for ImgRef<'_> {
     }
 }
 
+impl core::fmt::Debug for ImgRef<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("ImgRef").field(&self.src).finish()
+    }
+}
+
 struct CanonicalSpan<'src> {
     span: RefSpan<'src>,
 }
@@ -329,8 +423,12 @@ macro_rules! unwrap_inner_method {
 }
 
 impl<'src> CanonicalSpan<'src> {
-    fn new(src: &'src str) -> Result<Self, Error> {
-        Self::from_span(RefSpan::new(src)?)
+    fn new(
+        src: &'src [u8],
+        limits: Limits,
+        registry: AlgorithmRegistry<'_>,
+    ) -> Result<Self, Error> {
+        Self::from_span(RefSpan::new(src, limits, registry)?)
     }
     // FIXME: move from_span -> TryFrom<RefSpan> impl
     fn from_span(span: RefSpan<'src>) -> Result<Self, Error> {
@@ -376,8 +474,53 @@ impl<'src> CanonicalImgRef<'src> {
     /// Parse a canonical image reference string. The entire source string must be one
     /// valid canonical reference.
     pub fn new(src: &'src str) -> Result<Self, Error> {
-        let span = CanonicalSpan::new(src)?;
-        Ok(Self { src, span })
+        Self::with_limits(src, Limits::DEFAULT)
+    }
+    /// Parse a canonical image reference string with custom limits on the
+    /// length of each section. See [`Limits`] for details.
+    pub fn with_limits(src: &'src str, limits: Limits) -> Result<Self, Error> {
+        Self::with_registry(src, limits, AlgorithmRegistry::DEFAULT)
+    }
+    /// Parse a canonical image reference string, validating the digest against
+    /// the rules in a custom [`AlgorithmRegistry`].
+    pub fn with_registry(
+        src: &'src str,
+        limits: Limits,
+        registry: AlgorithmRegistry<'_>,
+    ) -> Result<Self, Error> {
+        Self::parse(src.as_bytes(), limits, registry)
+    }
+    /// Parse a canonical image reference from bytes without validating them as UTF-8.
+    pub fn from_bytes(src: &'src [u8]) -> Result<Self, Error> {
+        Self::parse(src, Limits::DEFAULT, AlgorithmRegistry::DEFAULT)
+    }
+    fn parse(
+        src: &'src [u8],
+        limits: Limits,
+        registry: AlgorithmRegistry<'_>,
+    ) -> Result<Self, Error> {
+        let span = CanonicalSpan::new(src, limits, registry)?;
+        // a valid reference consumes the entire source
+        Ok(Self {
+            src: ascii::prefix(src, src.len()),
+            span,
+        })
+    }
+    /// Check whether a string is a valid canonical image reference without recording
+    /// the location of any of its sections. Always agrees with `CanonicalImgRef::new(src).is_ok()`.
+    /// ```rust
+    /// use container_image_dist_ref::CanonicalImgRef;
+    /// assert!(CanonicalImgRef::is_valid("host.com/repo@algo:encoded"));
+    /// assert!(!CanonicalImgRef::is_valid("host.com/repo:tag"));
+    /// ```
+    pub fn is_valid(src: &str) -> bool {
+        matches!(
+            validate::reference(src),
+            Some(validate::Found {
+                domain: true,
+                digest: true
+            })
+        )
     }
     fn domain_str(&self) -> &str {
         self.span
@@ -599,6 +742,15 @@ mod tests {
         );
         should_parse_as("0_0/0", None, Some("0_0/0"), None, None);
         should_fail_with("0_0/", Error::at(4, err::Kind::PathComponentInvalidEnd));
+        // path errors after a domain are reported past the domain's '/'
+        should_fail_with("test.com/Repo", Error::at(9, err::Kind::PathInvalidChar));
+        should_fail_with(
+            "test.com/repo/",
+            Error::at(14, err::Kind::PathComponentInvalidEnd),
+        );
+        // tags after a path are limited to 128 characters, like any other tag
+        let long_tag = String::from("test.com/repo:") + &"t".repeat(129);
+        should_fail_with(&long_tag, Error::at(142, err::Kind::TagTooLong));
     }
     #[test]
     fn test_with_digest() {
@@ -648,6 +800,13 @@ mod tests {
             src.push(':');
             should_fail_with(&src, Error::at(258, err::Kind::EncodedMissing))
         };
+        // uppercase letters anywhere in an algorithm component aren't OCI-compliant
+        let hex = "f".repeat(32);
+        let upper = String::from("repo@aB:") + &hex;
+        assert_eq!(
+            should_parse(&upper).digest().unwrap().compliance(),
+            digest::Compliance::Distribution
+        );
     }
     #[test]
     fn test_bad_ipv6_fails() {
@@ -0,0 +1,207 @@
+//! # Length limits
+//! The maximum length of each section of an image reference. The defaults are
+//! the limits used by `distribution/reference` (for names and tags) and the
+//! arbitrary-but-realistic limits set in this repository (for digests).
+//!
+//! Limits are clamped to what each section's span type can store: tags and
+//! algorithms are stored as `u8`s, so they can be at most 255 characters long.
+//! Names and encoded digests are clamped so that every offset within a valid
+//! reference fits in a `u16`. Individual path components are always limited to
+//! 255 characters.
+//! ```rust
+//! use container_image_dist_ref::{err, ImgRef, Limits};
+//! let long_path = format!("host.com/{}", "a/".repeat(200) + "b");
+//! let err = ImgRef::new(&long_path).err().unwrap();
+//! assert_eq!(err.kind(), err::Kind::PathTooLong);
+//!
+//! let limits = Limits::DEFAULT.with_name(1024);
+//! let img_ref = ImgRef::with_limits(&long_path, limits).unwrap();
+//! assert_eq!(img_ref.name().to_str(), long_path);
+//!
+//! let limits = Limits::DEFAULT.with_tag(3);
+//! let err = ImgRef::with_limits("repo:tags", limits).err().unwrap();
+//! assert_eq!((err.index(), err.kind()), (8, err::Kind::TagTooLong));
+//! ```
+
+use crate::{digest, name, tag};
+
+/// Upper bounds on the length of each section of an image reference.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limits {
+    name: u16,
+    tag: u8,
+    algorithm: u8,
+    encoded: u16,
+}
+
+impl Limits {
+    /// The limits used by [`ImgRef::new`](crate::ImgRef::new) and every other
+    /// parser that doesn't accept `Limits`.
+    pub const DEFAULT: Self = Self {
+        name: name::MAX_LEN as u16,
+        tag: tag::MAX_LEN.get(),
+        algorithm: digest::algorithm::MAX_LEN,
+        encoded: digest::encoded::MAX_LEN,
+    };
+    /// The largest limit on the total length of a name (domain and path).
+    pub const MAX_NAME: u16 = 8192;
+    /// The largest limit on the length of an encoded digest.
+    pub const MAX_ENCODED: u16 = 32768;
+
+    /// Limit the total length of a name, including the domain and path.
+    /// Clamped to `1..=`[`Limits::MAX_NAME`].
+    pub const fn with_name(self, max: u16) -> Self {
+        Self {
+            name: clamp(max, Self::MAX_NAME),
+            ..self
+        }
+    }
+    /// Limit the length of a tag, not including the leading `:`.
+    pub const fn with_tag(self, max: u8) -> Self {
+        Self {
+            tag: if max == 0 { 1 } else { max },
+            ..self
+        }
+    }
+    /// Limit the length of a digest's algorithm.
+    pub const fn with_algorithm(self, max: u8) -> Self {
+        Self {
+            algorithm: if max == 0 { 1 } else { max },
+            ..self
+        }
+    }
+    /// Limit the length of a digest's encoded section. Clamped to
+    /// `1..=`[`Limits::MAX_ENCODED`].
+    pub const fn with_encoded(self, max: u16) -> Self {
+        Self {
+            encoded: clamp(max, Self::MAX_ENCODED),
+            ..self
+        }
+    }
+    /// The maximum total length of a name, including the domain and path.
+    pub const fn name(self) -> u16 {
+        self.name
+    }
+    /// The maximum length of a tag, not including the leading `:`.
+    pub const fn tag(self) -> u8 {
+        self.tag
+    }
+    /// The maximum length of a digest algorithm.
+    pub const fn algorithm(self) -> u8 {
+        self.algorithm
+    }
+    /// The maximum length of a digest's encoded section.
+    pub const fn encoded(self) -> u16 {
+        self.encoded
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// keep a limit within `1..=max`, since every section is non-empty
+const fn clamp(n: u16, max: u16) -> u16 {
+    if n == 0 {
+        1
+    } else if n > max {
+        max
+    } else {
+        n
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    extern crate alloc;
+    use alloc::string::String;
+
+    use super::*;
+    use crate::{
+        digest::{Compliance, Digest},
+        err::Kind,
+        ImgRef,
+    };
+
+    fn err(src: &str, limits: Limits) -> (u16, Kind) {
+        let e = ImgRef::with_limits(src, limits).err().unwrap();
+        (e.index(), e.kind())
+    }
+
+    #[test]
+    fn defaults_match_constants() {
+        assert_eq!(Limits::default(), Limits::DEFAULT);
+        assert_eq!(Limits::DEFAULT.name(), 255);
+        assert_eq!(Limits::DEFAULT.tag(), 128);
+        assert_eq!(Limits::DEFAULT.algorithm(), 255);
+        assert_eq!(Limits::DEFAULT.encoded(), 1024);
+        assert_eq!(Limits::DEFAULT.with_name(0).name(), 1);
+        assert_eq!(
+            Limits::DEFAULT.with_encoded(u16::MAX).encoded(),
+            Limits::MAX_ENCODED
+        );
+    }
+
+    #[test]
+    fn raised_limits() {
+        let path = String::from("host.com/") + &"abcdefghi/".repeat(100) + "z";
+        assert_eq!(err(&path, Limits::DEFAULT), (9 + 255, Kind::PathTooLong));
+        let limits = Limits::DEFAULT.with_name(2048);
+        let img = ImgRef::with_limits(&path, limits).unwrap();
+        assert_eq!(img.path().to_str().len(), 1001);
+        let tagged = path.clone() + ":" + &"t".repeat(200);
+        assert_eq!(
+            ImgRef::with_limits(&tagged, limits.with_tag(255))
+                .unwrap()
+                .tag()
+                .unwrap()
+                .len(),
+            200
+        );
+        let digest = String::from("a:") + &"0".repeat(4096);
+        assert!(Digest::new(&digest).is_err());
+        assert!(Digest::with_limits(&digest, limits.with_encoded(4096)).is_ok());
+        // long hex digests stay compliant with distribution/reference
+        let digest = String::from("a:") + &"0".repeat(2000);
+        let digest = Digest::with_limits(&digest, limits.with_encoded(4096)).unwrap();
+        assert_eq!(digest.compliance(), Compliance::Universal);
+        assert_eq!(digest.explain_compliance().count(), 0);
+        let distribution = digest
+            .encoded()
+            .validate_algorithm(&digest.algorithm(), Compliance::Distribution);
+        assert_eq!(distribution.unwrap(), Compliance::Distribution);
+        // path components are still limited to 255 characters
+        let component = String::from("a/") + &"b".repeat(256);
+        assert_eq!(err(&component, limits).1, Kind::PathTooLong);
+    }
+
+    #[test]
+    fn lowered_limits_report_offsets() {
+        let limits = Limits::DEFAULT;
+        assert_eq!(
+            err("host.com/repo", limits.with_name(10)),
+            (10, Kind::NameTooLong)
+        );
+        assert_eq!(
+            err("a_b/cdef/ghi", limits.with_name(5)),
+            (5, Kind::PathTooLong)
+        );
+        assert_eq!(err("repo:tags", limits.with_tag(3)), (8, Kind::TagTooLong));
+        assert_eq!(err("a_b/c:tags", limits.with_tag(3)), (9, Kind::TagTooLong));
+        assert_eq!(
+            err("host.com/a:tags", limits.with_tag(3)),
+            (14, Kind::TagTooLong)
+        );
+        assert_eq!(
+            err("repo@a.bc:0", limits.with_algorithm(2)),
+            (7, Kind::AlgorithmTooLong)
+        );
+        assert_eq!(
+            err("repo@a:00000", limits.with_encoded(4)),
+            (11, Kind::EncodingTooLong)
+        );
+    }
+}
@@ -69,7 +69,7 @@ impl<'src> HostSpan<'src> {
     /// Parses a host from the start of a string. Can be either a domain name or an IPv6 address.
     /// Can consume only part of the source string if it reaches a valid stopping point,
     /// i.e. `:`, `/`, or `@`.
-    pub(crate) fn new(src: &'src str) -> Result<Self, Error> {
+    pub(crate) fn new(src: &'src [u8]) -> Result<Self, Error> {
         let ambiguous = HostOrPathSpan::new(src, HostKind::Any).map_err(disambiguate_err)?;
         // handle bracketed ipv6 addresses
         Self::try_from(ambiguous)
@@ -139,8 +139,12 @@ impl<'src> Host<'src> {
     /// Parse a valid host from the start of the string. Parsing may not consume the entire string
     /// if it reaches a valid stopping point, i.e. `:`, `/`, or `@`.
     pub fn new(src: &'src str) -> Result<Self, Error> {
+        Self::from_bytes(src.as_bytes())
+    }
+    /// Parse a host from the start of some bytes without validating them as UTF-8.
+    pub fn from_bytes(src: &'src [u8]) -> Result<Self, Error> {
         let span = HostSpan::new(src)?;
-        Ok(Self::from_span(src, span))
+        Ok(Self::from_span(crate::ascii::prefix(src, span.len()), span))
     }
     /// checks that the entire source string is consumed
     pub fn from_exact_match(src: &'src str) -> Result<Self, Error> {
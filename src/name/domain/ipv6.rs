@@ -73,7 +73,7 @@ impl State {
     fn increment_colon_count(&mut self) -> Result<(), err::Kind> {
         self.set_colon_count(self.colon_count().saturating_add(1))
     }
-    fn set_position_in_group(&mut self, pos: u8) -> Result<(), err::Kind> {
+    const fn set_position_in_group(&mut self, pos: u8) -> Result<(), err::Kind> {
         match pos {
             0..=3 => {
                 self.0 &= !Self::POSITION_IN_GROUP; // clear the position in group
@@ -83,7 +83,7 @@ impl State {
             _ => Err(err::Kind::Ipv6TooManyHexDigits),
         }
     }
-    fn set_group(&mut self, group: u8) -> Result<(), err::Kind> {
+    const fn set_group(&mut self, group: u8) -> Result<(), err::Kind> {
         match group {
             0..=7 => {
                 self.0 &= !Self::CURRENT_GROUP; // clear the current group
@@ -93,7 +93,7 @@ impl State {
             _ => Err(err::Kind::Ipv6TooManyGroups),
         }
     }
-    fn increment_group(&mut self) -> Result<(), err::Kind> {
+    const fn increment_group(&mut self) -> Result<(), err::Kind> {
         self.set_group(self.current_group().saturating_add(1))
     }
     fn set_colon(&mut self) -> Result<(), err::Kind> {
@@ -101,7 +101,7 @@ impl State {
         self.increment_group()?;
         self.set_position_in_group(0) // <- position=0 is always valid
     }
-    fn set_double_colon(&mut self) -> Result<(), err::Kind> {
+    const fn set_double_colon(&mut self) -> Result<(), err::Kind> {
         if self.double_colon_already_seen() {
             Err(err::Kind::Ipv6BadColon)
         } else {
@@ -109,7 +109,7 @@ impl State {
             Ok(())
         }
     }
-    fn set_last_was_colon(&mut self, last_was_colon: bool) {
+    const fn set_last_was_colon(&mut self, last_was_colon: bool) {
         self.0 |= (if last_was_colon { 1 } else { 0 }) << 5;
     }
     // getters -------------------------------------------------------------
@@ -132,8 +132,8 @@ impl State {
     }
 }
 impl<'src> Ipv6Span<'src> {
-    pub(crate) fn new(src: &'src str) -> Result<Self, Error> {
-        let mut ascii = src.bytes();
+    pub(crate) fn new(src: &'src [u8]) -> Result<Self, Error> {
+        let mut ascii = src.iter().copied();
         let mut index: NonZeroU8 = match ascii.next() {
             None => Error::at(0, err::Kind::HostMissing).into(),
             Some(b'[') => Ok(nonzero!(u8, 1_u8)), // consume the opening bracket
@@ -161,8 +161,8 @@ impl<'src> Ipv6Span<'src> {
                 .checked_add(1)
                 .ok_or(Error::at(u8::MAX, err::Kind::Ipv6TooLong))?;
         }
-        debug_assert!(src.as_bytes().first() == Some(&b'['));
-        debug_assert!(src.as_bytes().get(index.as_usize()) == Some(&b']'));
+        debug_assert!(src.first() == Some(&b'['));
+        debug_assert!(src.get(index.as_usize()) == Some(&b']'));
         index = index
             .checked_add(1) // consume t he closing bracket
             .ok_or(Error::at(u8::MAX, err::Kind::Ipv6TooLong))?;
@@ -185,7 +185,7 @@ mod test {
     use crate::span::Lengthy;
     #[allow(clippy::indexing_slicing)]
     fn should_work(ip: &str) {
-        match super::Ipv6Span::new(ip) {
+        match super::Ipv6Span::new(ip.as_bytes()) {
             Ok(span) => assert_eq!(
                 span.span_of(ip),
                 ip,
@@ -201,7 +201,7 @@ mod test {
         }
     }
     fn should_fail(ip: &str) {
-        if let Ok(span) = super::Ipv6Span::new(ip) {
+        if let Ok(span) = super::Ipv6Span::new(ip.as_bytes()) {
             panic!("should have failed to parse\n{ip}\n{}", span.span_of(ip),)
         }
     }
@@ -44,6 +44,14 @@ use crate::{
 };
 type Error = err::Error<u16>;
 
+/// whether a reference's first component names a registry rather than starting
+/// its path, following distribution/reference's `splitDockerDomain`
+pub(crate) fn is_registry(domain: &str) -> bool {
+    domain.contains(['.', ':'])
+        || domain == "localhost"
+        || domain.bytes().any(|c| c.is_ascii_uppercase())
+}
+
 /// a definite host and an optional port. Combined length MUST be under 255 chars.
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub(crate) struct DomainSpan<'src> {
@@ -84,10 +92,10 @@ impl<'src> DomainSpan<'src> {
     }
     /// parse a domain from the start of a string. Can consume only part of the source
     /// string if it reaches a valid stopping point, i.e. `/` or `@`
-    pub(crate) fn new(src: &'src str) -> Result<Self, Error> {
+    pub(crate) fn new(src: &'src [u8]) -> Result<Self, Error> {
         let host = HostSpan::new(src)?;
         let len: u16 = host.short_len().widen().into(); // max 255 chars
-        let port = match src.as_bytes().get(host.len()) {
+        let port = match src.get(host.len()) {
             Some(b':') => PortSpan::new(&src[host.len().saturating_add(1)..])
                 .map(Some)
                 .map_err(|e| Error::at(len.saturating_add(e.index().into()), e.kind())),
@@ -120,6 +128,7 @@ impl<'src> DomainSpan<'src> {
 /// assert_eq!(domain.host().to_str(), "localhost");
 /// assert_eq!(domain.port(), Some("5000"));
 /// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Domain<'src> {
     src: &'src str,
     /// the host part of the domain. It can be an IPv4 address, an IPv6 address,
@@ -144,8 +153,12 @@ impl<'src> Domain<'src> {
     /// parse a domain from the start of a string. Can consume only part of the source
     /// string if it reaches a valid stopping point, i.e. `/` or `@`
     pub fn new(src: &'src str) -> Result<Self, Error> {
+        Self::from_bytes(src.as_bytes())
+    }
+    /// Parse a domain from the start of some bytes without validating them as UTF-8.
+    pub fn from_bytes(src: &'src [u8]) -> Result<Self, Error> {
         let span = DomainSpan::new(src)?;
-        Ok(Self::from_span(span, &src[..span.len()]))
+        Ok(Self::from_span(span, crate::ascii::prefix(src, span.len())))
     }
     /// checks that the entire string is parsed
     pub fn from_exact_match(src: &'src str) -> Result<Self, Error> {
@@ -179,6 +192,12 @@ impl<'src> Domain<'src> {
     }
 }
 
+impl core::fmt::Debug for Domain<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Domain").field(&self.to_str()).finish()
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
@@ -24,8 +24,9 @@ const fn disambiguate_err(e: Error) -> Error {
 
 impl<'src> PortSpan<'src> {
     /// parse a port from the start of a string. Does NOT include the leading colon.
-    pub(super) fn new(src: &'src str) -> Result<Self, Error> {
-        let span = PortOrTagSpan::new(src, PortKind::Port).map_err(disambiguate_err)?;
+    pub(super) fn new(src: &'src [u8]) -> Result<Self, Error> {
+        let span = PortOrTagSpan::new(src, PortKind::Port, crate::tag::MAX_LEN.get())
+            .map_err(disambiguate_err)?;
         Ok(Self(span.span())) // ^ OK since we pre-narrowed to PortKind::Port
     }
 }
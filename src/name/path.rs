@@ -25,14 +25,14 @@
 // -- https://github.com/opencontainers/distribution-spec/commit/a73835700327bd1c037e33d0834c46ff98ac1286
 // -- https://github.com/opencontainers/distribution-spec/commit/efe2de09470d7f182d2fbd83ac4462fbdc462455
 
-use core::num::NonZeroU8;
+use core::num::NonZeroU16;
 
 use crate::{
     ambiguous::host_or_path::{HostOrPathSpan, Kind as PathKind},
-    err,
-    span::{impl_span_methods_on_tuple, Length, Lengthy, ShortLength},
+    err, name,
+    span::{impl_span_methods_on_tuple, nonzero, Length, Lengthy, LongLength, OptionallyZero},
 };
-type Error = err::Error<u8>;
+type Error = err::Error<u16>;
 
 /// adapt ambiguous error kinds into path-specific error kinds
 const fn map_error(e: Error) -> Error {
@@ -44,22 +44,32 @@ const fn map_error(e: Error) -> Error {
     };
     Error::at(e.index(), kind)
 }
-/// max length = `u8::MAX` = 255
+/// max length = `u16::MAX`, though parsing is bounded by the `max` passed to
+/// [`PathSpan::new`]. Each component is at most `u8::MAX` = 255 characters long.
+/// The span is always 16 bits wide, even under the default 255-character limit,
+/// so that one parser serves every [`Limits`](crate::Limits).
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
-pub(crate) struct PathSpan<'src>(ShortLength<'src>);
-impl_span_methods_on_tuple!(PathSpan, u8, NonZeroU8);
+pub(crate) struct PathSpan<'src>(LongLength<'src>);
+impl_span_methods_on_tuple!(PathSpan, u16, NonZeroU16);
 
-const ERR_PATH_TOO_LONG: Error = Error::at(u8::MAX, err::Kind::PathTooLong);
+/// the length of a path after adding `n` more characters, if it's within `max`
+fn within(len: u16, n: u16, max: u16) -> Result<u16, Error> {
+    len.checked_add(n)
+        .filter(|&len| len <= max)
+        .ok_or(Error::at(max, err::Kind::PathTooLong))
+}
 
 impl<'src> PathSpan<'src> {
-    fn parse_component(src: &'src str) -> Result<Self, Error> {
-        let ambiguous = HostOrPathSpan::new(src, PathKind::Path).map_err(map_error)?;
+    fn parse_component(src: &'src [u8]) -> Result<Self, Error> {
+        let ambiguous =
+            HostOrPathSpan::new(src, PathKind::Path).map_err(|e| map_error(e.into()))?;
         Self::try_from(ambiguous)
     }
-    pub(crate) fn parse_from_slash(src: &'src str) -> Result<Option<Self>, Error> {
-        let mut index: u8 = 0;
+    /// parse `/`-prefixed components until the total length would exceed `max`
+    pub(crate) fn parse_from_slash(src: &'src [u8], max: u16) -> Result<Option<Self>, Error> {
+        let mut index: u16 = 0;
         loop {
-            index = match src.as_bytes().get(index as usize) {
+            index = match src.get(index as usize) {
                 Some(b'/') => index.checked_add(1).ok_or(err::Kind::PathTooLong),
                 None | Some(b':') | Some(b'@') => break,
                 Some(_) => Err(err::Kind::PathInvalidChar),
@@ -70,33 +80,33 @@ impl<'src> PathSpan<'src> {
                     err::Kind::PathMissing => err::Kind::PathComponentInvalidEnd,
                     kind => kind,
                 };
-                e.index()
-                    .checked_add(index)
+                within(index, e.index(), max)
                     .map(|i| Error::at(i, kind))
-                    .unwrap_or(ERR_PATH_TOO_LONG)
+                    .unwrap_or_else(|too_long| too_long)
             })?;
-            index = index
-                .checked_add(component.short_len().into())
-                .ok_or(ERR_PATH_TOO_LONG)?;
+            index = within(index, component.short_len().into(), max)?;
         }
         Ok(Length::new(index).map(Self))
     }
-    pub(crate) fn extend(self, rest: &'src str) -> Result<Self, Error> {
-        let extension = Self::parse_from_slash(rest).map_err(|e| {
-            e.index()
-                .checked_add(self.short_len().into())
+    /// extend a path with any `/`-prefixed components in `rest`, keeping the
+    /// total length within `max`
+    pub(crate) fn extend(self, rest: &'src [u8], max: u16) -> Result<Self, Error> {
+        let len = within(self.short_len().into(), 0, max)?;
+        let extension = Self::parse_from_slash(rest, max.saturating_sub(len)).map_err(|e| {
+            within(len, e.index(), max)
                 .map(|i| Error::at(i, e.kind()))
-                .unwrap_or(ERR_PATH_TOO_LONG)
+                .unwrap_or_else(|too_long| too_long)
         })?;
-        let len = self
-            .short_len()
-            .checked_add(extension.map(|e| e.short_len().into()).unwrap_or(0))
-            .ok_or(ERR_PATH_TOO_LONG)?;
-        Ok(Self(Length::from_nonzero(len)))
+        let len = within(
+            len,
+            extension.map(|e| e.short_len().into()).unwrap_or(0),
+            max,
+        )?;
+        Ok(Self(Length::from_nonzero(nonzero!(u16, len)))) // safe since len >= self.len() > 0
     }
-    pub fn new(src: &'src str) -> Result<Self, Error> {
+    pub fn new(src: &'src [u8], max: u16) -> Result<Self, Error> {
         let first_component = Self::parse_component(src)?;
-        first_component.extend(&src[first_component.len()..])
+        first_component.extend(&src[first_component.len()..], max)
     }
 }
 
@@ -105,11 +115,13 @@ impl<'src> TryFrom<HostOrPathSpan<'src>> for PathSpan<'src> {
     fn try_from(ambiguous: HostOrPathSpan<'src>) -> Result<Self, Error> {
         ambiguous
             .narrow(PathKind::Path)
-            .map(|disambiguated| Self(Length::from_nonzero(disambiguated.short_len())))
+            .map(|disambiguated| Self(Length::from_nonzero(disambiguated.short_len().widen())))
+            .map_err(Error::from)
     }
 }
 
 /// Not including any leading `/`.
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Path<'src> {
     src: &'src str,
     span: PathSpan<'src>,
@@ -123,9 +135,15 @@ impl<'src> Path<'src> {
     }
     /// Parse a path string NOT starting with a leading `/`. Parsing continues until it
     /// reaches a `:`, `@`, or the end of the string.
-    pub fn new(src: &'src str) -> Result<Self, Error> {
-        let span = PathSpan::new(src)?;
-        Ok(Self::from_span(span, src))
+    pub fn new(src: &'src str) -> Result<Self, err::Error<u8>> {
+        Self::from_bytes(src.as_bytes())
+    }
+    /// Parse a path from the start of some bytes without validating them as UTF-8.
+    pub fn from_bytes(src: &'src [u8]) -> Result<Self, err::Error<u8>> {
+        // errors are reported at or before name::MAX_LEN = 255, so this never saturates
+        let span = PathSpan::new(src, name::MAX_LEN.into())
+            .map_err(|e| err::Error::at(u8::try_from(e.index()).unwrap_or(u8::MAX), e.kind()))?;
+        Ok(Self::from_span(span, crate::ascii::prefix(src, span.len())))
     }
     #[allow(missing_docs)]
     pub fn to_str(&self) -> &'src str {
@@ -136,6 +154,12 @@ impl<'src> Path<'src> {
         self.to_str().split('/')
     }
 }
+impl core::fmt::Debug for Path<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Path").field(&self.to_str()).finish()
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
@@ -144,12 +168,26 @@ mod tests {
     fn test_this() {
         // some strings in front of "/" must be paths since they include a underscores:
         let src = "not_a_host/path:tag";
-        let span = PathSpan::new(src).unwrap();
+        let span = PathSpan::new(src.as_bytes(), 255).unwrap();
         assert_eq!(span.span_of(src), "not_a_host/path");
 
         // watch out, though: host names are also valid paths
         let src = "test.com/path:tag";
-        let span = PathSpan::new(src).unwrap();
+        let span = PathSpan::new(src.as_bytes(), 255).unwrap();
         assert_eq!(span.span_of(src), "test.com/path");
     }
+    #[test]
+    fn path_stops_before_tag() {
+        // `Path` holds only the parsed path, not the rest of the source string
+        let path = Path::new("repo/name:tag").unwrap();
+        assert_eq!(path.to_str(), "repo/name");
+        assert_eq!(path.parts().last(), Some("name"));
+        assert_eq!(Path::new("repo@algo:abc").unwrap().to_str(), "repo");
+    }
+    #[test]
+    fn default_errors_are_u8_indexed() {
+        let long = [b'a'; 256];
+        let e: err::Error<u8> = Path::from_bytes(&long).err().unwrap();
+        assert_eq!((e.index(), e.kind()), (255, err::Kind::PathTooLong));
+    }
 }
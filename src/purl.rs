@@ -0,0 +1,597 @@
+//! # Package URLs
+//! Software bills of materials identify images with
+//! [package URLs](https://github.com/package-url/purl-spec) of the
+//! [`oci` and `docker` types](https://github.com/package-url/purl-spec/blob/master/PURL-TYPES.rst):
+//! ```text
+//! pkg:oci/<name>@<digest>?arch=<arch>&repository_url=<repository>&tag=<tag>
+//! pkg:docker/<namespace>/<name>@<tag or digest>?repository_url=<registry>
+//! ```
+//! An `oci` purl's name is the last component of the repository's path, and
+//! its `repository_url` qualifier is the whole repository. A `docker` purl's
+//! namespace and name are the repository's path, and its `repository_url` is
+//! the registry, defaulting to Docker Hub.
+//!
+//! [`Purl`] parses and prints purls following the spec's percent-encoding and
+//! normalization rules: the type and qualifier keys are lowercased, `oci` names
+//! are lowercased, empty qualifiers are dropped, and qualifiers are sorted by key.
+//! ```rust
+//! use container_image_dist_ref::{purl::{Purl, PurlType}, ImgRef};
+//! let hex = "09ca7e4eaa6e8ae9c7d261167129184883644d07dfba7cbfbc4c8a2e08360d5b";
+//! let src = format!("docker.io/library/debian:latest@sha256:{hex}");
+//! let img = ImgRef::new(&src).unwrap();
+//! let purl = Purl::from_ref(&img, PurlType::Oci).unwrap().with_qualifier("arch", "amd64");
+//! assert_eq!(
+//!     purl.to_string(),
+//!     format!("pkg:oci/debian@sha256:{hex}?arch=amd64&repository_url=docker.io/library/debian&tag=latest")
+//! );
+//! assert_eq!(purl.to_reference().unwrap(), src);
+//!
+//! let purl = Purl::new("pkg:docker/smartentry/debian@dc437cc87d10?repository_url=quay.io").unwrap();
+//! assert_eq!(purl.namespace(), Some("smartentry"));
+//! assert_eq!(purl.to_reference().unwrap(), "quay.io/smartentry/debian:dc437cc87d10");
+//! ```
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::{
+    domain::is_registry, err::Kind, span::Lengthy, CanonicalImgRef, Error, ImgRef, RefSpan,
+};
+
+/// The purl types that identify container images.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PurlType {
+    /// `pkg:oci`, which identifies an image by digest.
+    Oci,
+    /// `pkg:docker`, which identifies an image by tag or digest.
+    Docker,
+}
+
+impl PurlType {
+    /// The type as written in a purl.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Oci => "oci",
+            Self::Docker => "docker",
+        }
+    }
+    /// Look up a type by name, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Self::Oci, Self::Docker]
+            .into_iter()
+            .find(|t| t.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// Registries that a `docker` purl's default `repository_url` refers to.
+const DOCKER_HUB: [&str; 4] = [
+    "docker.io",
+    "index.docker.io",
+    "registry-1.docker.io",
+    "hub.docker.com",
+];
+
+fn index(i: usize) -> u16 {
+    u16::try_from(i).unwrap_or(u16::MAX)
+}
+
+/// percent-decode `src`, which starts at offset `start`
+fn decode(src: &str, start: usize) -> Result<String, Error> {
+    let bytes = src.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while let Some(&c) = bytes.get(i) {
+        if c == b'%' {
+            let hex = |j: usize| bytes.get(j).and_then(|&d| char::from(d).to_digit(16));
+            let (Some(high), Some(low)) = (hex(i + 1), hex(i + 2)) else {
+                return Error::at(index(start + i), Kind::PurlInvalidEncoding).into();
+            };
+            #[allow(clippy::cast_possible_truncation)]
+            decoded.push((high << 4 | low) as u8); // safe since both digits are < 16
+            i += 3;
+        } else {
+            decoded.push(c);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| Error::at(index(start), Kind::PurlInvalidEncoding))
+}
+
+/// percent-encode everything but unreserved characters, `:` (which the spec
+/// says must not be encoded) and, optionally, `/`
+fn encode(f: &mut fmt::Formatter<'_>, src: &str, keep_slash: bool) -> fmt::Result {
+    for c in src.bytes() {
+        match c {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'.' | b'-' | b'_' | b'~' | b':' => {
+                write!(f, "{}", char::from(c))?;
+            }
+            b'/' if keep_slash => f.write_str("/")?,
+            _ => write!(f, "%{c:02X}")?,
+        }
+    }
+    Ok(())
+}
+
+/// decode the non-empty `/`-separated segments of `src`, skipping `.` and `..`
+/// if `relative` is set
+fn segments(src: &str, start: usize, relative: bool) -> Result<Option<String>, Error> {
+    let mut joined = String::new();
+    let mut offset = start;
+    for segment in src.split('/') {
+        let skip = segment.is_empty() || (relative && matches!(segment, "." | ".."));
+        if !skip {
+            if !joined.is_empty() {
+                joined.push('/');
+            }
+            joined.push_str(&decode(segment, offset)?);
+        }
+        offset += segment.len() + 1;
+    }
+    Ok(Some(joined).filter(|j| !j.is_empty()))
+}
+
+/// whether `key` is a valid qualifier key: `[a-z.\-_][a-z0-9.\-_]*` once lowercased
+fn is_valid_key(key: &str) -> bool {
+    !key.starts_with(|c: char| c.is_ascii_digit())
+        && !key.is_empty()
+        && key
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, b'.' | b'-' | b'_'))
+}
+
+/// parse `&`-separated `key=value` pairs starting at offset `start`
+fn parse_qualifiers(src: &str, start: usize) -> Result<Vec<(String, String)>, Error> {
+    let mut qualifiers: Vec<(String, String)> = Vec::new();
+    let mut offset = start;
+    for pair in src.split('&') {
+        if !pair.is_empty() {
+            let invalid = Error::at(index(offset), Kind::PurlQualifierInvalid);
+            let (key, value) = pair.split_once('=').ok_or(invalid)?;
+            if !is_valid_key(key) {
+                return Err(invalid);
+            }
+            let key = key.to_ascii_lowercase();
+            if qualifiers.iter().any(|(k, _)| *k == key) {
+                return Err(invalid);
+            }
+            let value = decode(value, offset + key.len() + 1)?;
+            if !value.is_empty() {
+                qualifiers.push((key, value));
+            }
+        }
+        offset += pair.len() + 1;
+    }
+    qualifiers.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(qualifiers)
+}
+
+/// A `pkg:oci` or `pkg:docker` package URL.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Purl {
+    purl_type: PurlType,
+    namespace: Option<String>,
+    name: String,
+    version: Option<String>,
+    /// decoded, sorted by key, with no empty values
+    qualifiers: Vec<(String, String)>,
+    subpath: Option<String>,
+}
+
+impl Purl {
+    /// Parse a `pkg:oci` or `pkg:docker` purl. Errors are reported at offsets
+    /// within `src`.
+    pub fn new(src: &str) -> Result<Self, Error> {
+        let (rest, subpath) = match src.rfind('#') {
+            Some(i) => (&src[..i], segments(&src[i + 1..], i + 1, true)?),
+            None => (src, None),
+        };
+        let (rest, qualifiers) = match rest.rfind('?') {
+            Some(i) => (&rest[..i], parse_qualifiers(&rest[i + 1..], i + 1)?),
+            None => (rest, Vec::new()),
+        };
+
+        let scheme_end = rest
+            .find(':')
+            .filter(|&i| rest[..i].eq_ignore_ascii_case("pkg"))
+            .ok_or(Error::at(0, Kind::PurlSchemeMissing))?;
+        let mut start = scheme_end + 1;
+        while rest.as_bytes().get(start) == Some(&b'/') {
+            start += 1;
+        }
+        let type_end = rest[start..]
+            .find('/')
+            .map(|i| start + i)
+            .ok_or(Error::at(index(rest.len()), Kind::PurlNameMissing))?;
+        let purl_type = PurlType::from_name(&rest[start..type_end])
+            .ok_or(Error::at(index(start), Kind::PurlTypeUnsupported))?;
+
+        let start = type_end + 1;
+        let (body, version) = match rest[start..].rfind('@') {
+            Some(i) => {
+                let at = start + i;
+                (&rest[start..at], Some(decode(&rest[at + 1..], at + 1)?))
+            }
+            None => (&rest[start..], None),
+        };
+        let body = body.trim_end_matches('/');
+        let (namespace, name_start) = match body.rfind('/') {
+            Some(i) => (segments(&body[..i], start, false)?, start + i + 1),
+            None => (None, start),
+        };
+        let name = &rest[name_start..start + body.len()];
+        if name.is_empty() {
+            return Error::at(index(name_start), Kind::PurlNameMissing).into();
+        }
+        let mut name = decode(name, name_start)?;
+        if purl_type == PurlType::Oci {
+            if namespace.is_some() {
+                return Error::at(index(start), Kind::PurlNamespaceUnexpected).into();
+            }
+            name.make_ascii_lowercase();
+        }
+        Ok(Self {
+            purl_type,
+            namespace,
+            name,
+            version,
+            qualifiers,
+            subpath,
+        })
+    }
+    /// Describe an image reference as a purl. A `pkg:oci` purl requires the
+    /// reference to have a digest; errors are reported at the end of the reference.
+    pub fn from_ref(img: &ImgRef<'_>, purl_type: PurlType) -> Result<Self, Error> {
+        Self::from_span(img.src, &img.span, purl_type)
+    }
+    /// Describe a canonical image reference as a purl.
+    pub fn from_canonical(img: &CanonicalImgRef<'_>, purl_type: PurlType) -> Result<Self, Error> {
+        Self::from_span(img.src, &img.span.span, purl_type)
+    }
+    fn from_span(src: &str, span: &RefSpan<'_>, purl_type: PurlType) -> Result<Self, Error> {
+        let domain = span
+            .name
+            .domain
+            .map(|d| &src[..d.len()])
+            .filter(|d| is_registry(d));
+        let path = match domain {
+            Some(_) => &src[span.path_range()],
+            None => &src[span.name_range()],
+        };
+        let tag = span.tag_range().map(|r| &src[r]);
+        let digest = span.digest.map(|_| &src[span.digest_index()..]);
+        let (namespace, name) = match path.rsplit_once('/') {
+            Some((namespace, name)) => (Some(namespace), name),
+            None => (None, path),
+        };
+        let mut purl = match purl_type {
+            PurlType::Oci => {
+                let digest = digest.ok_or(Error::at(index(src.len()), Kind::PurlDigestMissing))?;
+                let repository_url = match (domain, namespace) {
+                    (Some(domain), _) => format!("{domain}/{path}"),
+                    (None, Some(_)) => format!("docker.io/{path}"),
+                    (None, None) => format!("docker.io/library/{path}"),
+                };
+                Self {
+                    purl_type,
+                    namespace: None,
+                    name: name.to_ascii_lowercase(),
+                    version: Some(digest.to_string()),
+                    qualifiers: Vec::new(),
+                    subpath: None,
+                }
+                .with_qualifier("repository_url", &repository_url)
+                .with_qualifier("tag", tag.unwrap_or(""))
+            }
+            PurlType::Docker => {
+                let registry = domain.filter(|d| !DOCKER_HUB.contains(d));
+                Self {
+                    purl_type,
+                    namespace: namespace.map(String::from),
+                    name: name.to_string(),
+                    version: digest.or(tag).map(String::from),
+                    qualifiers: Vec::new(),
+                    subpath: None,
+                }
+                .with_qualifier("repository_url", registry.unwrap_or(""))
+            }
+        };
+        if purl_type == PurlType::Docker && digest.is_some() {
+            purl = purl.with_qualifier("tag", tag.unwrap_or(""));
+        }
+        Ok(purl)
+    }
+    /// Set a qualifier such as `arch`, replacing any existing value. The key is
+    /// lowercased, and an empty value removes the qualifier.
+    pub fn with_qualifier(mut self, key: &str, value: &str) -> Self {
+        let key = key.to_ascii_lowercase();
+        self.qualifiers.retain(|(k, _)| *k != key);
+        if !value.is_empty() {
+            let i = self.qualifiers.partition_point(|(k, _)| *k < key);
+            self.qualifiers.insert(i, (key, value.to_string()));
+        }
+        self
+    }
+    /// Build the image reference the purl identifies. `pkg:docker` versions
+    /// containing a `:` are read as digests, and other versions as tags.
+    /// Errors from validating the reference are reported at offsets within it.
+    pub fn to_reference(&self) -> Result<String, Error> {
+        let repository_url = self.qualifier("repository_url").map(|url| {
+            url.trim_start_matches("https://")
+                .trim_start_matches("http://")
+                .trim_end_matches('/')
+        });
+        let mut reference = String::new();
+        let (tag, digest) = match self.purl_type {
+            PurlType::Oci => {
+                let digest = self
+                    .version
+                    .as_deref()
+                    .ok_or(Error::at(0, Kind::PurlDigestMissing))?;
+                match repository_url {
+                    Some(url) => {
+                        let last = url.rsplit('/').next().unwrap_or(url);
+                        if !last.eq_ignore_ascii_case(&self.name) {
+                            return Error::at(0, Kind::PurlQualifierInvalid).into();
+                        }
+                        reference.push_str(url);
+                    }
+                    None => reference.push_str(&self.name),
+                }
+                (self.qualifier("tag"), Some(digest))
+            }
+            PurlType::Docker => {
+                if let Some(registry) = repository_url.filter(|r| !DOCKER_HUB.contains(r)) {
+                    reference.push_str(registry);
+                    reference.push('/');
+                }
+                if let Some(namespace) = &self.namespace {
+                    reference.push_str(namespace);
+                    reference.push('/');
+                }
+                reference.push_str(&self.name);
+                match self.version.as_deref() {
+                    Some(v) if v.contains(':') => (self.qualifier("tag"), Some(v)),
+                    Some(v) => (Some(v), None),
+                    None => (self.qualifier("tag"), None),
+                }
+            }
+        };
+        if let Some(tag) = tag {
+            reference.push(':');
+            reference.push_str(tag);
+        }
+        if let Some(digest) = digest {
+            reference.push('@');
+            reference.push_str(digest);
+        }
+        ImgRef::new(&reference)?;
+        Ok(reference)
+    }
+    /// Whether this is a `pkg:oci` or `pkg:docker` purl.
+    pub const fn purl_type(&self) -> PurlType {
+        self.purl_type
+    }
+    /// The decoded namespace, with segments separated by `/`.
+    pub fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+    /// The decoded name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// The decoded version: a digest, or for `pkg:docker`, possibly a tag.
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+    /// The decoded value of a qualifier.
+    pub fn qualifier(&self, key: &str) -> Option<&str> {
+        self.qualifiers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+    /// The decoded qualifiers, sorted by key.
+    pub fn qualifiers(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.qualifiers
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+    /// The decoded subpath, with segments separated by `/`.
+    pub fn subpath(&self) -> Option<&str> {
+        self.subpath.as_deref()
+    }
+}
+
+impl fmt::Display for Purl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pkg:{}/", self.purl_type.name())?;
+        if let Some(namespace) = &self.namespace {
+            for segment in namespace.split('/') {
+                encode(f, segment, false)?;
+                f.write_str("/")?;
+            }
+        }
+        encode(f, &self.name, false)?;
+        if let Some(version) = &self.version {
+            f.write_str("@")?;
+            encode(f, version, false)?;
+        }
+        for (i, (key, value)) in self.qualifiers.iter().enumerate() {
+            f.write_str(if i == 0 { "?" } else { "&" })?;
+            write!(f, "{key}=")?;
+            encode(f, value, true)?;
+        }
+        if let Some(subpath) = &self.subpath {
+            f.write_str("#")?;
+            encode(f, subpath, true)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    const HEX: &str = "853ff93762a06ddbf722c4ebe9ddd66d8f63ddaea97f521c3ecc20da7c976020";
+
+    fn canonical(src: &str) -> String {
+        Purl::new(src).unwrap().to_string()
+    }
+    fn err(src: &str) -> (u16, Kind) {
+        let e = Purl::new(src).err().unwrap();
+        (e.index(), e.kind())
+    }
+    fn purl(src: &str, purl_type: PurlType) -> Purl {
+        Purl::from_ref(&ImgRef::new(src).unwrap(), purl_type).unwrap()
+    }
+
+    #[test]
+    fn spec_fixtures() {
+        // purls and canonical purls from the purl spec's test-suite-data.json
+        for (src, expected) in [
+            (
+                "pkg:docker/cassandra@sha256:244fd47e07d1004f0aed9c",
+                "pkg:docker/cassandra@sha256:244fd47e07d1004f0aed9c",
+            ),
+            (
+                "pkg:docker/customer/dockerimage@sha256:244fd47e07d1004f0aed9c?repository_url=gcr.io",
+                "pkg:docker/customer/dockerimage@sha256:244fd47e07d1004f0aed9c?repository_url=gcr.io",
+            ),
+            ("pkg:docker/cassandra@latest", "pkg:docker/cassandra@latest"),
+            (
+                "pkg:docker/smartentry/debian@dc437cc87d10",
+                "pkg:docker/smartentry/debian@dc437cc87d10",
+            ),
+            (
+                "pkg:oci/debian@sha256%3A244fd47e07d10?repository_url=docker.io/library/debian&arch=amd64&tag=latest",
+                "pkg:oci/debian@sha256:244fd47e07d10?arch=amd64&repository_url=docker.io/library/debian&tag=latest",
+            ),
+            (
+                "pkg:oci/debian@sha256%3A244fd47e07d10?repository_url=ghcr.io/debian&tag=bullseye",
+                "pkg:oci/debian@sha256:244fd47e07d10?repository_url=ghcr.io/debian&tag=bullseye",
+            ),
+            (
+                "pkg:oci/static@sha256%3A244fd47e07d10?repository_url=gcr.io/distroless/static&tag=latest",
+                "pkg:oci/static@sha256:244fd47e07d10?repository_url=gcr.io/distroless/static&tag=latest",
+            ),
+            (
+                "pkg:oci/hello-wasm@sha256%3A244fd47e07d10?tag=v1",
+                "pkg:oci/hello-wasm@sha256:244fd47e07d10?tag=v1",
+            ),
+        ] {
+            assert_eq!(canonical(src), expected, "{src}");
+        }
+    }
+
+    #[test]
+    fn normalization() {
+        assert_eq!(
+            canonical("PKG:OCI/Debian@sha256%3aabc?Tag=x&Arch=&repository_url=a%2fb"),
+            "pkg:oci/debian@sha256:abc?repository_url=a/b&tag=x"
+        );
+        // docker names and namespaces keep their case
+        assert_eq!(canonical("pkg:/docker//a//B/C/"), "pkg:docker/a/B/C");
+        let purl = Purl::new("pkg:docker/x@1?repository_url=localhost%3A5000#./a/../b").unwrap();
+        assert_eq!(purl.qualifier("repository_url"), Some("localhost:5000"));
+        assert_eq!(purl.subpath(), Some("a/b"));
+        assert_eq!(
+            purl.to_string(),
+            "pkg:docker/x@1?repository_url=localhost:5000#a/b"
+        );
+    }
+
+    #[test]
+    fn invalid_purls() {
+        assert_eq!(err("docker/x"), (0, Kind::PurlSchemeMissing));
+        assert_eq!(err("pkg:npm/x"), (4, Kind::PurlTypeUnsupported));
+        assert_eq!(err("pkg:docker"), (10, Kind::PurlNameMissing));
+        assert_eq!(err("pkg:docker/@1"), (11, Kind::PurlNameMissing));
+        assert_eq!(err("pkg:docker/x%2"), (12, Kind::PurlInvalidEncoding));
+        assert_eq!(err("pkg:docker/x%+1"), (12, Kind::PurlInvalidEncoding));
+        assert_eq!(err("pkg:docker/x%ff"), (11, Kind::PurlInvalidEncoding));
+        assert_eq!(err("pkg:docker/x?a=1&b"), (17, Kind::PurlQualifierInvalid));
+        assert_eq!(err("pkg:docker/x?1a=b"), (13, Kind::PurlQualifierInvalid));
+        assert_eq!(
+            err("pkg:docker/x?a=1&A=2"),
+            (17, Kind::PurlQualifierInvalid)
+        );
+        assert_eq!(err("pkg:oci/ns/x@a"), (8, Kind::PurlNamespaceUnexpected));
+    }
+
+    #[test]
+    fn references_round_trip() {
+        for (src, purl_type, expected) in [
+            (
+                format!("ghcr.io/a/b:v1@sha256:{HEX}"),
+                PurlType::Oci,
+                format!("pkg:oci/b@sha256:{HEX}?repository_url=ghcr.io/a/b&tag=v1"),
+            ),
+            (
+                format!("localhost:5000/b@sha256:{HEX}"),
+                PurlType::Oci,
+                format!("pkg:oci/b@sha256:{HEX}?repository_url=localhost:5000/b"),
+            ),
+            (
+                format!("quay.io/a/b:v1@sha256:{HEX}"),
+                PurlType::Docker,
+                format!("pkg:docker/a/b@sha256:{HEX}?repository_url=quay.io&tag=v1"),
+            ),
+            (
+                "library/debian:bookworm".to_string(),
+                PurlType::Docker,
+                "pkg:docker/library/debian@bookworm".to_string(),
+            ),
+        ] {
+            let purl = purl(&src, purl_type);
+            assert_eq!(purl.to_string(), expected);
+            let parsed = Purl::new(&expected).unwrap();
+            assert_eq!(parsed, purl);
+            assert_eq!(parsed.to_reference().unwrap(), src);
+        }
+
+        // docker hub is the default registry, and is filled in for oci purls
+        let hub = purl("docker.io/library/debian", PurlType::Docker);
+        assert_eq!(hub.to_string(), "pkg:docker/library/debian");
+        let short = format!("debian@sha256:{HEX}");
+        assert_eq!(
+            purl(&short, PurlType::Oci).qualifier("repository_url"),
+            Some("docker.io/library/debian")
+        );
+        // a first component that isn't a hostname is part of the path
+        let namespaced = format!("library/debian@sha256:{HEX}");
+        assert_eq!(
+            purl(&namespaced, PurlType::Oci).qualifier("repository_url"),
+            Some("docker.io/library/debian")
+        );
+
+        let canonical = format!("host.com/img@sha256:{HEX}");
+        let canonical = CanonicalImgRef::new(&canonical).unwrap();
+        let from_canonical = Purl::from_canonical(&canonical, PurlType::Oci).unwrap();
+        assert_eq!(from_canonical.name(), "img");
+    }
+
+    #[test]
+    fn conversion_errors() {
+        let img = ImgRef::new("host.com/x:tag").unwrap();
+        let e = Purl::from_ref(&img, PurlType::Oci).err().unwrap();
+        assert_eq!((e.index(), e.kind()), (14, Kind::PurlDigestMissing));
+        let e = Purl::new("pkg:oci/x")
+            .unwrap()
+            .to_reference()
+            .err()
+            .unwrap();
+        assert_eq!(e.kind(), Kind::PurlDigestMissing);
+        let mismatched = Purl::new("pkg:oci/x@a:b?repository_url=host.com/y").unwrap();
+        let e = mismatched.to_reference().err().unwrap();
+        assert_eq!(e.kind(), Kind::PurlQualifierInvalid);
+        // short digests aren't valid references
+        let short = Purl::new("pkg:oci/x@sha256%3Aabc").unwrap();
+        assert!(short.to_reference().is_err());
+    }
+}
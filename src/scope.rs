@@ -0,0 +1,305 @@
+//! # Token authentication scopes
+//! Registries using [token authentication](https://distribution.github.io/distribution/spec/auth/scope/)
+//! grant access to resources named by scopes:
+//! ```ebnf
+//! scope                ::= resource-scope (" " resource-scope)*
+//! resource-scope       ::= resource-type ":" resource-name ":" action ("," action)*
+//! resource-type        ::= resource-type-value ("(" resource-type-value ")")?
+//! resource-type-value  ::= [a-z0-9]+
+//! action               ::= [a-z*]*
+//! ```
+//! The resource name must match the [`Path`] grammar, so it can't include a
+//! registry's port.
+//! ```rust
+//! use container_image_dist_ref::{scope::{required_scope, Action, Scope}, ImgRef};
+//! let scope = Scope::new("repository:library/nginx:pull,push repository(plugin):x:pull").unwrap();
+//! let resources: Vec<_> = scope.resources().collect();
+//! assert_eq!(resources[0].name().to_str(), "library/nginx");
+//! assert!(resources[0].allows(Action::Push));
+//! assert_eq!(resources[1].resource_class(), Some("plugin"));
+//!
+//! let img = ImgRef::new("nginx:1.25").unwrap();
+//! assert_eq!(required_scope(&img, Action::Push).to_string(), "repository:library/nginx:pull,push");
+//! ```
+
+use core::fmt;
+
+use crate::{endpoint::Endpoint, err::Kind, path::Path, Error, ImgRef};
+
+fn index(i: usize) -> u16 {
+    u16::try_from(i).unwrap_or(u16::MAX)
+}
+
+/// Something a scope can allow doing to a resource.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    /// `pull`
+    Pull,
+    /// `push`
+    Push,
+    /// `delete`
+    Delete,
+    /// `*`, which allows every action.
+    All,
+}
+
+impl Action {
+    /// The action as written in a scope.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Pull => "pull",
+            Self::Push => "push",
+            Self::Delete => "delete",
+            Self::All => "*",
+        }
+    }
+    /// Look up an action by name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Self::Pull, Self::Push, Self::Delete, Self::All]
+            .into_iter()
+            .find(|a| a.name() == name)
+    }
+}
+
+/// find the first character of `src` outside `[a-z0-9]+`, or the end of an
+/// empty `src`
+fn invalid_type_value(src: &str) -> Option<usize> {
+    src.bytes()
+        .position(|c| !matches!(c, b'a'..=b'z' | b'0'..=b'9'))
+        .or(src.is_empty().then_some(0))
+}
+
+/// Access to one resource, such as `repository:library/nginx:pull,push`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ResourceScope<'src> {
+    src: &'src str,
+    resource_type: &'src str,
+    resource_class: Option<&'src str>,
+    name: Path<'src>,
+    actions: &'src str,
+}
+
+impl<'src> ResourceScope<'src> {
+    /// Parse a single resource scope.
+    pub fn new(src: &'src str) -> Result<Self, Error> {
+        let missing = Error::at(index(src.len()), Kind::ScopeActionsMissing);
+        let first = src.find(':').ok_or(missing)?;
+        let last = src.rfind(':').filter(|&last| last > first).ok_or(missing)?;
+
+        let full_type = &src[..first];
+        let (resource_type, resource_class) = match full_type.split_once('(') {
+            Some((resource_type, rest)) => {
+                let class = rest
+                    .strip_suffix(')')
+                    .ok_or(Error::at(index(first), Kind::ScopeResourceTypeInvalid))?;
+                (resource_type, Some(class))
+            }
+            None => (full_type, None),
+        };
+        if let Some(i) = invalid_type_value(resource_type) {
+            return Error::at(index(i), Kind::ScopeResourceTypeInvalid).into();
+        }
+        if let Some(class) = resource_class {
+            if let Some(i) = invalid_type_value(class) {
+                let start = resource_type.len() + 1;
+                return Error::at(index(start + i), Kind::ScopeResourceTypeInvalid).into();
+            }
+        }
+
+        let start = first + 1;
+        let name = &src[start..last];
+        let shift = |e: Error| Error::at(e.index().saturating_add(index(start)), e.kind());
+        let name = Path::new(name).map_err(Error::from).map_err(shift)?;
+        if start + name.to_str().len() != last {
+            let at = start + name.to_str().len();
+            return Error::at(index(at), Kind::PathInvalidChar).into();
+        }
+
+        let actions = &src[last + 1..];
+        if let Some(i) = actions
+            .bytes()
+            .position(|c| !matches!(c, b'a'..=b'z' | b'*' | b','))
+        {
+            return Error::at(index(last + 1 + i), Kind::ScopeActionInvalid).into();
+        }
+        Ok(Self {
+            src,
+            resource_type,
+            resource_class,
+            name,
+            actions,
+        })
+    }
+    /// The original scope string.
+    pub const fn to_str(&self) -> &'src str {
+        self.src
+    }
+    /// The type of resource, such as `repository` or `registry`.
+    pub const fn resource_type(&self) -> &'src str {
+        self.resource_type
+    }
+    /// The resource class in parentheses after the type, such as `plugin`.
+    pub const fn resource_class(&self) -> Option<&'src str> {
+        self.resource_class
+    }
+    /// The resource's name, such as a repository's path.
+    pub const fn name(&self) -> &Path<'src> {
+        &self.name
+    }
+    /// The names of the allowed actions, in order.
+    pub fn actions(&self) -> impl Iterator<Item = &'src str> {
+        self.actions.split(',').filter(|a| !a.is_empty())
+    }
+    /// Whether the scope allows an action, either by name or with `*`.
+    pub fn allows(&self, action: Action) -> bool {
+        self.actions()
+            .any(|a| a == action.name() || a == Action::All.name())
+    }
+}
+
+impl fmt::Display for ResourceScope<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.src)
+    }
+}
+
+/// A space-separated list of [`ResourceScope`]s.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Scope<'src>(&'src str);
+
+impl<'src> Scope<'src> {
+    /// Parse a scope, reporting errors at offsets within the whole string.
+    pub fn new(src: &'src str) -> Result<Self, Error> {
+        let mut start = 0;
+        for resource in src.split(' ') {
+            ResourceScope::new(resource)
+                .map_err(|e| Error::at(e.index().saturating_add(index(start)), e.kind()))?;
+            start += resource.len() + 1;
+        }
+        Ok(Self(src))
+    }
+    /// The original scope string.
+    pub const fn to_str(&self) -> &'src str {
+        self.0
+    }
+    /// The scope's resources, in order.
+    pub fn resources(&self) -> impl Iterator<Item = ResourceScope<'src>> {
+        self.0
+            .split(' ')
+            .filter_map(|resource| ResourceScope::new(resource).ok())
+    }
+}
+
+impl fmt::Display for Scope<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+/// The repository scope needed to do something to an image; see [`required_scope`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RequiredScope<'src> {
+    endpoint: Endpoint<'src>,
+    action: Action,
+}
+
+/// The scope a client requests to do `action` to the repository of `img`. The
+/// repository is named as the registry API names it, so official Docker Hub
+/// images get their `library/` namespace. Pushing also requests `pull`, as
+/// Docker clients do, since pushes check which blobs already exist.
+pub fn required_scope<'src>(img: &ImgRef<'src>, action: Action) -> RequiredScope<'src> {
+    RequiredScope {
+        endpoint: Endpoint::from_ref(img),
+        action,
+    }
+}
+
+impl fmt::Display for RequiredScope<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("repository:")?;
+        self.endpoint.write_name(f)?;
+        match self.action {
+            Action::Push => f.write_str(":pull,push"),
+            action => write!(f, ":{}", action.name()),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    extern crate alloc;
+    use alloc::{format, string::ToString, vec::Vec};
+
+    use super::*;
+
+    fn err(src: &str) -> (u16, Kind) {
+        let e = Scope::new(src).err().unwrap();
+        (e.index(), e.kind())
+    }
+
+    #[test]
+    fn resource_scopes() {
+        let scope = ResourceScope::new("repository:samalba/my-app:pull,push").unwrap();
+        assert_eq!(scope.resource_type(), "repository");
+        assert_eq!(scope.resource_class(), None);
+        assert_eq!(scope.name().to_str(), "samalba/my-app");
+        assert_eq!(scope.actions().collect::<Vec<_>>(), ["pull", "push"]);
+        assert!(scope.allows(Action::Pull) && !scope.allows(Action::Delete));
+
+        let catalog = ResourceScope::new("registry:catalog:*").unwrap();
+        assert!(catalog.allows(Action::Delete));
+        let plugin = ResourceScope::new("repository(plugin):x:pull").unwrap();
+        assert_eq!(plugin.resource_class(), Some("plugin"));
+        assert_eq!(plugin.to_string(), "repository(plugin):x:pull");
+        let none = ResourceScope::new("repository:x:").unwrap();
+        assert_eq!(none.actions().count(), 0);
+
+        let copy = plugin;
+        assert_eq!(copy, plugin);
+        assert_ne!(plugin, none);
+        assert!(format!("{plugin:?}").contains(r#"name: Path("x")"#));
+    }
+
+    #[test]
+    fn invalid_scopes() {
+        assert_eq!(err("repository"), (10, Kind::ScopeActionsMissing));
+        assert_eq!(err("repository:x"), (12, Kind::ScopeActionsMissing));
+        assert_eq!(err(":x:pull"), (0, Kind::ScopeResourceTypeInvalid));
+        assert_eq!(err("Repo:x:pull"), (0, Kind::ScopeResourceTypeInvalid));
+        assert_eq!(err("repo(:x:pull"), (5, Kind::ScopeResourceTypeInvalid));
+        assert_eq!(err("repo(P):x:pull"), (5, Kind::ScopeResourceTypeInvalid));
+        assert_eq!(err("repo::pull"), (5, Kind::PathMissing));
+        assert_eq!(err("repo:X:pull"), (5, Kind::PathInvalidChar));
+        assert_eq!(
+            err("repo:localhost:5000/x:pull"),
+            (14, Kind::PathInvalidChar)
+        );
+        assert_eq!(err("repo:x:Pull"), (7, Kind::ScopeActionInvalid));
+        assert_eq!(
+            err("repo:x:pull repo:y:p-sh"),
+            (20, Kind::ScopeActionInvalid)
+        );
+    }
+
+    #[test]
+    fn required_scopes() {
+        let scope = |src, action| required_scope(&ImgRef::new(src).unwrap(), action).to_string();
+        assert_eq!(
+            scope("nginx", Action::Pull),
+            "repository:library/nginx:pull"
+        );
+        assert_eq!(
+            scope("ghcr.io/org/app:v1", Action::Push),
+            "repository:org/app:pull,push"
+        );
+        assert_eq!(
+            scope("localhost:5000/app", Action::Delete),
+            "repository:app:delete"
+        );
+        // the result is a valid scope
+        let pushed = scope("docker.io/x/y", Action::Push);
+        let parsed = Scope::new(&pushed).unwrap();
+        assert_eq!(parsed.resources().count(), 1);
+    }
+}
@@ -0,0 +1,230 @@
+//! # Character-class scanning
+//! Long runs of hex digits (in the encoded section of a digest) or lowercase
+//! alphanumerics (in hosts and paths) never change the state of their parsers, so
+//! they can be skipped in bulk. This module finds the length of such runs 16 or
+//! 32 bytes at a time using SSE2/AVX2 on `x86_64` and NEON on `aarch64`, falling
+//! back to a byte-by-byte loop elsewhere and for the tail of each run.
+//!
+//! AVX2 is only used when it's enabled at compile time (e.g. with
+//! `RUSTFLAGS="-C target-cpu=native"`) since runtime feature detection requires `std`.
+
+/// A set of ascii bytes: the digits `0-9` plus one range of lowercase letters.
+/// Setting `fold` to `0x20` also matches the corresponding uppercase letters.
+#[derive(Clone, Copy)]
+pub(crate) struct Class {
+    fold: u8,
+    lo: u8,
+    hi: u8,
+}
+
+impl Class {
+    /// `[0-9a-fA-F]`
+    pub(crate) const HEX: Self = Self {
+        fold: 0x20,
+        lo: b'a',
+        hi: b'f',
+    };
+    /// `[0-9a-z]`
+    pub(crate) const LOWER_ALNUM: Self = Self {
+        fold: 0,
+        lo: b'a',
+        hi: b'z',
+    };
+    #[inline(always)]
+    const fn contains(self, c: u8) -> bool {
+        c.is_ascii_digit() || (c | self.fold).wrapping_sub(self.lo) <= self.hi.wrapping_sub(self.lo)
+    }
+}
+
+/// The shortest input worth scanning with `prefix_len`: anything shorter never
+/// reaches the vectorized path.
+pub(crate) const MIN_LEN: usize = 16;
+
+/// The length of the longest prefix of `src` made up of bytes in `class`.
+#[inline]
+pub(crate) fn prefix_len(src: &[u8], class: Class) -> usize {
+    let start = arch::prefix_len(src, class);
+    start
+        + src[start..]
+            .iter()
+            .position(|&c| !class.contains(c))
+            .unwrap_or(src.len() - start)
+}
+
+/// reinterpret a byte as the `i8` lane type the x86 intrinsics expect
+#[cfg(target_arch = "x86_64")]
+#[inline(always)]
+const fn splat(b: u8) -> i8 {
+    i8::from_ne_bytes([b])
+}
+
+#[cfg(target_arch = "x86_64")]
+mod arch {
+    use core::arch::x86_64::{
+        __m128i, _mm_cmpeq_epi8, _mm_loadu_si128, _mm_min_epu8, _mm_movemask_epi8, _mm_or_si128,
+        _mm_set1_epi8, _mm_sub_epi8,
+    };
+    #[cfg(target_feature = "avx2")]
+    use core::arch::x86_64::{
+        __m256i, _mm256_cmpeq_epi8, _mm256_loadu_si256, _mm256_min_epu8, _mm256_movemask_epi8,
+        _mm256_or_si256, _mm256_set1_epi8, _mm256_sub_epi8,
+    };
+
+    use super::{splat, Class};
+
+    /// Returns the index of the first byte outside of `class` if it's within a
+    /// full 16-byte chunk, otherwise the start of the partial chunk at the end.
+    #[inline]
+    pub(super) fn prefix_len(src: &[u8], class: Class) -> usize {
+        #[cfg(target_feature = "avx2")]
+        let start = {
+            let mut i = 0;
+            while i + 32 <= src.len() {
+                // SAFETY: the load reads src[i..i + 32], which is in bounds
+                let mask = unsafe { avx2_mask(src.as_ptr().add(i).cast(), class) };
+                if mask != u32::MAX {
+                    return i + (!mask).trailing_zeros() as usize;
+                }
+                i += 32;
+            }
+            i
+        };
+        #[cfg(not(target_feature = "avx2"))]
+        let start = 0;
+
+        let mut i = start;
+        while i + 16 <= src.len() {
+            // SAFETY: the load reads src[i..i + 16], which is in bounds. SSE2 is
+            // part of the x86_64 baseline.
+            let mask = unsafe { sse2_mask(src.as_ptr().add(i).cast(), class) };
+            if mask != 0xFFFF {
+                return i + (!mask).trailing_zeros() as usize;
+            }
+            i += 16;
+        }
+        i
+    }
+
+    /// a 16-bit mask with bit `n` set iff the `n`th byte is in `class`
+    #[inline(always)]
+    unsafe fn sse2_mask(chunk: *const __m128i, class: Class) -> u32 {
+        let chunk = _mm_loadu_si128(chunk);
+        // (x - lo) <= (hi - lo) as unsigned bytes, i.e. min(x - lo, hi - lo) == x - lo
+        let in_range = |x: __m128i, lo: u8, hi: u8| {
+            let offset = _mm_sub_epi8(x, _mm_set1_epi8(splat(lo)));
+            _mm_cmpeq_epi8(
+                _mm_min_epu8(offset, _mm_set1_epi8(splat(hi.wrapping_sub(lo)))),
+                offset,
+            )
+        };
+        let digit = in_range(chunk, b'0', b'9');
+        let folded = _mm_or_si128(chunk, _mm_set1_epi8(splat(class.fold)));
+        let letter = in_range(folded, class.lo, class.hi);
+        _mm_movemask_epi8(_mm_or_si128(digit, letter)) as u32
+    }
+
+    /// a 32-bit mask with bit `n` set iff the `n`th byte is in `class`
+    #[cfg(target_feature = "avx2")]
+    #[inline(always)]
+    unsafe fn avx2_mask(chunk: *const __m256i, class: Class) -> u32 {
+        let chunk = _mm256_loadu_si256(chunk);
+        let in_range = |x: __m256i, lo: u8, hi: u8| {
+            let offset = _mm256_sub_epi8(x, _mm256_set1_epi8(splat(lo)));
+            _mm256_cmpeq_epi8(
+                _mm256_min_epu8(offset, _mm256_set1_epi8(splat(hi.wrapping_sub(lo)))),
+                offset,
+            )
+        };
+        let digit = in_range(chunk, b'0', b'9');
+        let folded = _mm256_or_si256(chunk, _mm256_set1_epi8(splat(class.fold)));
+        let letter = in_range(folded, class.lo, class.hi);
+        _mm256_movemask_epi8(_mm256_or_si256(digit, letter)) as u32
+    }
+}
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+mod arch {
+    use core::arch::aarch64::{vcleq_u8, vdupq_n_u8, vld1q_u8, vminvq_u8, vorrq_u8, vsubq_u8};
+
+    use super::Class;
+
+    /// Returns the start of the first 16-byte chunk containing a byte outside of
+    /// `class`, or the start of the partial chunk at the end. NEON has no
+    /// movemask, so the caller finds the exact index within the chunk.
+    #[inline]
+    pub(super) fn prefix_len(src: &[u8], class: Class) -> usize {
+        let mut i = 0;
+        while i + 16 <= src.len() {
+            // SAFETY: the load reads src[i..i + 16], which is in bounds. NEON is
+            // enabled at compile time.
+            let all_in_class = unsafe {
+                let chunk = vld1q_u8(src.as_ptr().add(i));
+                let digit = vcleq_u8(vsubq_u8(chunk, vdupq_n_u8(b'0')), vdupq_n_u8(9));
+                let folded = vorrq_u8(chunk, vdupq_n_u8(class.fold));
+                let letter = vcleq_u8(
+                    vsubq_u8(folded, vdupq_n_u8(class.lo)),
+                    vdupq_n_u8(class.hi.wrapping_sub(class.lo)),
+                );
+                vminvq_u8(vorrq_u8(digit, letter)) == u8::MAX
+            };
+            if !all_in_class {
+                break;
+            }
+            i += 16;
+        }
+        i
+    }
+}
+
+#[cfg(not(any(
+    target_arch = "x86_64",
+    all(target_arch = "aarch64", target_feature = "neon")
+)))]
+mod arch {
+    use super::Class;
+
+    /// no vector instructions available: leave everything to the scalar loop
+    #[inline(always)]
+    pub(super) const fn prefix_len(_src: &[u8], _class: Class) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scalar_prefix_len(src: &[u8], class: Class) -> usize {
+        src.iter().take_while(|&&c| class.contains(c)).count()
+    }
+
+    #[test]
+    fn class_membership() {
+        for c in 0..=u8::MAX {
+            assert_eq!(Class::HEX.contains(c), c.is_ascii_hexdigit(), "{c:#x}");
+            assert_eq!(
+                Class::LOWER_ALNUM.contains(c),
+                c.is_ascii_digit() || c.is_ascii_lowercase(),
+                "{c:#x}"
+            );
+        }
+    }
+
+    #[test]
+    fn matches_scalar_at_every_offset() {
+        let mut src = [b'a'; 80];
+        for class in [Class::HEX, Class::LOWER_ALNUM] {
+            for stop in 0..src.len() {
+                for c in 0..=u8::MAX {
+                    src[stop] = c;
+                    assert_eq!(
+                        prefix_len(&src, class),
+                        scalar_prefix_len(&src, class),
+                        "stop={stop} c={c:#x}"
+                    );
+                }
+                src[stop] = b'a';
+            }
+        }
+    }
+}
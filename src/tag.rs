@@ -36,21 +36,18 @@ type Error = err::Error<u8>;
 pub(crate) struct TagSpan<'src>(ShortLength<'src>);
 impl_span_methods_on_tuple!(TagSpan, u8, NonZeroU8);
 
-impl<'src> TryFrom<PortOrTagSpan<'src>> for TagSpan<'src> {
-    type Error = Error;
-    fn try_from(ambiguous: PortOrTagSpan<'src>) -> Result<Self, Error> {
-        if ambiguous.short_len() <= MAX_LEN {
+impl<'src> TagSpan<'src> {
+    /// narrow an ambiguous port-or-tag into a tag of at most `max` characters
+    pub(crate) fn from_ambiguous(ambiguous: PortOrTagSpan<'src>, max: u8) -> Result<Self, Error> {
+        if ambiguous.short_len().upcast() <= max {
             Ok(Self(ambiguous.span()))
         } else {
-            Err(Error::at(MAX_LEN.upcast(), err::Kind::TagTooLong))
+            Err(Error::at(max, err::Kind::TagTooLong))
         }
     }
-}
-
-impl TagSpan<'_> {
     /// can match an empty span if the first character in `src` is a `/` or `@`
-    pub(crate) fn new(src: &str) -> Result<Self, Error> {
-        let span = PortOrTagSpan::new(src, TagKind::Tag).map_err(|e| {
+    pub(crate) fn new(src: &'src [u8], max: u8) -> Result<Self, Error> {
+        let span = PortOrTagSpan::new(src, TagKind::Tag, max).map_err(|e| {
             let kind = match e.kind() {
                 err::Kind::PortOrTagInvalidChar => err::Kind::TagInvalidChar,
                 err::Kind::PortOrTagMissing => err::Kind::TagMissing,
@@ -59,7 +56,7 @@ impl TagSpan<'_> {
             };
             Error::at(e.index(), kind)
         })?;
-        Ok(Self(span.span())) // safe since we parsed in TagKind::Tag mode
+        Self::from_ambiguous(span, max)
     }
 }
 
@@ -72,8 +69,12 @@ impl<'src> Tag<'src> {
     /// Parsing may not consume the entire string if it encounters a valid stopping point,
     /// i.e. '@'.
     pub fn new(src: &'src str) -> Result<Self, Error> {
-        let span = TagSpan::new(src)?;
-        Ok(Self(span.span_of(src)))
+        Self::from_bytes(src.as_bytes())
+    }
+    /// Parse a tag from the start of some bytes without validating them as UTF-8.
+    pub fn from_bytes(src: &'src [u8]) -> Result<Self, Error> {
+        let span = TagSpan::new(src, MAX_LEN.get())?;
+        Ok(Self(crate::ascii::prefix(src, span.len())))
     }
     #[allow(missing_docs)]
     #[inline]
@@ -0,0 +1,470 @@
+//! # Transport-prefixed references
+//! Tools built on [`containers/image`](https://github.com/containers/image),
+//! such as skopeo, podman, and buildah, prefix references with a transport
+//! naming where the image lives:
+//!
+//! | transport        | payload                                        |
+//! |------------------|------------------------------------------------|
+//! | `docker://`      | a reference to an image in a registry          |
+//! | `docker-daemon:` | a reference, or a `sha256:` image ID           |
+//! | `docker-archive:`| a path, then optionally `:` and a tagged reference or `@<index>` |
+//! | `oci:`           | a path, then optionally `:` and an image name or `@<index>` |
+//! | `oci-archive:`   | a path, then optionally `:` and an image name or `@<index>` |
+//! | `dir:`           | a path                                         |
+//! | `containers-storage:` | a [`storage::StorageRef`]                 |
+//!
+//! Like `containers/image` on unix-like systems, paths end at the first `:`.
+//! References are parsed like `ParseNormalizedNamed`, so repository names
+//! can't be image IDs, and `docker://` references can't have both a tag and a
+//! digest. OCI image names are parsed as [`RefName`]s. Errors are reported at
+//! offsets within the whole transport reference.
+//! ```rust
+//! use container_image_dist_ref::transport::{ArchiveImage, Transport, TransportRef};
+//! let src = "docker://quay.io/skopeo/stable:latest";
+//! let TransportRef::Docker(img) = TransportRef::new(src).unwrap() else {
+//!     unreachable!()
+//! };
+//! assert_eq!(img.tag(), Some("latest"));
+//!
+//! let src = "oci:/tmp/layout:v1.0";
+//! let parsed = TransportRef::new(src).unwrap();
+//! assert_eq!(parsed.transport(), Transport::Oci);
+//! assert_eq!(parsed.to_string(), src);
+//! let TransportRef::Oci { path, image } = parsed else {
+//!     unreachable!()
+//! };
+//! assert_eq!(path, "/tmp/layout");
+//! assert!(matches!(image, Some(ArchiveImage::Name(name)) if name.to_str() == "v1.0"));
+//! ```
+
+pub mod storage;
+
+use core::fmt;
+
+use self::storage::StorageRef;
+use crate::{
+    any_ref::{normalized_name, AnyRef},
+    err::Kind,
+    layout::RefName,
+    span::Lengthy,
+    Error, ImgRef, Limits,
+};
+
+/// The transports `containers/image` can copy images between. See the
+/// [module docs](self) for each transport's syntax.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Transport {
+    /// `docker://`: a registry.
+    Docker,
+    /// `docker-daemon:`: the local docker daemon's image store.
+    DockerDaemon,
+    /// `docker-archive:`: a tarball written by `docker save`.
+    DockerArchive,
+    /// `oci:`: an OCI image layout directory.
+    Oci,
+    /// `oci-archive:`: a tarball of an OCI image layout.
+    OciArchive,
+    /// `dir:`: a directory of manifests and blobs.
+    Dir,
+    /// `containers-storage:`: a `containers/storage` store, as used by podman.
+    ContainersStorage,
+}
+
+impl Transport {
+    /// Every supported transport.
+    pub const ALL: [Transport; 7] = [
+        Transport::Docker,
+        Transport::DockerDaemon,
+        Transport::DockerArchive,
+        Transport::Oci,
+        Transport::OciArchive,
+        Transport::Dir,
+        Transport::ContainersStorage,
+    ];
+    /// The transport's name, as it appears before the `:`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Transport::Docker => "docker",
+            Transport::DockerDaemon => "docker-daemon",
+            Transport::DockerArchive => "docker-archive",
+            Transport::Oci => "oci",
+            Transport::OciArchive => "oci-archive",
+            Transport::Dir => "dir",
+            Transport::ContainersStorage => "containers-storage",
+        }
+    }
+    /// Look up a transport by name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.name() == name)
+    }
+}
+
+/// The image selected within an archive or OCI layout.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ArchiveImage<'src> {
+    /// An image in a `docker save` tarball, selected by reference.
+    Reference(ImgRef<'src>),
+    /// An image in an OCI layout, selected by its
+    /// [`REF_NAME_ANNOTATION`](crate::layout::REF_NAME_ANNOTATION).
+    Name(RefName<'src>),
+    /// An image selected by its position in the archive, written `@<index>`.
+    Index(usize),
+}
+
+/// A reference prefixed with its [`Transport`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TransportRef<'src> {
+    /// A reference to an image in a registry.
+    Docker(ImgRef<'src>),
+    /// A reference, or an image ID using the `sha256` algorithm. Bare hex
+    /// image IDs are rejected as ambiguous.
+    DockerDaemon(AnyRef<'src>),
+    /// A `docker save` tarball and, optionally, which image in it to use.
+    DockerArchive {
+        /// The path to the tarball.
+        path: &'src str,
+        /// Which image in the tarball to use, by tagged reference or index.
+        /// References may not include digests.
+        image: Option<ArchiveImage<'src>>,
+    },
+    /// An OCI image layout directory and, optionally, which image in it to use.
+    Oci {
+        /// The path to the layout directory.
+        path: &'src str,
+        /// Which image in the layout to use, by ref name or index.
+        image: Option<ArchiveImage<'src>>,
+    },
+    /// A tarball of an OCI image layout and, optionally, which image in it to use.
+    OciArchive {
+        /// The path to the tarball.
+        path: &'src str,
+        /// Which image in the layout to use, by ref name or index.
+        image: Option<ArchiveImage<'src>>,
+    },
+    /// The path to a directory of manifests and blobs.
+    Dir(&'src str),
+    /// An image in a `containers/storage` store.
+    ContainersStorage(StorageRef<'src>),
+}
+
+/// convert an offset to an error index, saturating for absurdly long paths
+fn index(i: usize) -> u16 {
+    u16::try_from(i).unwrap_or(u16::MAX)
+}
+
+/// shift errors from parsing a payload starting at `start`
+fn shift(start: usize) -> impl Fn(Error) -> Error {
+    move |e| Error::at(e.index().saturating_add(index(start)), e.kind())
+}
+
+/// split `path[:image]` or `path[:@index]` at the first `:`, parsing the
+/// image with `parse`
+fn archive<'src>(
+    src: &'src str,
+    start: usize,
+    parse: impl Fn(&'src str) -> Result<ArchiveImage<'src>, Error>,
+) -> Result<(&'src str, Option<ArchiveImage<'src>>), Error> {
+    let payload = &src[start..];
+    let (path, image) = match payload.split_once(':') {
+        Some((path, image)) => (path, image),
+        None => (payload, ""),
+    };
+    if path.is_empty() {
+        return Error::at(index(start), Kind::TransportPathMissing).into();
+    }
+    let image_start = start + path.len() + 1;
+    let image = if image.is_empty() {
+        None
+    } else if let Some(n) = image.strip_prefix('@') {
+        let n = n
+            .parse()
+            .map_err(|_| Error::at(index(image_start + 1), Kind::TransportImageInvalid))?;
+        Some(ArchiveImage::Index(n))
+    } else {
+        Some(parse(image).map_err(shift(image_start))?)
+    };
+    Ok((path, image))
+}
+
+/// parse an image name in an OCI layout
+fn layout_image(image: &str) -> Result<ArchiveImage<'_>, Error> {
+    RefName::new(image).map(ArchiveImage::Name)
+}
+
+/// whether a reference names docker's reserved `sha256` repository
+fn is_reserved(img: &ImgRef<'_>) -> bool {
+    let path = &img.src[img.span.path_range()];
+    match img.span.name.domain {
+        None => path == "sha256",
+        Some(domain) => {
+            matches!(&img.src[..domain.len()], "docker.io" | "index.docker.io")
+                && path == "library/sha256"
+        }
+    }
+}
+
+impl<'src> TransportRef<'src> {
+    /// Parse a transport-prefixed reference.
+    pub fn new(src: &'src str) -> Result<Self, Error> {
+        let (name, _) = src
+            .split_once(':')
+            .ok_or(Error::at(0, Kind::TransportUnknown))?;
+        let transport = Transport::from_name(name).ok_or(Error::at(0, Kind::TransportUnknown))?;
+        let start = name.len() + 1;
+        let payload = &src[start..];
+        Ok(match transport {
+            Transport::Docker => {
+                let rest = payload
+                    .strip_prefix("//")
+                    .ok_or(Error::at(index(start), Kind::TransportUnknown))?;
+                let img =
+                    normalized_name(rest.as_bytes(), Limits::DEFAULT).map_err(shift(start + 2))?;
+                // like containers/image, reject references with both a tag and a digest
+                if img.span.tag.is_some() && img.span.digest.is_some() {
+                    let at = start + 2 + img.span.digest_index() - 1;
+                    return Error::at(index(at), Kind::TransportImageInvalid).into();
+                }
+                TransportRef::Docker(img)
+            }
+            Transport::DockerDaemon => {
+                let any = AnyRef::new(payload).map_err(shift(start))?;
+                let invalid = match &any {
+                    AnyRef::Identifier(digest) => {
+                        !payload.contains(':') || digest.digest().algorithm().to_str() != "sha256"
+                    }
+                    AnyRef::Reference(img) => is_reserved(img),
+                };
+                if invalid {
+                    return Error::at(index(start), Kind::TransportImageInvalid).into();
+                }
+                TransportRef::DockerDaemon(any)
+            }
+            Transport::DockerArchive => {
+                let (path, image) = archive(src, start, |image| {
+                    normalized_name(image.as_bytes(), Limits::DEFAULT).map(ArchiveImage::Reference)
+                })?;
+                if let Some(ArchiveImage::Reference(img)) = &image {
+                    if img.span.digest.is_some() {
+                        let at = start + path.len() + 1 + img.span.digest_index() - 1;
+                        return Error::at(index(at), Kind::TransportImageInvalid).into();
+                    }
+                }
+                TransportRef::DockerArchive { path, image }
+            }
+            Transport::Oci => {
+                let (path, image) = archive(src, start, layout_image)?;
+                TransportRef::Oci { path, image }
+            }
+            Transport::OciArchive => {
+                let (path, image) = archive(src, start, layout_image)?;
+                TransportRef::OciArchive { path, image }
+            }
+            Transport::Dir => {
+                if payload.is_empty() {
+                    return Error::at(index(start), Kind::TransportPathMissing).into();
+                }
+                TransportRef::Dir(payload)
+            }
+            Transport::ContainersStorage => {
+                TransportRef::ContainersStorage(StorageRef::new(payload).map_err(shift(start))?)
+            }
+        })
+    }
+    /// Which transport the reference uses.
+    pub const fn transport(&self) -> Transport {
+        match self {
+            TransportRef::Docker(_) => Transport::Docker,
+            TransportRef::DockerDaemon(_) => Transport::DockerDaemon,
+            TransportRef::DockerArchive { .. } => Transport::DockerArchive,
+            TransportRef::Oci { .. } => Transport::Oci,
+            TransportRef::OciArchive { .. } => Transport::OciArchive,
+            TransportRef::Dir(_) => Transport::Dir,
+            TransportRef::ContainersStorage(_) => Transport::ContainersStorage,
+        }
+    }
+}
+
+impl fmt::Display for ArchiveImage<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchiveImage::Reference(img) => f.write_str(img.src),
+            ArchiveImage::Name(name) => f.write_str(name.to_str()),
+            ArchiveImage::Index(n) => write!(f, "@{n}"),
+        }
+    }
+}
+
+/// Displays as a transport-prefixed reference that parses to the same value.
+impl fmt::Display for TransportRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:", self.transport().name())?;
+        match self {
+            TransportRef::Docker(img) => write!(f, "//{}", img.src),
+            TransportRef::DockerDaemon(AnyRef::Identifier(digest)) => write!(f, "{digest}"),
+            TransportRef::DockerDaemon(AnyRef::Reference(img)) => f.write_str(img.src),
+            TransportRef::DockerArchive { path, image }
+            | TransportRef::Oci { path, image }
+            | TransportRef::OciArchive { path, image } => match image {
+                Some(image) => write!(f, "{path}:{image}"),
+                None => f.write_str(path),
+            },
+            TransportRef::Dir(path) => f.write_str(path),
+            TransportRef::ContainersStorage(storage) => write!(f, "{storage}"),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    extern crate alloc;
+    use alloc::{format, string::ToString};
+
+    use super::*;
+
+    const HEX: &str = "853ff93762a06ddbf722c4ebe9ddd66d8f63ddaea97f521c3ecc20da7c976020";
+
+    fn err(src: &str) -> (u16, Kind) {
+        let e = TransportRef::new(src).err().unwrap();
+        (e.index(), e.kind())
+    }
+    /// parse, then check that the displayed form round-trips
+    fn parse(src: &str) -> TransportRef<'_> {
+        let parsed = TransportRef::new(src).unwrap();
+        assert_eq!(parsed.to_string(), src);
+        parsed
+    }
+
+    #[test]
+    fn transport_names() {
+        for transport in Transport::ALL {
+            assert_eq!(Transport::from_name(transport.name()), Some(transport));
+        }
+        assert_eq!(Transport::from_name("ostree"), None);
+        assert_eq!(err("alpine"), (0, Kind::TransportUnknown));
+        assert_eq!(err("ftp:alpine"), (0, Kind::TransportUnknown));
+        assert_eq!(err("docker:alpine"), (7, Kind::TransportUnknown));
+    }
+
+    #[test]
+    fn docker() {
+        let TransportRef::Docker(img) = parse("docker://quay.io/x:y") else {
+            panic!()
+        };
+        assert_eq!((img.path().to_str(), img.tag()), ("x", Some("y")));
+        assert_eq!(err("docker://quay.io/X"), (17, Kind::PathInvalidChar));
+        // repository names can't be mistaken for image IDs
+        assert_eq!(err(&format!("docker://{HEX}")), (9, Kind::PathIsIdentifier));
+        assert!(TransportRef::new(&format!("docker://quay.io/{HEX}")).is_ok());
+        // references may have a tag or a digest, but not both
+        assert!(TransportRef::new(&format!("docker://x@sha256:{HEX}")).is_ok());
+        assert_eq!(
+            err(&format!("docker://x:y@sha256:{HEX}")),
+            (12, Kind::TransportImageInvalid)
+        );
+    }
+
+    #[test]
+    fn docker_daemon() {
+        let parsed = parse("docker-daemon:alpine:latest");
+        assert!(matches!(
+            parsed,
+            TransportRef::DockerDaemon(AnyRef::Reference(img)) if img.tag() == Some("latest")
+        ));
+        let id = format!("docker-daemon:sha256:{HEX}");
+        assert!(matches!(
+            parse(&id),
+            TransportRef::DockerDaemon(AnyRef::Identifier(_))
+        ));
+        // image IDs must use sha256 and include the algorithm
+        let sha512 = format!("docker-daemon:sha512:{HEX}{HEX}");
+        assert_eq!(err(&sha512), (14, Kind::TransportImageInvalid));
+        let bare = format!("docker-daemon:{HEX}");
+        assert_eq!(err(&bare), (14, Kind::TransportImageInvalid));
+        // the sha256 repository is reserved for image IDs
+        assert_eq!(
+            err("docker-daemon:sha256:abc"),
+            (14, Kind::TransportImageInvalid)
+        );
+        assert_eq!(
+            err("docker-daemon:docker.io/library/sha256:abc"),
+            (14, Kind::TransportImageInvalid)
+        );
+        assert!(TransportRef::new("docker-daemon:quay.io/sha256:abc").is_ok());
+    }
+
+    #[test]
+    fn archives() {
+        let TransportRef::DockerArchive { path, image } = parse("docker-archive:/tmp/a.tar") else {
+            panic!()
+        };
+        assert_eq!(path, "/tmp/a.tar");
+        assert!(image.is_none());
+        let TransportRef::DockerArchive { image, .. } = parse("docker-archive:/tmp/a.tar:x:y")
+        else {
+            panic!()
+        };
+        assert!(matches!(image, Some(ArchiveImage::Reference(img)) if img.tag() == Some("y")));
+        let TransportRef::OciArchive { path, image } = parse("oci-archive:a.tar:@3") else {
+            panic!()
+        };
+        assert_eq!(path, "a.tar");
+        assert!(matches!(image, Some(ArchiveImage::Index(3))));
+        // an empty image is no image
+        let TransportRef::Oci { image, .. } = TransportRef::new("oci:/layout:").unwrap() else {
+            panic!()
+        };
+        assert!(image.is_none());
+
+        let digested = format!("docker-archive:a.tar:x@sha256:{HEX}");
+        assert_eq!(err(&digested), (22, Kind::TransportImageInvalid));
+        let id = format!("docker-archive:a.tar:{HEX}");
+        assert_eq!(err(&id), (21, Kind::PathIsIdentifier));
+        assert!(TransportRef::new(&format!("oci:/layout:x@sha256:{HEX}")).is_ok());
+        assert_eq!(err("oci:/layout:@x"), (13, Kind::TransportImageInvalid));
+        assert_eq!(err("oci:/layout:x y"), (13, Kind::RefNameInvalidChar));
+        assert_eq!(
+            err("oci:/layout:v1.-rc"),
+            (15, Kind::RefNameInvalidSeparator)
+        );
+        // OCI image names aren't references, so may be uppercase or use `+`
+        for src in ["oci:/l:V1", "oci-archive:/l.tar:1.0+build"] {
+            assert_eq!(parse(src).to_string(), src);
+        }
+        let TransportRef::Oci { image, .. } = parse("oci:/l:V1") else {
+            panic!()
+        };
+        assert!(matches!(image, Some(ArchiveImage::Name(name)) if name.to_str() == "V1"));
+        assert_eq!(err("oci-archive::x"), (12, Kind::TransportPathMissing));
+        assert_eq!(err("dir:"), (4, Kind::TransportPathMissing));
+        assert!(matches!(parse("dir:/tmp/x"), TransportRef::Dir("/tmp/x")));
+    }
+
+    #[test]
+    fn copies_compare_equal() {
+        for src in ["oci:/l:v1", "docker-archive:a.tar:@0", "dir:/tmp/x"] {
+            let parsed = parse(src);
+            let copy = parsed;
+            assert_eq!(copy, parsed);
+        }
+        assert_ne!(parse("oci:/l:v1"), parse("oci-archive:/l:v1"));
+        assert_eq!(
+            format!("{:?}", parse("oci:/l:@1")),
+            r#"Oci { path: "/l", image: Some(Index(1)) }"#
+        );
+    }
+
+    #[test]
+    fn containers_storage() {
+        let src = "containers-storage:[overlay@/var/lib/containers/storage]alpine";
+        let TransportRef::ContainersStorage(storage) = parse(src) else {
+            panic!()
+        };
+        assert_eq!(storage.store().unwrap().driver(), Some("overlay"));
+        // errors are relative to the whole string
+        assert_eq!(
+            err("containers-storage:[vfs@g]x"),
+            (24, Kind::StorePathNotAbsolute)
+        );
+    }
+}
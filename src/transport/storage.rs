@@ -0,0 +1,294 @@
+//! # `containers-storage:` references
+//! Podman and buildah keep images in a `containers/storage` store. References
+//! to images in a store follow containers/image's [`storage` transport](https://github.com/containers/image/blob/v5.30.0/storage/storage_transport.go):
+//! an optional store specifier in brackets, then a name, an image ID, or both.
+//! ```text
+//! [driver@graph-root+run-root:option,option]name:tag@digest@image-id
+//! ```
+//! Every part of the store specifier is optional, but it can't be empty, and
+//! its paths must be absolute. An image ID is 64 lowercase hex digits; after an
+//! `@`, it may be truncated to as few as 3 digits. Without an `@`, a value that
+//! could be either a name or a truncated image ID is parsed as a name, since
+//! telling them apart requires looking in the store.
+//! ```rust
+//! use container_image_dist_ref::transport::storage::StorageRef;
+//! let src = "[overlay@/var/lib/containers/storage+/run/containers/storage:opt=1]alpine:3.19@0a1b2c";
+//! let storage = StorageRef::new(src).unwrap();
+//! let store = storage.store().unwrap();
+//! assert_eq!(store.driver(), Some("overlay"));
+//! assert_eq!(store.graph_root(), Some("/var/lib/containers/storage"));
+//! assert_eq!(store.run_root(), Some("/run/containers/storage"));
+//! assert_eq!(store.options().collect::<Vec<_>>(), ["opt=1"]);
+//! assert_eq!(storage.name().unwrap().tag(), Some("3.19"));
+//! assert_eq!(storage.id(), Some("0a1b2c"));
+//! assert_eq!(storage.to_string(), src);
+//! ```
+
+use core::fmt;
+
+use super::{index, shift};
+use crate::{
+    any_ref::{is_identifier, normalized_name},
+    digest::{registry::AlgorithmRegistry, Digest},
+    err::Kind,
+    Error, ImgRef, Limits,
+};
+
+/// The shortest truncated image ID containers/image looks up in a store.
+pub const MIN_TRUNCATED_ID_LEN: usize = 3;
+
+/// Which store to use, and how to configure it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct StoreSpec<'src> {
+    driver: Option<&'src str>,
+    graph_root: Option<&'src str>,
+    run_root: Option<&'src str>,
+    options: Option<&'src str>,
+}
+
+impl<'src> StoreSpec<'src> {
+    /// parse the text between the brackets, which starts at offset 1
+    fn new(spec: &'src str) -> Result<Self, Error> {
+        let invalid = |i: usize| Error::at(index(i), Kind::StoreSpecInvalid);
+        let (driver, rest, rest_start) = match spec.split_once('@') {
+            Some(("", _)) => return Err(invalid(1)),
+            Some((_, "")) => return Err(invalid(spec.len() + 1)),
+            Some((driver, rest)) => (Some(driver), rest, driver.len() + 2),
+            None if spec.is_empty() => return Err(invalid(1)),
+            None => (None, spec, 1),
+        };
+        let (roots, options) = match rest.split_once(':') {
+            Some((roots, options)) => (roots, Some(options)),
+            None => (rest, None),
+        };
+        let (graph_root, run_root) = match roots.split_once('+') {
+            Some((graph_root, run_root)) => (graph_root, run_root),
+            None => (roots, ""),
+        };
+        let run_start = rest_start + graph_root.len() + 1;
+        for (path, start) in [(graph_root, rest_start), (run_root, run_start)] {
+            if !path.is_empty() && !path.starts_with('/') {
+                return Error::at(index(start), Kind::StorePathNotAbsolute).into();
+            }
+        }
+        let non_empty = |s: &'src str| Some(s).filter(|s| !s.is_empty());
+        Ok(Self {
+            driver,
+            graph_root: non_empty(graph_root),
+            run_root: non_empty(run_root),
+            options,
+        })
+    }
+    /// The storage driver, such as `overlay` or `vfs`.
+    pub const fn driver(&self) -> Option<&'src str> {
+        self.driver
+    }
+    /// The absolute path of the directory holding images.
+    pub const fn graph_root(&self) -> Option<&'src str> {
+        self.graph_root
+    }
+    /// The absolute path of the directory holding runtime state.
+    pub const fn run_root(&self) -> Option<&'src str> {
+        self.run_root
+    }
+    /// The comma-separated driver options.
+    pub fn options(&self) -> impl Iterator<Item = &'src str> {
+        self.options.into_iter().flat_map(|o| o.split(','))
+    }
+}
+
+impl fmt::Display for StoreSpec<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        if let Some(driver) = self.driver {
+            write!(f, "{driver}@")?;
+        }
+        f.write_str(self.graph_root.unwrap_or(""))?;
+        if let Some(run_root) = self.run_root {
+            write!(f, "+{run_root}")?;
+        }
+        if let Some(options) = self.options {
+            write!(f, ":{options}")?;
+        }
+        f.write_str("]")
+    }
+}
+
+/// A reference to an image in a `containers/storage` store: a name, an image
+/// ID, or both, optionally preceded by a [`StoreSpec`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct StorageRef<'src> {
+    store: Option<StoreSpec<'src>>,
+    name: Option<ImgRef<'src>>,
+    id: Option<&'src str>,
+}
+
+/// whether `src` is a digest go-digest would accept: a valid digest using a
+/// registered algorithm
+fn is_registered_digest(src: &str) -> bool {
+    src.split_once(':')
+        .is_some_and(|(algorithm, _)| AlgorithmRegistry::DEFAULT.get(algorithm).is_some())
+        && Digest::new(src).is_ok()
+}
+
+/// whether `src` could be a possibly-truncated image ID
+fn is_id_prefix(src: &str) -> bool {
+    (MIN_TRUNCATED_ID_LEN..=64).contains(&src.len())
+        && src.bytes().all(|c| matches!(c, b'a'..=b'f' | b'0'..=b'9'))
+}
+
+impl<'src> StorageRef<'src> {
+    /// Parse a reference following `containers-storage:`.
+    pub fn new(src: &'src str) -> Result<Self, Error> {
+        let (store, start) = match src.strip_prefix('[') {
+            Some(rest) => {
+                let close = rest
+                    .find(']')
+                    .ok_or(Error::at(index(src.len()), Kind::StoreSpecUnterminated))?;
+                (Some(StoreSpec::new(&rest[..close])?), close + 2)
+            }
+            None => (None, 0),
+        };
+        let mut rest = &src[start..];
+        if rest.is_empty() {
+            return Error::at(index(start), Kind::RefMissing).into();
+        }
+
+        // a trailing `@` may separate an image ID; digests are left in place
+        let mut id = None;
+        if let Some(at) = rest.rfind('@') {
+            let possible_id = &rest[at + 1..];
+            if !is_registered_digest(possible_id) {
+                if !is_id_prefix(possible_id) {
+                    return Error::at(index(start + at + 1), Kind::TransportImageInvalid).into();
+                }
+                id = Some(possible_id);
+                rest = &rest[..at];
+            }
+        }
+        if id.is_none() && is_identifier(rest.as_bytes()) {
+            id = Some(rest);
+            rest = "";
+        }
+        let name = match rest {
+            "" => None,
+            rest => Some(normalized_name(rest.as_bytes(), Limits::DEFAULT).map_err(shift(start))?),
+        };
+        Ok(Self { store, name, id })
+    }
+    /// The store specifier, if any.
+    pub const fn store(&self) -> Option<&StoreSpec<'src>> {
+        self.store.as_ref()
+    }
+    /// The image's name, possibly with a tag and/or digest.
+    pub const fn name(&self) -> Option<&ImgRef<'src>> {
+        self.name.as_ref()
+    }
+    /// The image ID, which may be truncated.
+    pub const fn id(&self) -> Option<&'src str> {
+        self.id
+    }
+}
+
+impl fmt::Display for StorageRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(store) = &self.store {
+            write!(f, "{store}")?;
+        }
+        let name = self.name.as_ref().map(|n| n.src);
+        match (name, self.id) {
+            (Some(name), Some(id)) => write!(f, "{name}@{id}"),
+            (Some(name), None) => f.write_str(name),
+            // full IDs don't need an `@`, but truncated ones do
+            (None, Some(id)) if is_identifier(id.as_bytes()) => f.write_str(id),
+            (None, Some(id)) => write!(f, "@{id}"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    extern crate alloc;
+    use alloc::{format, string::ToString, vec::Vec};
+
+    use super::*;
+
+    const HEX: &str = "853ff93762a06ddbf722c4ebe9ddd66d8f63ddaea97f521c3ecc20da7c976020";
+
+    fn err(src: &str) -> (u16, Kind) {
+        let e = StorageRef::new(src).err().unwrap();
+        (e.index(), e.kind())
+    }
+    fn parse(src: &str) -> StorageRef<'_> {
+        let parsed = StorageRef::new(src).unwrap();
+        assert_eq!(parsed.to_string(), src);
+        parsed
+    }
+    fn spec(src: &str) -> StoreSpec<'_> {
+        *parse(src).store().unwrap()
+    }
+
+    #[test]
+    fn store_specifiers() {
+        let vfs = spec("[vfs@/g]x");
+        assert_eq!((vfs.driver(), vfs.graph_root()), (Some("vfs"), Some("/g")));
+        assert_eq!((vfs.run_root(), vfs.options().count()), (None, 0));
+        let roots = spec("[/g+/r]x");
+        assert_eq!((roots.driver(), roots.graph_root()), (None, Some("/g")));
+        assert_eq!(roots.run_root(), Some("/r"));
+        let run_only = spec("[overlay@+/r:a,b=c]x");
+        assert_eq!(run_only.graph_root(), None);
+        assert_eq!(run_only.options().collect::<Vec<_>>(), ["a", "b=c"]);
+
+        assert_eq!(err("[/g"), (3, Kind::StoreSpecUnterminated));
+        assert_eq!(err("[]x"), (1, Kind::StoreSpecInvalid));
+        assert_eq!(err("[@/g]x"), (1, Kind::StoreSpecInvalid));
+        assert_eq!(err("[vfs@]x"), (5, Kind::StoreSpecInvalid));
+        assert_eq!(err("[vfs@g]x"), (5, Kind::StorePathNotAbsolute));
+        assert_eq!(err("[/g+r]x"), (4, Kind::StorePathNotAbsolute));
+        assert_eq!(err("[/g]"), (4, Kind::RefMissing));
+    }
+
+    #[test]
+    fn names_and_ids() {
+        let named = parse("alpine:3.19");
+        assert_eq!(named.name().unwrap().tag(), Some("3.19"));
+        assert_eq!(named.id(), None);
+
+        let digested = format!("quay.io/x@sha256:{HEX}");
+        let digested = parse(&digested);
+        assert!(digested.name().unwrap().digest().is_some());
+        assert_eq!(digested.id(), None);
+
+        let both = format!("x:y@sha256:{HEX}@{HEX}");
+        let both = parse(&both);
+        assert!(both.name().unwrap().digest().is_some());
+        assert_eq!(both.id(), Some(HEX));
+
+        let full = parse(HEX);
+        assert!(full.name().is_none());
+        assert_eq!(full.id(), Some(HEX));
+        let truncated = parse("@853ff9");
+        assert!(truncated.name().is_none());
+        assert_eq!(truncated.id(), Some("853ff9"));
+        // without an `@`, short hex could be a name
+        assert!(parse("853ff9").name().is_some());
+
+        assert_eq!(err("x@"), (2, Kind::TransportImageInvalid));
+        assert_eq!(err("x@ab"), (2, Kind::TransportImageInvalid));
+        assert_eq!(err("x@ABC123"), (2, Kind::TransportImageInvalid));
+        assert_eq!(err("[/g]X"), (4, Kind::PathInvalidChar));
+        assert_eq!(err(&format!("[/g]{HEX}:tag")), (4, Kind::PathIsIdentifier));
+    }
+
+    #[test]
+    fn copies_compare_equal() {
+        let parsed = parse("[vfs@/g]alpine:3.19");
+        let copy = parsed;
+        assert_eq!(copy, parsed);
+        assert_ne!(parsed, parse("alpine:3.19"));
+        assert!(format!("{parsed:?}").contains(r#"name: Some(ImgRef("alpine:3.19"))"#));
+    }
+}
@@ -0,0 +1,272 @@
+//! # References from URLs
+//! Recognize links to images and rewrite them as fully-qualified references.
+//! Registry API URLs are recognized for any host:
+//! ```text
+//! https://<host>/v2/<name>/manifests/<tag or digest>
+//! https://<host>/v2/<name>/blobs/<digest>
+//! https://<host>/v2/<name>/referrers/<digest>
+//! https://<host>/v2/<name>/tags/list
+//! ```
+//! as are web UI URLs for some public registries:
+//! ```text
+//! https://hub.docker.com/_/<name>                 -> docker.io/library/<name>
+//! https://hub.docker.com/r/<namespace>/<name>     -> docker.io/<namespace>/<name>
+//! https://quay.io/repository/<namespace>/<name>   -> quay.io/<namespace>/<name>
+//! https://gallery.ecr.aws/<alias>/<name>          -> public.ecr.aws/<alias>/<name>
+//! ```
+//! The scheme is optional, and query strings and fragments are ignored. Docker
+//! Hub's API hosts are normalized to `docker.io`, adding the `library/`
+//! namespace to official images' names.
+//! ```rust
+//! use container_image_dist_ref::url::{UrlKind, UrlRef};
+//! let hex = "09ca7e4eaa6e8ae9c7d261167129184883644d07dfba7cbfbc4c8a2e08360d5b";
+//! let url = format!("https://ghcr.io/v2/org/app/manifests/sha256:{hex}");
+//! let parsed = UrlRef::new(&url).unwrap();
+//! assert_eq!(parsed.kind(), UrlKind::Manifest);
+//! assert_eq!(parsed.to_str(), format!("ghcr.io/org/app@sha256:{hex}"));
+//! assert_eq!(parsed.img_ref().digest().unwrap().encoded().to_str(), hex);
+//!
+//! let parsed = UrlRef::new("https://hub.docker.com/_/nginx").unwrap();
+//! assert_eq!(parsed.to_str(), "docker.io/library/nginx");
+//! let parsed = UrlRef::new("https://quay.io/repository/org/app?tab=tags").unwrap();
+//! assert_eq!(parsed.to_str(), "quay.io/org/app");
+//! ```
+
+use alloc::{format, string::String};
+use core::fmt;
+
+use crate::{err::Kind, Error, ImgRef};
+
+/// Which kind of URL a reference came from.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UrlKind {
+    /// A `/v2/<name>/manifests/<reference>` API URL.
+    Manifest,
+    /// A `/v2/<name>/blobs/<digest>` API URL. The digest is a blob's, not an
+    /// image's.
+    Blob,
+    /// A `/v2/<name>/referrers/<digest>` API URL.
+    Referrers,
+    /// A `/v2/<name>/tags/list` API URL.
+    TagsList,
+    /// A registry's web UI page for a repository.
+    WebUi,
+}
+
+/// Hosts serving Docker Hub's registry API.
+const DOCKER_HUB_API: [&str; 2] = ["registry-1.docker.io", "index.docker.io"];
+
+fn index(i: usize) -> u16 {
+    u16::try_from(i).unwrap_or(u16::MAX)
+}
+
+/// A fully-qualified image reference recognized from a URL.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct UrlRef {
+    reference: String,
+    kind: UrlKind,
+}
+
+impl UrlRef {
+    /// Recognize a registry API or web UI URL. Errors are reported at offsets
+    /// within `url`; if the rewritten reference is invalid, its error's kind is
+    /// reported at the start of the URL's path.
+    pub fn new(url: &str) -> Result<Self, Error> {
+        let start = ["https://", "http://"]
+            .into_iter()
+            .find_map(|scheme| url.strip_prefix(scheme).map(|_| scheme.len()))
+            .unwrap_or(0);
+        let end = url.find(['?', '#']).unwrap_or(url.len());
+        let rest = &url[start..end];
+        let host_end = rest
+            .find('/')
+            .ok_or(Error::at(index(end), Kind::UrlUnrecognized))?;
+        let host = &rest[..host_end];
+        let path_start = start + host_end + 1;
+        let path = rest[host_end + 1..].trim_end_matches('/');
+        let unrecognized = Error::at(index(path_start), Kind::UrlUnrecognized);
+
+        let (reference, kind) = if let Some(api) = path.strip_prefix("v2/") {
+            let (host, official) = if DOCKER_HUB_API.contains(&host) {
+                ("docker.io", "library/")
+            } else {
+                (host, "")
+            };
+            Self::api(host, official, api).ok_or(unrecognized)?
+        } else {
+            let web = |reference| (reference, UrlKind::WebUi);
+            let mut parts = path.split('/');
+            match (host, parts.next(), parts.next(), parts.next()) {
+                ("hub.docker.com", Some("_"), Some(name), _) => {
+                    web(format!("docker.io/library/{name}"))
+                }
+                ("hub.docker.com", Some("r"), Some(namespace), Some(name)) => {
+                    web(format!("docker.io/{namespace}/{name}"))
+                }
+                ("quay.io", Some("repository"), Some(namespace), Some(name)) => {
+                    let mut reference = format!("quay.io/{namespace}/{name}");
+                    if let (Some("manifest"), Some(digest)) = (parts.next(), parts.next()) {
+                        reference.push('@');
+                        reference.push_str(digest);
+                    }
+                    web(reference)
+                }
+                ("gallery.ecr.aws", Some(alias), Some(name), _) => {
+                    web(format!("public.ecr.aws/{alias}/{name}"))
+                }
+                _ => return Err(unrecognized),
+            }
+        };
+        if let Err(e) = ImgRef::new(&reference) {
+            return Error::at(index(path_start), e.kind()).into();
+        }
+        Ok(Self { reference, kind })
+    }
+    /// rewrite the part of an API URL's path after `/v2/`, putting
+    /// single-component names in the `official` namespace
+    fn api(host: &str, official: &str, path: &str) -> Option<(String, UrlKind)> {
+        let namespace = |name: &str| if name.contains('/') { "" } else { official };
+        if let Some(name) = path.strip_suffix("/tags/list") {
+            let namespace = namespace(name);
+            return Some((format!("{host}/{namespace}{name}"), UrlKind::TagsList));
+        }
+        let (name, reference) = path.rsplit_once('/')?;
+        let (name, route) = name.rsplit_once('/')?;
+        let kind = match route {
+            "manifests" => UrlKind::Manifest,
+            "blobs" => UrlKind::Blob,
+            "referrers" => UrlKind::Referrers,
+            _ => return None,
+        };
+        let separator = match kind {
+            UrlKind::Manifest if !reference.contains(':') => ':',
+            _ => '@',
+        };
+        let namespace = namespace(name);
+        Some((
+            format!("{host}/{namespace}{name}{separator}{reference}"),
+            kind,
+        ))
+    }
+    /// The fully-qualified reference.
+    pub fn to_str(&self) -> &str {
+        &self.reference
+    }
+    /// The parsed reference.
+    #[allow(clippy::unwrap_used)]
+    pub fn img_ref(&self) -> ImgRef<'_> {
+        ImgRef::new(&self.reference).unwrap() // validated in `new`
+    }
+    /// Which kind of URL the reference came from.
+    pub const fn kind(&self) -> UrlKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for UrlRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.reference)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    const HEX: &str = "853ff93762a06ddbf722c4ebe9ddd66d8f63ddaea97f521c3ecc20da7c976020";
+
+    fn parse(url: &str) -> (String, UrlKind) {
+        let parsed = UrlRef::new(url).unwrap();
+        (parsed.to_str().into(), parsed.kind())
+    }
+    fn err(url: &str) -> (u16, Kind) {
+        let e = UrlRef::new(url).err().unwrap();
+        (e.index(), e.kind())
+    }
+
+    #[test]
+    fn api_urls() {
+        use UrlKind::*;
+        assert_eq!(
+            parse("https://registry-1.docker.io/v2/library/redis/manifests/7.2"),
+            ("docker.io/library/redis:7.2".into(), Manifest)
+        );
+        // official images on docker hub get their `library/` namespace
+        assert_eq!(
+            parse("https://registry-1.docker.io/v2/redis/manifests/7.2"),
+            ("docker.io/library/redis:7.2".into(), Manifest)
+        );
+        assert_eq!(
+            parse("index.docker.io/v2/redis/tags/list"),
+            ("docker.io/library/redis".into(), TagsList)
+        );
+        assert_eq!(
+            parse("https://ghcr.io/v2/redis/manifests/7.2"),
+            ("ghcr.io/redis:7.2".into(), Manifest)
+        );
+        assert_eq!(
+            parse(&format!(
+                "http://localhost:5000/v2/a/b/c/blobs/sha256:{HEX}"
+            )),
+            (format!("localhost:5000/a/b/c@sha256:{HEX}"), Blob)
+        );
+        assert_eq!(
+            parse(&format!(
+                "[::1]:5000/v2/app/referrers/sha256:{HEX}?artifactType=x"
+            )),
+            (format!("[::1]:5000/app@sha256:{HEX}"), Referrers)
+        );
+        assert_eq!(
+            parse("https://ghcr.io/v2/org/app/tags/list?n=10"),
+            ("ghcr.io/org/app".into(), TagsList)
+        );
+        // repositories may be named like routes
+        assert_eq!(
+            parse("https://ghcr.io/v2/manifests/manifests/latest"),
+            ("ghcr.io/manifests:latest".into(), Manifest)
+        );
+    }
+
+    #[test]
+    fn web_ui_urls() {
+        for (url, expected) in [
+            ("https://hub.docker.com/_/nginx/", "docker.io/library/nginx"),
+            (
+                "hub.docker.com/r/bitnami/redis/tags",
+                "docker.io/bitnami/redis",
+            ),
+            (
+                "https://quay.io/repository/org/app?tab=tags",
+                "quay.io/org/app",
+            ),
+            ("https://gallery.ecr.aws/x/y", "public.ecr.aws/x/y"),
+        ] {
+            assert_eq!(parse(url), (expected.into(), UrlKind::WebUi));
+        }
+        let with_digest = format!("https://quay.io/repository/org/app/manifest/sha256:{HEX}");
+        assert_eq!(
+            parse(&with_digest).0,
+            format!("quay.io/org/app@sha256:{HEX}")
+        );
+    }
+
+    #[test]
+    fn unrecognized_urls() {
+        assert_eq!(err("https://ghcr.io"), (15, Kind::UrlUnrecognized));
+        assert_eq!(err("https://ghcr.io/org/app"), (16, Kind::UrlUnrecognized));
+        assert_eq!(err("https://ghcr.io/v2/"), (16, Kind::UrlUnrecognized));
+        assert_eq!(
+            err("https://ghcr.io/v2/app/uploads/x"),
+            (16, Kind::UrlUnrecognized)
+        );
+        assert_eq!(
+            err("https://hub.docker.com/r/x"),
+            (23, Kind::UrlUnrecognized)
+        );
+        assert_eq!(
+            err("https://ghcr.io/v2/App/tags/list"),
+            (16, Kind::PathInvalidChar)
+        );
+    }
+}
@@ -0,0 +1,349 @@
+//! # Validate-only parsing
+//! A single forward pass over an image reference that answers "would parsing
+//! succeed?" without building any `DomainOrRefSpan`, `PathSpan`, or `DigestSpan`.
+//!
+//! The automaton mirrors the span-building parsers exactly; the only state it
+//! carries between sections is the kind of the leading host-or-path segment and
+//! the digest's compliance level. Since every length limit in the name is at
+//! most 255, only the total name length needs to be checked.
+
+use crate::{
+    ambiguous::host_or_path::{Kind as HostOrPathKind, Scan},
+    digest::{encoded, registry::AlgorithmRegistry, Compliance},
+    name::{self, domain::ipv6::Ipv6Span},
+    span::Lengthy,
+    tag,
+};
+
+/// What a valid reference was found to contain, beyond the mandatory path.
+#[derive(Clone, Copy)]
+pub(crate) struct Found {
+    pub(crate) domain: bool,
+    pub(crate) digest: bool,
+}
+
+/// The leading segment of a reference before any `:`, `/`, or `@`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Left {
+    /// must be a host: either uppercase or a bracketed IPv6 address
+    Host,
+    /// must be a path: contains underscores
+    Path,
+    /// could be either
+    HostOrPath,
+}
+
+/// Scan one host-or-path component starting at `start`. Returns the index of
+/// the byte that stopped the component and the final scan state.
+fn component(src: &[u8], start: usize, mut scan: Scan) -> Option<(usize, Scan)> {
+    // a component must start with an alphanumeric character
+    if !src.get(start)?.is_ascii_alphanumeric() {
+        return None;
+    }
+    let mut index = start;
+    while let Some(&c) = src.get(index) {
+        if matches!(c, b':' | b'/' | b'@') {
+            break;
+        }
+        scan.update(c).ok()?;
+        index += 1;
+    }
+    scan.at_component_end().then_some((index, scan))
+}
+
+/// Scan the `/`-delimited path components starting at `start` until a `:`, `@`,
+/// or the end of the source. Returns the index of the stopping byte.
+fn path(src: &[u8], start: usize) -> Option<usize> {
+    let mut index = start;
+    loop {
+        let (end, _) = component(src, index, Scan::from(HostOrPathKind::Path))?;
+        match src.get(end) {
+            Some(b'/') => index = end + 1,
+            _ => return Some(end),
+        }
+    }
+}
+
+/// Scan a tag starting after its leading `:`. Returns the index of the stopping byte.
+fn tag(src: &[u8], start: usize) -> Option<usize> {
+    match src.get(start)? {
+        b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'_' => {}
+        _ => return None,
+    }
+    let mut index = start + 1;
+    loop {
+        match src.get(index) {
+            Some(b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'_' | b'.' | b'-') => index += 1,
+            Some(b'@') | None => break,
+            Some(_) => return None,
+        }
+    }
+    (index - start <= tag::MAX_LEN.get().into()).then_some(index)
+}
+
+/// Scan a digest starting after its leading `@` through to the end of the source.
+fn digest(src: &str, start: usize) -> Option<()> {
+    use Compliance::*;
+    let bytes = src.as_bytes();
+    // algorithm ---------------------------------------------------------------
+    let mut compliance = Universal;
+    let mut index = start;
+    loop {
+        compliance = match bytes.get(index)? {
+            b'a'..=b'z' => compliance,
+            b'0'..=b'9' if compliance != Distribution => Oci,
+            b'A'..=b'Z' if compliance != Oci => Distribution,
+            _ => return None,
+        };
+        index += 1;
+        loop {
+            match bytes.get(index)? {
+                b'a'..=b'z' | b'0'..=b'9' => {}
+                b'A'..=b'Z' if compliance != Oci => compliance = Distribution,
+                b':' | b'+' | b'.' | b'_' | b'-' => break,
+                _ => return None,
+            }
+            index += 1;
+        }
+        if index - start > usize::from(crate::digest::algorithm::MAX_LEN) {
+            return None;
+        }
+        if bytes[index] == b':' {
+            break;
+        }
+        index += 1; // consume the separator
+    }
+    let algorithm = &src[start..index];
+
+    // encoded -----------------------------------------------------------------
+    let start = index + 1;
+    for &c in bytes.get(start..)? {
+        match c {
+            b'a'..=b'f' | b'0'..=b'9' | b'A'..=b'F' => {}
+            b'g'..=b'z' | b'G'..=b'Z' | b'=' | b'_' | b'-' if compliance != Distribution => {
+                compliance = Oci;
+            }
+            _ => return None,
+        }
+    }
+    let len = bytes.len() - start;
+    if len == 0 || len > usize::from(encoded::MAX_LEN) {
+        return None;
+    }
+    if let Some(rule) = AlgorithmRegistry::DEFAULT.get(algorithm) {
+        rule.validate(&src[start..]).ok()?;
+        compliance = encoded::restrict(compliance, rule.compliance())?;
+    }
+    if compliance == Distribution && len < 32 {
+        return None;
+    }
+    Some(())
+}
+
+/// Validate an image reference in a single forward pass.
+pub(crate) fn reference(src: &str) -> Option<Found> {
+    let bytes = src.as_bytes();
+    // leading host-or-path segment ---------------------------------------------
+    let (mut index, left) = if bytes.first() == Some(&b'[') {
+        (Ipv6Span::new(bytes).ok()?.len(), Left::Host)
+    } else {
+        let (end, scan) = component(bytes, 0, Scan::from(HostOrPathKind::Any))?;
+        let left = if scan.has_upper() {
+            Left::Host
+        } else if scan.has_underscore() {
+            Left::Path
+        } else {
+            Left::HostOrPath
+        };
+        (end, left)
+    };
+
+    // ambiguous port-or-tag ----------------------------------------------------
+    let mut name_end = index;
+    let mut domain = false;
+    if bytes.get(index) == Some(&b':') {
+        let start = index + 1;
+        let mut is_port = true;
+        index = start;
+        loop {
+            match bytes.get(index) {
+                Some(b'0'..=b'9') => {}
+                Some(b'.' | b'-') if index != start => is_port = false,
+                Some(b'a'..=b'z' | b'A'..=b'Z' | b'_') => is_port = false,
+                Some(b'/') if is_port && index != start => break,
+                Some(b'@') | None if index != start => break,
+                _ => return None,
+            }
+            index += 1;
+        }
+        if bytes.get(index) == Some(&b'/') {
+            // a port, so the left segment must be a host
+            if left == Left::Path {
+                return None;
+            }
+            domain = true;
+        } else if left == Left::Host || index - start > tag::MAX_LEN.get().into() {
+            return None;
+        }
+    }
+
+    // path ---------------------------------------------------------------------
+    match bytes.get(index) {
+        Some(b'/') => {
+            domain = domain || left != Left::Path;
+            index = path(bytes, index + 1)?;
+            name_end = index;
+        }
+        _ if left == Left::Host => return None,
+        _ => {}
+    }
+    if name_end > usize::from(name::MAX_LEN) {
+        return None;
+    }
+
+    // tag and digest -----------------------------------------------------------
+    if bytes.get(index) == Some(&b':') {
+        index = tag(bytes, index + 1)?;
+    }
+    let digest = match bytes.get(index) {
+        Some(b'@') => {
+            digest(src, index + 1)?;
+            true
+        }
+        _ => false,
+    };
+    if !digest && index != bytes.len() {
+        return None;
+    }
+    Some(Found { domain, digest })
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::arithmetic_side_effects,
+    clippy::cast_possible_truncation
+)]
+mod tests {
+    extern crate alloc;
+    use alloc::string::String;
+
+    use crate::{CanonicalImgRef, ImgRef};
+
+    fn should_match(src: &str) {
+        assert_eq!(
+            ImgRef::is_valid(src),
+            ImgRef::new(src).is_ok(),
+            "is_valid disagrees with ImgRef::new for {src:?}"
+        );
+        assert_eq!(
+            CanonicalImgRef::is_valid(src),
+            CanonicalImgRef::new(src).is_ok(),
+            "is_valid disagrees with CanonicalImgRef::new for {src:?}"
+        );
+    }
+
+    #[test]
+    fn matches_fixture_corpus() {
+        include_str!("../tests/fixtures/references/valid/inputs.txt")
+            .lines()
+            .chain(include_str!("../tests/fixtures/references/invalid/inputs.txt").lines())
+            .chain(include_str!("../tests/fixtures/references/xfail.txt").lines())
+            .for_each(should_match);
+    }
+
+    #[test]
+    fn matches_edge_cases() {
+        let long = "0".repeat(255);
+        for src in [
+            "",
+            ":",
+            "a:",
+            "a/",
+            "a@",
+            "[::1]",
+            "[::1]/a",
+            "[::1]:5000/a",
+            "A/a",
+            "A:1/a",
+            "A:a",
+            "a_b:1/c",
+            "a_b/c:tag@sha256:0",
+            "a:1a/b",
+            "a@aB+1a:abc",
+            "a@aB:zzzz",
+            "a@1a:zzzz",
+            "a@sha256:abc",
+        ] {
+            should_match(src);
+        }
+        should_match(&long);
+        should_match(&(long.clone() + "0"));
+        should_match(&(String::from("a/") + &long));
+        should_match(&(String::from("a/b:") + &"0".repeat(128)));
+        should_match(&(String::from("a/b:") + &"0".repeat(129)));
+        should_match(&(String::from("a:") + &"0".repeat(129)));
+        should_match(&(String::from("a@") + &"a".repeat(255) + ":0"));
+        should_match(&(String::from("a@") + &"a".repeat(256) + ":0"));
+        should_match(&(String::from("a@a:") + &"0".repeat(1024)));
+        should_match(&(String::from("a@a:") + &"0".repeat(1025)));
+        should_match(&(String::from("a@blake3:") + &"0".repeat(64)));
+        should_match(&(String::from("a@blake3:") + &"0".repeat(63)));
+    }
+
+    /// compare against the span-building parser on pseudo-random inputs assembled
+    /// from fragments that exercise each section of the grammar.
+    #[test]
+    fn matches_random_inputs() {
+        const FRAGMENTS: &[&str] = &[
+            "a",
+            "Z",
+            "0",
+            "9",
+            "_",
+            "__",
+            ".",
+            "-",
+            ":",
+            "/",
+            "@",
+            "[",
+            "]",
+            "::",
+            "[::1]",
+            "host.com",
+            "Host",
+            "a_b",
+            "5000",
+            "tag",
+            "sha256",
+            "sha512",
+            "blake3",
+            "+",
+            "=",
+            "f",
+            "ff",
+            "0123456789abcdef0123456789abcdef",
+            "ABCDEF",
+            "g",
+            " ",
+        ];
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = || {
+            // xorshift64
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+        let mut src = String::new();
+        for _ in 0..200_000 {
+            src.clear();
+            let n = next() % 12;
+            for _ in 0..=n {
+                src.push_str(FRAGMENTS[(next() % FRAGMENTS.len() as u64) as usize]);
+            }
+            should_match(&src);
+        }
+    }
+}